@@ -0,0 +1,701 @@
+//! Plain-Rust reference implementation of the circuits in `encrypted-ixs`.
+//!
+//! Every function here mirrors one `#[instruction]` line for line, with the
+//! `Enc<..>` wrappers stripped: inputs are plaintext and outputs are returned
+//! instead of being re-encrypted or revealed. Keep the two in sync; the tests in
+//! `tests/` check the payout math and that the shared constants agree.
+//!
+//! The program keeps its own copy of the tally, odds, payout and reputation math
+//! (`programs/prediction_markets/src/tally.rs`) for public markets, which settle
+//! in plaintext without the MXE; its tests check that copy against this crate.
+
+// Arcis has no abs_diff/saturating_sub and only fixed-bound index loops; the
+// reference keeps the circuits' exact shape so the two can be diffed side by side
+#![allow(
+    clippy::implicit_saturating_sub,
+    clippy::manual_abs_diff,
+    clippy::needless_range_loop
+)]
+
+// =====================================================================
+// CIRCUIT TYPES
+// =====================================================================
+
+/// Vote data structure for private voting
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VoteData {
+    pub voter: [u8; 32],
+    pub market_id: u64,
+    pub vote_choice: u8, // 0 = No, 1 = Yes, 2 = Skip
+    pub stake_amount: u64,
+    pub predicted_probability: u8, // 0-100
+    pub conviction_score: u16,     // Internal confidence metric
+    pub timestamp: u64,
+    pub nonce: u128, // For replay protection
+}
+
+/// Market voting state for aggregation
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MarketVotingState {
+    pub market_id: u64,
+    pub total_yes_votes: u32,
+    pub total_no_votes: u32,
+    pub total_skip_votes: u32,
+    pub total_yes_stake: u64,
+    pub total_no_stake: u64,
+    pub total_participants: u32,
+    pub weighted_probability_sum: u64, // Sum of stake * probability
+    pub conviction_weighted_yes: u64,
+    pub conviction_weighted_no: u64,
+    pub last_updated: u64,
+    pub brier_score_sum_yes: u64, // Sum of Brier scores if the market resolves Yes
+    pub brier_score_sum_no: u64,  // Sum of Brier scores if the market resolves No
+    pub time_weighted_yes_stake: u64, // Sum of stake * time weight (100-200)
+    pub time_weighted_no_stake: u64,
+    pub herding_aligned_votes: u32, // Votes that sided with the prevailing odds
+    pub herding_counted_votes: u32, // Votes cast while the odds favoured a side
+}
+
+/// Payout calculation data
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PayoutData {
+    pub user: [u8; 32],
+    pub market_id: u64,
+    pub user_stake: u64,
+    pub user_vote: u8,
+    pub user_probability: u8,
+    pub user_conviction: u16,
+    pub user_timestamp: u64,
+    pub market_outcome: u8,    // 0 = No, 1 = Yes
+    pub accuracy_bonus: u64,   // Accuracy pool, shared by all Yes/No voters
+    pub conviction_bonus: u64, // Conviction pool, shared by winners
+}
+
+/// A position's stored vote: VoteData minus the voter field
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PositionVote {
+    pub market_id: u64,
+    pub vote_choice: u8,
+    pub stake_amount: u64,
+    pub predicted_probability: u8,
+    pub conviction_score: u16,
+    pub timestamp: u64,
+    pub nonce: u128,
+}
+
+/// Plaintext pool of a hybrid market's public votes: the fields the odds and
+/// payout math read, which the hybrid circuits take as scalars
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PublicPool {
+    pub total_yes_stake: u64,
+    pub total_no_stake: u64,
+    pub total_participants: u32,
+    pub conviction_weighted_yes: u64,
+    pub conviction_weighted_no: u64,
+    pub brier_score_sum_yes: u64,
+    pub brier_score_sum_no: u64,
+    pub time_weighted_yes_stake: u64,
+    pub time_weighted_no_stake: u64,
+    pub herding_aligned_votes: u32,
+    pub herding_counted_votes: u32,
+}
+
+impl From<&MarketVotingState> for PublicPool {
+    fn from(state: &MarketVotingState) -> Self {
+        PublicPool {
+            total_yes_stake: state.total_yes_stake,
+            total_no_stake: state.total_no_stake,
+            total_participants: state.total_participants,
+            conviction_weighted_yes: state.conviction_weighted_yes,
+            conviction_weighted_no: state.conviction_weighted_no,
+            brier_score_sum_yes: state.brier_score_sum_yes,
+            brier_score_sum_no: state.brier_score_sum_no,
+            time_weighted_yes_stake: state.time_weighted_yes_stake,
+            time_weighted_no_stake: state.time_weighted_no_stake,
+            herding_aligned_votes: state.herding_aligned_votes,
+            herding_counted_votes: state.herding_counted_votes,
+        }
+    }
+}
+
+/// Sealed reputation state stored on UserProfile
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReputationState {
+    pub correct_predictions: u32,
+    pub total_winnings: u64,
+    pub streak_current: u16,
+    pub streak_best: u16,
+    pub reputation_score: u16,
+}
+
+/// Sealed top-N reputation scores, sorted descending
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LeaderboardScores {
+    pub scores: [u16; LEADERBOARD_SIZE],
+}
+
+/// A parlay's pick on each leg (0 = No, 1 = Yes)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ParlayChoices {
+    pub choices: [u8; MAX_PARLAY_LEGS],
+}
+
+/// Revealed market odds: (yes_prob, no_prob, participants, high_confidence, herding_index)
+pub type MarketOdds = (u8, u8, u32, bool, u8);
+
+// =====================================================================
+// SHARED CONSTANTS AND HELPERS
+// =====================================================================
+
+/// Positions re-encrypted per reencrypt_positions computation
+pub const REENCRYPT_BATCH_SIZE: usize = 4;
+
+/// Positions revealed per reveal_positions computation
+pub const REVEAL_BATCH_SIZE: usize = 4;
+
+/// Number of ranked slots on a seasonal leaderboard
+pub const LEADERBOARD_SIZE: usize = 10;
+
+/// Most markets a parlay can combine
+pub const MAX_PARLAY_LEGS: usize = 4;
+
+/// Leg outcome of a cancelled market or an unused slot; the leg is dropped
+pub const PARLAY_LEG_VOID: u8 = 2;
+
+/// Most a parlay pays, in multiples of its stake
+pub const MAX_PARLAY_MULTIPLIER: u64 = 100;
+
+/// Starting reputation for a profile without sealed state yet
+pub const BASE_REPUTATION: u16 = 1000;
+pub const MAX_REPUTATION: u16 = 10000;
+
+/// Maximum allowed distance between a vote's timestamp and the Solana clock (seconds)
+pub const MAX_TIMESTAMP_DRIFT: u64 = 300;
+
+/// Most a market takes in stakes; the program enforces the same cap
+pub const MAX_STAKE: u64 = u64::MAX / 20_000;
+
+/// Minimum number of counted votes before a herding index is reported
+pub const MIN_HERDING_SAMPLE: u32 = 10;
+
+/// Maximum Brier score: a 100% forecast on the realised outcome
+pub const MAX_BRIER_SCORE: u64 = 10000;
+
+/// Time weight in percent: 100 without decay, otherwise from 200 for a vote cast
+/// at market creation down to 100 for one cast at the close
+pub fn time_weight(timestamp: u64, created_at: u64, voting_ends_at: u64, time_decay: bool) -> u64 {
+    let duration = if voting_ends_at > created_at { voting_ends_at - created_at } else { 1 };
+    let remaining = if voting_ends_at > timestamp { voting_ends_at - timestamp } else { 0 };
+    let remaining = remaining.min(duration);
+    if time_decay {
+        100 + (remaining * 100) / duration
+    } else {
+        100
+    }
+}
+
+/// Coarse herding index (0-4) from the share of votes that followed the crowd
+pub fn herding_index(aligned: u32, counted: u32) -> u8 {
+    let alignment = if counted > 0 { (aligned as u64 * 100) / counted as u64 } else { 0 };
+    if counted < MIN_HERDING_SAMPLE || alignment <= 55 {
+        0
+    } else if alignment <= 65 {
+        1
+    } else if alignment <= 75 {
+        2
+    } else if alignment <= 85 {
+        3
+    } else {
+        4
+    }
+}
+
+/// Inverted Brier score on a 0-10000 scale (higher is better calibrated)
+pub fn brier_score(probability: u8, outcome: u8) -> u64 {
+    let target = if outcome == 1 { 100u64 } else { 0u64 };
+    let p = probability as u64;
+    let error = if target > p { target - p } else { p - target };
+    MAX_BRIER_SCORE - error * error
+}
+
+/// Fold a hybrid market's public pool into its sealed voting state; stakes,
+/// weights and score sums are plain sums over votes, so the combined state pays
+/// out exactly as if every vote had been aggregated into one pool
+pub fn combine_pools(state: &MarketVotingState, pool: &PublicPool) -> MarketVotingState {
+    MarketVotingState {
+        market_id: state.market_id,
+        total_yes_votes: state.total_yes_votes,
+        total_no_votes: state.total_no_votes,
+        total_skip_votes: state.total_skip_votes,
+        total_yes_stake: state.total_yes_stake + pool.total_yes_stake,
+        total_no_stake: state.total_no_stake + pool.total_no_stake,
+        total_participants: state.total_participants + pool.total_participants,
+        weighted_probability_sum: state.weighted_probability_sum,
+        conviction_weighted_yes: state.conviction_weighted_yes + pool.conviction_weighted_yes,
+        conviction_weighted_no: state.conviction_weighted_no + pool.conviction_weighted_no,
+        last_updated: state.last_updated,
+        brier_score_sum_yes: state.brier_score_sum_yes + pool.brier_score_sum_yes,
+        brier_score_sum_no: state.brier_score_sum_no + pool.brier_score_sum_no,
+        time_weighted_yes_stake: state.time_weighted_yes_stake + pool.time_weighted_yes_stake,
+        time_weighted_no_stake: state.time_weighted_no_stake + pool.time_weighted_no_stake,
+        herding_aligned_votes: state.herding_aligned_votes + pool.herding_aligned_votes,
+        herding_counted_votes: state.herding_counted_votes + pool.herding_counted_votes,
+    }
+}
+
+/// PayoutData for a position's stored vote, with the plaintext outcome and bonus
+/// pools, as `calculate_payout_v2` and `calculate_payout_hybrid_v1` build it
+pub fn payout_data_for(
+    vote: &VoteData,
+    market_outcome: u8,
+    accuracy_bonus: u64,
+    conviction_bonus: u64,
+) -> PayoutData {
+    PayoutData {
+        user: vote.voter,
+        market_id: vote.market_id,
+        user_stake: vote.stake_amount,
+        user_vote: vote.vote_choice,
+        user_probability: vote.predicted_probability,
+        user_conviction: vote.conviction_score,
+        user_timestamp: vote.timestamp,
+        market_outcome,
+        accuracy_bonus,
+        conviction_bonus,
+    }
+}
+
+// =====================================================================
+// CIRCUITS
+// =====================================================================
+
+/// `submit_private_vote_v1`: 1 if the vote is valid, 0 otherwise
+///
+/// `stake_amount` is the plaintext stake the program recorded for the vote.
+pub fn submit_private_vote(vote: &VoteData, current_time: u64, stake_amount: u64) -> u8 {
+    let mut is_valid = 1u8;
+
+    if vote.vote_choice > 2 {
+        is_valid = 0;
+    }
+    if vote.stake_amount == 0
+        || vote.stake_amount > MAX_STAKE
+        || vote.stake_amount != stake_amount
+    {
+        is_valid = 0;
+    }
+    if vote.predicted_probability > 100 {
+        is_valid = 0;
+    }
+
+    let drift = if vote.timestamp > current_time {
+        vote.timestamp - current_time
+    } else {
+        current_time - vote.timestamp
+    };
+    if vote.timestamp == 0 || drift > MAX_TIMESTAMP_DRIFT {
+        is_valid = 0;
+    }
+
+    if vote.conviction_score == 0 || vote.conviction_score > 1000 {
+        is_valid = 0;
+    }
+    if vote.nonce == 0 {
+        is_valid = 0;
+    }
+
+    is_valid
+}
+
+/// `aggregate_market_votes_v1` / `_v2`: fold a single vote into the market state
+///
+/// `_v2` also returns the accumulator root it was given, unchanged.
+pub fn aggregate_market_votes(
+    vote: &VoteData,
+    current_time: u64,
+    created_at: u64,
+    voting_ends_at: u64,
+    time_decay: bool,
+    current_state: &MarketVotingState,
+) -> MarketVotingState {
+    let mut state = current_state.clone();
+
+    let in_window = vote.timestamp >= created_at && vote.timestamp <= voting_ends_at;
+    let weight = time_weight(vote.timestamp, created_at, voting_ends_at, time_decay);
+
+    let total_weighted = state.time_weighted_yes_stake + state.time_weighted_no_stake;
+    let prevailing_yes = if total_weighted > 0 {
+        (state.time_weighted_yes_stake * 100) / total_weighted
+    } else {
+        50
+    };
+
+    if vote.vote_choice <= 2 && vote.stake_amount > 0 && vote.predicted_probability <= 100 && in_window {
+        if vote.vote_choice != 2 && prevailing_yes != 50 {
+            state.herding_counted_votes += 1;
+            let crowd_says_yes = prevailing_yes > 50;
+            if (vote.vote_choice == 1) == crowd_says_yes {
+                state.herding_aligned_votes += 1;
+            }
+        }
+
+        if vote.vote_choice == 1 {
+            state.total_yes_votes += 1;
+            state.total_yes_stake += vote.stake_amount;
+            state.time_weighted_yes_stake += vote.stake_amount * weight;
+            state.conviction_weighted_yes += vote.conviction_score as u64 * vote.stake_amount;
+        } else if vote.vote_choice == 0 {
+            state.total_no_votes += 1;
+            state.total_no_stake += vote.stake_amount;
+            state.time_weighted_no_stake += vote.stake_amount * weight;
+            state.conviction_weighted_no += vote.conviction_score as u64 * vote.stake_amount;
+        } else if vote.vote_choice == 2 {
+            state.total_skip_votes += 1;
+        }
+
+        if vote.vote_choice != 2 {
+            state.weighted_probability_sum += vote.stake_amount * vote.predicted_probability as u64;
+            state.brier_score_sum_yes += brier_score(vote.predicted_probability, 1);
+            state.brier_score_sum_no += brier_score(vote.predicted_probability, 0);
+        }
+
+        state.total_participants += 1;
+    }
+
+    state.last_updated = current_time;
+
+    state
+}
+
+/// `calculate_payout_v1`: a single user's payout after resolution
+pub fn calculate_payout(
+    payout_data: &PayoutData,
+    created_at: u64,
+    voting_ends_at: u64,
+    time_decay: bool,
+    state: &MarketVotingState,
+) -> u64 {
+    calculate_payout_after_fees(payout_data, 0, created_at, voting_ends_at, time_decay, state)
+}
+
+/// `calculate_payout_v2`: a single user's payout once `fee_amount` is taken off the total stake
+pub fn calculate_payout_after_fees(
+    payout_data: &PayoutData,
+    fee_amount: u64,
+    created_at: u64,
+    voting_ends_at: u64,
+    time_decay: bool,
+    state: &MarketVotingState,
+) -> u64 {
+    let mut final_payout = 0u64;
+
+    let outcome_is_yes = payout_data.market_outcome == 1;
+    let (total_winning_stake, total_losing_stake, winning_conviction, score_sum) = if outcome_is_yes {
+        (state.total_yes_stake, state.total_no_stake, state.conviction_weighted_yes, state.brier_score_sum_yes)
+    } else {
+        (state.total_no_stake, state.total_yes_stake, state.conviction_weighted_no, state.brier_score_sum_no)
+    };
+    let total_winning_weight = if outcome_is_yes {
+        state.time_weighted_yes_stake
+    } else {
+        state.time_weighted_no_stake
+    };
+
+    let total_stake = total_winning_stake + total_losing_stake;
+    let distributable = total_stake - fee_amount.min(total_stake);
+    // With no calibration score to share it by (every forecast maximally wrong),
+    // the accuracy pool stays in the prize pool instead of going unpaid. With no
+    // Yes/No voter on the winning side, the prize and conviction pools have nobody
+    // to go to, so everything is shared out by calibration score instead
+    let has_winners = total_winning_weight > 0;
+    let accuracy_pool = if score_sum == 0 {
+        0
+    } else if has_winners {
+        payout_data.accuracy_bonus.min(distributable)
+    } else {
+        distributable
+    };
+    let conviction_pool = if has_winners {
+        payout_data.conviction_bonus.min(distributable - accuracy_pool)
+    } else {
+        0
+    };
+    let prize_pool = distributable - accuracy_pool - conviction_pool;
+
+    let counted = payout_data.user_vote <= 2
+        && payout_data.user_stake > 0
+        && payout_data.user_probability <= 100
+        && payout_data.user_timestamp >= created_at
+        && payout_data.user_timestamp <= voting_ends_at;
+
+    let user_won = counted && payout_data.user_vote == payout_data.market_outcome;
+
+    if user_won {
+        if total_winning_weight > 0 {
+            let weight = time_weight(payout_data.user_timestamp, created_at, voting_ends_at, time_decay);
+            let user_weight = payout_data.user_stake as u128 * weight as u128;
+            final_payout += ((user_weight * prize_pool as u128) / total_winning_weight as u128) as u64;
+        }
+
+        if winning_conviction > 0 {
+            let user_conviction_weight = payout_data.user_conviction as u128 * payout_data.user_stake as u128;
+            final_payout += ((conviction_pool as u128 * user_conviction_weight) / winning_conviction as u128) as u64;
+        }
+    }
+
+    if counted && payout_data.user_vote != 2 && score_sum > 0 {
+        let score = brier_score(payout_data.user_probability, payout_data.market_outcome);
+        final_payout += ((accuracy_pool as u128 * score as u128) / score_sum as u128) as u64;
+    }
+
+    final_payout
+}
+
+/// `calculate_payout_hybrid_v1` / `calculate_public_payout_v1`: a position's
+/// payout in a hybrid market, after fees, from the sealed and public pools combined
+///
+/// The circuits differ only in whether the vote comes in encrypted and whether
+/// the payout goes out re-encrypted or revealed.
+pub fn calculate_payout_hybrid(
+    payout_data: &PayoutData,
+    fee_amount: u64,
+    created_at: u64,
+    voting_ends_at: u64,
+    time_decay: bool,
+    state: &MarketVotingState,
+    pool: &PublicPool,
+) -> u64 {
+    calculate_payout_after_fees(
+        payout_data,
+        fee_amount,
+        created_at,
+        voting_ends_at,
+        time_decay,
+        &combine_pools(state, pool),
+    )
+}
+
+/// `calculate_market_odds_v1`: revealed odds, spread widened by the herding index
+pub fn calculate_market_odds(state: &MarketVotingState) -> MarketOdds {
+    let herding = herding_index(state.herding_aligned_votes, state.herding_counted_votes);
+
+    let total_stake = state.total_yes_stake + state.total_no_stake;
+    let total_weighted = state.time_weighted_yes_stake + state.time_weighted_no_stake;
+
+    let (yes_probability, no_probability, high_confidence) = if total_weighted > 0 {
+        let yes_prob = (state.time_weighted_yes_stake * 100) / total_weighted;
+        let no_prob = (state.time_weighted_no_stake * 100) / total_weighted;
+
+        let liquidity_factor: u64 = if total_stake > 10000 {
+            95
+        } else if total_stake > 1000 {
+            90
+        } else {
+            85
+        };
+        let liquidity_factor = liquidity_factor - herding as u64 * 2;
+
+        let adjusted_yes = (yes_prob * liquidity_factor) / 100;
+        let adjusted_no = (no_prob * liquidity_factor) / 100;
+
+        (adjusted_yes as u8, adjusted_no as u8, total_stake > 1000)
+    } else {
+        (50u8, 50u8, false)
+    };
+
+    (yes_probability, no_probability, state.total_participants, high_confidence, herding)
+}
+
+/// `calculate_market_odds_hybrid_v1`: revealed odds over a hybrid market's
+/// sealed and public pools combined
+pub fn calculate_market_odds_hybrid(state: &MarketVotingState, pool: &PublicPool) -> MarketOdds {
+    calculate_market_odds(&combine_pools(state, pool))
+}
+
+/// `detect_manipulation`: 0-100 suspicion score for a pair of votes
+pub fn detect_manipulation(vote1: &VoteData, vote2: &VoteData) -> u8 {
+    let mut suspicious_patterns = 0u8;
+
+    let time_diff = if vote1.timestamp > vote2.timestamp {
+        vote1.timestamp - vote2.timestamp
+    } else {
+        vote2.timestamp - vote1.timestamp
+    };
+
+    if time_diff < 5 {
+        suspicious_patterns += 1;
+    }
+    if vote1.predicted_probability == vote2.predicted_probability {
+        suspicious_patterns += 1;
+    }
+    if vote1.conviction_score == vote2.conviction_score {
+        suspicious_patterns += 1;
+    }
+    if vote1.vote_choice == vote2.vote_choice
+        && vote1.stake_amount == vote2.stake_amount
+        && suspicious_patterns >= 2
+    {
+        suspicious_patterns += 1;
+    }
+
+    (suspicious_patterns * 25).min(100)
+}
+
+/// `update_reputation_v1`: fold a resolved position into the sealed reputation
+pub fn update_reputation(
+    vote: &VoteData,
+    payout: u64,
+    market_outcome: u8,
+    sealed: Option<&ReputationState>,
+) -> ReputationState {
+    let mut reputation = match sealed {
+        Some(state) => state.clone(),
+        None => ReputationState {
+            correct_predictions: 0,
+            total_winnings: 0,
+            streak_current: 0,
+            streak_best: 0,
+            reputation_score: BASE_REPUTATION,
+        },
+    };
+
+    if vote.vote_choice != 2 {
+        if vote.vote_choice == market_outcome {
+            reputation.correct_predictions += 1;
+            reputation.total_winnings += payout;
+            reputation.streak_current += 1;
+            if reputation.streak_current > reputation.streak_best {
+                reputation.streak_best = reputation.streak_current;
+            }
+            let gain = (10 + reputation.streak_current * 2).min(50);
+            reputation.reputation_score = (reputation.reputation_score + gain).min(MAX_REPUTATION);
+        } else {
+            reputation.streak_current = 0;
+            reputation.reputation_score = if reputation.reputation_score > 20 {
+                reputation.reputation_score - 20
+            } else {
+                0
+            };
+        }
+    }
+
+    reputation
+}
+
+/// `insert_leaderboard_entry`: the updated board and the revealed slot
+/// (LEADERBOARD_SIZE if the score didn't make the board)
+pub fn insert_leaderboard_entry(
+    reputation: &ReputationState,
+    sealed: Option<&LeaderboardScores>,
+) -> (LeaderboardScores, u8) {
+    let board = match sealed {
+        Some(board) => board.clone(),
+        None => LeaderboardScores::default(),
+    };
+    let score = reputation.reputation_score;
+
+    let mut slot = 0u8;
+    for i in 0..LEADERBOARD_SIZE {
+        if board.scores[i] >= score {
+            slot += 1;
+        }
+    }
+
+    let mut updated = LeaderboardScores::default();
+    for i in 0..LEADERBOARD_SIZE {
+        let idx = i as u8;
+        let previous = if i == 0 { 0 } else { board.scores[i - 1] };
+        updated.scores[i] = if idx < slot {
+            board.scores[i]
+        } else if idx == slot {
+            score
+        } else {
+            previous
+        };
+    }
+
+    (updated, slot)
+}
+
+/// `reveal_leaderboard`: final scores rounded down to the nearest 10
+pub fn reveal_leaderboard(board: &LeaderboardScores) -> [u16; LEADERBOARD_SIZE] {
+    let mut rounded = [0u16; LEADERBOARD_SIZE];
+    for i in 0..LEADERBOARD_SIZE {
+        rounded[i] = (board.scores[i] / 10) * 10;
+    }
+    rounded
+}
+
+/// `reencrypt_market_state_v1`: re-sealing leaves the state unchanged
+pub fn reencrypt_market_state(state: &MarketVotingState) -> MarketVotingState {
+    state.clone()
+}
+
+/// `reencrypt_positions_v1`: re-encrypting leaves every vote unchanged
+pub fn reencrypt_positions(
+    votes: &[PositionVote; REENCRYPT_BATCH_SIZE],
+) -> [PositionVote; REENCRYPT_BATCH_SIZE] {
+    votes.clone()
+}
+
+/// `disclose_vote_v1`: the recipient receives the vote unchanged
+pub fn disclose_vote(vote: &PositionVote) -> PositionVote {
+    vote.clone()
+}
+
+/// Revealed votes: (vote_choice, predicted_probability, conviction_score) per slot
+pub type RevealedVotes = (
+    [u8; REVEAL_BATCH_SIZE],
+    [u8; REVEAL_BATCH_SIZE],
+    [u16; REVEAL_BATCH_SIZE],
+);
+
+/// `reveal_votes_v1`: choice, probability and conviction of each vote in the batch
+pub fn reveal_votes(votes: &[PositionVote; REVEAL_BATCH_SIZE]) -> RevealedVotes {
+    let mut choices = [0u8; REVEAL_BATCH_SIZE];
+    let mut probabilities = [0u8; REVEAL_BATCH_SIZE];
+    let mut convictions = [0u16; REVEAL_BATCH_SIZE];
+    for i in 0..REVEAL_BATCH_SIZE {
+        choices[i] = votes[i].vote_choice;
+        probabilities[i] = votes[i].predicted_probability;
+        convictions[i] = votes[i].conviction_score;
+    }
+    (choices, probabilities, convictions)
+}
+
+/// `calculate_parlay_payout_v1`: the stake multiplied by each leg's decimal odds
+/// (100 over the picked side's revealed probability, floored at 1%), or 0 unless
+/// every leg was picked right. Void legs count at even odds, and the payout is
+/// capped at MAX_PARLAY_MULTIPLIER times the stake
+pub fn calculate_parlay_payout(
+    picks: &ParlayChoices,
+    stake_amount: u64,
+    outcomes: &[u8; MAX_PARLAY_LEGS],
+    odds_yes: &[u8; MAX_PARLAY_LEGS],
+    odds_no: &[u8; MAX_PARLAY_LEGS],
+) -> u64 {
+    let mut payout = stake_amount as u128;
+    let mut won = true;
+    for i in 0..MAX_PARLAY_LEGS {
+        if outcomes[i] != PARLAY_LEG_VOID {
+            let pick = picks.choices[i];
+            let odds = if pick == 1 { odds_yes[i] } else { odds_no[i] };
+            let odds = if odds == 0 { 1 } else { odds };
+            payout = (payout * 100) / odds as u128;
+            if pick != outcomes[i] {
+                won = false;
+            }
+        }
+    }
+
+    let max_payout = stake_amount as u128 * MAX_PARLAY_MULTIPLIER as u128;
+    let payout = if payout > max_payout { max_payout } else { payout };
+
+    if !won {
+        0
+    } else if payout > u64::MAX as u128 {
+        u64::MAX
+    } else {
+        payout as u64
+    }
+}
@@ -0,0 +1,224 @@
+//! Checks that the reference stays in step with `encrypted-ixs/src/lib.rs`:
+//! every circuit has a reference counterpart and the shared constants match.
+
+use circuits_reference::*;
+
+const CIRCUITS_SRC: &str = include_str!("../../encrypted-ixs/src/lib.rs");
+
+/// Circuit name -> reference function name
+const REFERENCE_IMPLS: &[(&str, &str)] = &[
+    ("submit_private_vote_v1", "submit_private_vote"),
+    ("aggregate_market_votes_v1", "aggregate_market_votes"),
+    ("aggregate_market_votes_v2", "aggregate_market_votes"),
+    ("calculate_payout_v1", "calculate_payout"),
+    ("calculate_payout_v2", "calculate_payout_after_fees"),
+    ("calculate_market_odds_v1", "calculate_market_odds"),
+    ("calculate_payout_hybrid_v1", "calculate_payout_hybrid"),
+    ("calculate_public_payout_v1", "calculate_payout_hybrid"),
+    ("calculate_market_odds_hybrid_v1", "calculate_market_odds_hybrid"),
+    ("detect_manipulation", "detect_manipulation"),
+    ("update_reputation_v1", "update_reputation"),
+    ("insert_leaderboard_entry", "insert_leaderboard_entry"),
+    ("reveal_leaderboard", "reveal_leaderboard"),
+    ("reencrypt_market_state_v1", "reencrypt_market_state"),
+    ("reencrypt_positions_v1", "reencrypt_positions"),
+    ("disclose_vote_v1", "disclose_vote"),
+    ("reveal_votes_v1", "reveal_votes"),
+    ("calculate_parlay_payout_v1", "calculate_parlay_payout"),
+];
+
+fn circuit_names() -> Vec<String> {
+    let mut names = Vec::new();
+    let mut lines = CIRCUITS_SRC.lines();
+    while let Some(line) = lines.next() {
+        if line.trim() != "#[instruction]" {
+            continue;
+        }
+        let signature = lines.next().expect("#[instruction] without a function");
+        let name = signature
+            .trim()
+            .strip_prefix("pub fn ")
+            .and_then(|rest| rest.split('(').next())
+            .expect("#[instruction] must be followed by `pub fn`");
+        names.push(name.to_string());
+    }
+    names
+}
+
+fn circuit_const(name: &str) -> String {
+    let prefix = format!("const {name}:");
+    let line = CIRCUITS_SRC
+        .lines()
+        .map(str::trim)
+        .find(|line| line.starts_with(&prefix))
+        .unwrap_or_else(|| panic!("constant {name} not found in circuits"));
+    line.split('=')
+        .nth(1)
+        .unwrap()
+        .trim()
+        .trim_end_matches(';')
+        .to_string()
+}
+
+#[test]
+fn every_circuit_has_a_reference_implementation() {
+    let mut circuits = circuit_names();
+    circuits.sort();
+    let mut covered: Vec<String> = REFERENCE_IMPLS.iter().map(|(c, _)| c.to_string()).collect();
+    covered.sort();
+    assert_eq!(circuits, covered);
+}
+
+#[test]
+fn shared_constants_match_circuits() {
+    assert_eq!(circuit_const("LEADERBOARD_SIZE"), LEADERBOARD_SIZE.to_string());
+    assert_eq!(circuit_const("REENCRYPT_BATCH_SIZE"), REENCRYPT_BATCH_SIZE.to_string());
+    assert_eq!(circuit_const("REVEAL_BATCH_SIZE"), REVEAL_BATCH_SIZE.to_string());
+    assert_eq!(circuit_const("MAX_PARLAY_LEGS"), MAX_PARLAY_LEGS.to_string());
+    assert_eq!(circuit_const("PARLAY_LEG_VOID"), PARLAY_LEG_VOID.to_string());
+    assert_eq!(circuit_const("MAX_PARLAY_MULTIPLIER"), MAX_PARLAY_MULTIPLIER.to_string());
+    assert_eq!(circuit_const("BASE_REPUTATION"), BASE_REPUTATION.to_string());
+    assert_eq!(circuit_const("MAX_REPUTATION"), MAX_REPUTATION.to_string());
+    assert_eq!(circuit_const("MAX_TIMESTAMP_DRIFT"), MAX_TIMESTAMP_DRIFT.to_string());
+    assert_eq!(circuit_const("MAX_STAKE"), MAX_STAKE.to_string());
+    assert_eq!(circuit_const("MIN_HERDING_SAMPLE"), MIN_HERDING_SAMPLE.to_string());
+    assert_eq!(circuit_const("MAX_BRIER_SCORE"), MAX_BRIER_SCORE.to_string());
+}
+
+#[test]
+fn brier_score_matches_circuit_scale() {
+    assert_eq!(brier_score(100, 1), MAX_BRIER_SCORE);
+    assert_eq!(brier_score(0, 0), MAX_BRIER_SCORE);
+    assert_eq!(brier_score(0, 1), 0);
+    assert_eq!(brier_score(50, 1), 7500);
+    assert_eq!(brier_score(70, 0), 5100);
+}
+
+#[test]
+fn time_weight_decays_from_double_to_single() {
+    assert_eq!(time_weight(1_000, 1_000, 2_000, true), 200);
+    assert_eq!(time_weight(1_500, 1_000, 2_000, true), 150);
+    assert_eq!(time_weight(2_000, 1_000, 2_000, true), 100);
+    assert_eq!(time_weight(1_000, 1_000, 2_000, false), 100);
+}
+
+#[test]
+fn submit_private_vote_rejects_stale_timestamps() {
+    let vote = VoteData {
+        vote_choice: 1,
+        stake_amount: 10,
+        predicted_probability: 60,
+        conviction_score: 500,
+        timestamp: 1_000,
+        nonce: 7,
+        ..Default::default()
+    };
+    assert_eq!(submit_private_vote(&vote, 1_000 + MAX_TIMESTAMP_DRIFT, 10), 1);
+    assert_eq!(submit_private_vote(&vote, 1_001 + MAX_TIMESTAMP_DRIFT, 10), 0);
+    assert_eq!(submit_private_vote(&VoteData { vote_choice: 3, ..vote.clone() }, 1_000, 10), 0);
+    assert_eq!(submit_private_vote(&VoteData { nonce: 0, ..vote }, 1_000, 10), 0);
+}
+
+#[test]
+fn submit_private_vote_binds_the_sealed_stake() {
+    let vote = VoteData {
+        vote_choice: 1,
+        stake_amount: 10,
+        predicted_probability: 60,
+        conviction_score: 500,
+        timestamp: 1_000,
+        nonce: 7,
+        ..Default::default()
+    };
+    assert_eq!(submit_private_vote(&vote, 1_000, 10), 1);
+    assert_eq!(submit_private_vote(&vote, 1_000, 1), 0);
+    let oversized = VoteData { stake_amount: MAX_STAKE + 1, ..vote };
+    assert_eq!(submit_private_vote(&oversized, 1_000, MAX_STAKE + 1), 0);
+}
+
+#[test]
+fn payout_data_for_maps_the_stored_vote() {
+    let vote = VoteData {
+        market_id: 3,
+        vote_choice: 1,
+        stake_amount: 10,
+        predicted_probability: 60,
+        conviction_score: 500,
+        timestamp: 1_000,
+        nonce: 7,
+        ..Default::default()
+    };
+    let expected = PayoutData {
+        market_id: 3,
+        user_stake: 10,
+        user_vote: 1,
+        user_probability: 60,
+        user_conviction: 500,
+        user_timestamp: 1_000,
+        market_outcome: 0,
+        accuracy_bonus: 20,
+        conviction_bonus: 30,
+        ..Default::default()
+    };
+    assert_eq!(payout_data_for(&vote, 0, 20, 30), expected);
+}
+
+#[test]
+fn odds_widen_spread_when_herding() {
+    let state = MarketVotingState {
+        total_yes_stake: 30_000,
+        total_no_stake: 10_000,
+        time_weighted_yes_stake: 3_000_000,
+        time_weighted_no_stake: 1_000_000,
+        total_participants: 20,
+        ..Default::default()
+    };
+    assert_eq!(calculate_market_odds(&state), (71, 23, 20, true, 0));
+
+    let herding = MarketVotingState {
+        herding_aligned_votes: 19,
+        herding_counted_votes: 20,
+        ..state
+    };
+    assert_eq!(calculate_market_odds(&herding), (65, 21, 20, true, 4));
+}
+
+#[test]
+fn detect_manipulation_flags_identical_votes() {
+    let vote = VoteData {
+        vote_choice: 1,
+        stake_amount: 10,
+        predicted_probability: 60,
+        conviction_score: 500,
+        timestamp: 1_000,
+        ..Default::default()
+    };
+    assert_eq!(detect_manipulation(&vote, &vote), 100);
+    let independent = VoteData {
+        predicted_probability: 20,
+        conviction_score: 10,
+        timestamp: 5_000,
+        ..vote.clone()
+    };
+    assert_eq!(detect_manipulation(&vote, &independent), 0);
+}
+
+#[test]
+fn parlay_multiplies_leg_odds() {
+    let picks = ParlayChoices { choices: [1, 0, 1, 0] };
+    let odds_yes = [50, 20, 0, 0];
+    let odds_no = [40, 80, 0, 0];
+    let void = PARLAY_LEG_VOID;
+
+    // 2x on the first leg, 1.25x on the second, unused slots dropped
+    let outcomes = [1, 0, void, void];
+    assert_eq!(calculate_parlay_payout(&picks, 1_000, &outcomes, &odds_yes, &odds_no), 2_500);
+    // One miss loses the lot
+    let outcomes = [1, 1, void, void];
+    assert_eq!(calculate_parlay_payout(&picks, 1_000, &outcomes, &odds_yes, &odds_no), 0);
+    // A side nobody backed is priced at 1%, which takes this one past the cap
+    let outcomes = [1, 0, 1, void];
+    let capped = 1_000 * MAX_PARLAY_MULTIPLIER;
+    assert_eq!(calculate_parlay_payout(&picks, 1_000, &outcomes, &odds_yes, &odds_no), capped);
+    assert_eq!(calculate_parlay_payout(&picks, u64::MAX, &outcomes, &odds_yes, &odds_no), u64::MAX);
+}
@@ -0,0 +1,318 @@
+//! Property tests over the reference circuits.
+
+use circuits_reference::*;
+use proptest::prelude::*;
+
+const CREATED_AT: u64 = 1_700_000_000;
+const VOTING_ENDS_AT: u64 = CREATED_AT + 7 * 24 * 60 * 60;
+
+fn vote_strategy() -> impl Strategy<Value = VoteData> {
+    (
+        0u8..=2,
+        1u64..=1_000_000_000_000,
+        0u8..=100,
+        1u16..=1000,
+        CREATED_AT..=VOTING_ENDS_AT,
+    )
+        .prop_map(|(vote_choice, stake_amount, predicted_probability, conviction_score, timestamp)| VoteData {
+            voter: [0; 32],
+            market_id: 1,
+            vote_choice,
+            stake_amount,
+            predicted_probability,
+            conviction_score,
+            timestamp,
+            nonce: 1,
+        })
+}
+
+fn aggregate_all(votes: &[VoteData], time_decay: bool) -> MarketVotingState {
+    votes.iter().fold(MarketVotingState::default(), |state, vote| {
+        aggregate_market_votes(vote, VOTING_ENDS_AT, CREATED_AT, VOTING_ENDS_AT, time_decay, &state)
+    })
+}
+
+/// Payouts for every vote, with bonus pools and fees derived from the plaintext
+/// total stake the same way `calculate_user_payout` does on-chain
+fn payouts(
+    votes: &[VoteData],
+    outcome: u8,
+    time_decay: bool,
+    bonuses: bool,
+    fee_bps: u64,
+) -> (MarketVotingState, Vec<u64>) {
+    let state = aggregate_all(votes, time_decay);
+    let market_total_stake: u64 = votes.iter().map(|vote| vote.stake_amount).sum();
+    let fee_amount = (market_total_stake as u128 * fee_bps as u128 / 10_000) as u64;
+    let (accuracy_bonus, conviction_bonus) = if bonuses {
+        (market_total_stake / 100, market_total_stake / 200)
+    } else {
+        (0, 0)
+    };
+
+    let amounts = votes
+        .iter()
+        .map(|vote| {
+            let data = PayoutData {
+                user: vote.voter,
+                market_id: vote.market_id,
+                user_stake: vote.stake_amount,
+                user_vote: vote.vote_choice,
+                user_probability: vote.predicted_probability,
+                user_conviction: vote.conviction_score,
+                user_timestamp: vote.timestamp,
+                market_outcome: outcome,
+                accuracy_bonus,
+                conviction_bonus,
+            };
+            calculate_payout_after_fees(&data, fee_amount, CREATED_AT, VOTING_ENDS_AT, time_decay, &state)
+        })
+        .collect();
+
+    (state, amounts)
+}
+
+#[test]
+fn unscored_accuracy_pool_stays_with_the_winners() {
+    // A lone 0% forecast on the side that won scores 0, so nobody can claim the
+    // accuracy pool; it must be paid out as prize money instead
+    let vote = VoteData {
+        market_id: 1,
+        vote_choice: 1,
+        stake_amount: 400,
+        predicted_probability: 0,
+        conviction_score: 1,
+        timestamp: CREATED_AT,
+        nonce: 1,
+        ..Default::default()
+    };
+    let (_, amounts) = payouts(&[vote], 1, false, true, 0);
+    assert_eq!(amounts, vec![400]);
+}
+
+#[test]
+fn pools_without_winners_go_by_calibration() {
+    // Nobody backed Yes, so the prize and conviction pools have no winner to go
+    // to; the whole pool is shared by Brier score (3600 and 6400 out of 10000)
+    let vote = |stake_amount, predicted_probability| VoteData {
+        market_id: 1,
+        vote_choice: 0,
+        stake_amount,
+        predicted_probability,
+        conviction_score: 1,
+        timestamp: CREATED_AT,
+        nonce: 1,
+        ..Default::default()
+    };
+    let (_, amounts) = payouts(&[vote(300, 20), vote(700, 40)], 1, false, true, 0);
+    assert_eq!(amounts, vec![360, 640]);
+}
+
+proptest! {
+    #[test]
+    fn payouts_never_exceed_the_pool(
+        votes in prop::collection::vec(vote_strategy(), 1..40),
+        outcome in 0u8..=1,
+        time_decay in any::<bool>(),
+    ) {
+        let (state, amounts) = payouts(&votes, outcome, time_decay, true, 0);
+        let pool = state.total_yes_stake as u128 + state.total_no_stake as u128;
+        let paid: u128 = amounts.iter().map(|&amount| amount as u128).sum();
+        prop_assert!(paid <= pool, "paid {} from a pool of {}", paid, pool);
+    }
+
+    #[test]
+    fn payouts_lose_at_most_rounding_dust(
+        votes in prop::collection::vec(vote_strategy(), 1..40),
+        outcome in 0u8..=1,
+        time_decay in any::<bool>(),
+    ) {
+        let (state, amounts) = payouts(&votes, outcome, time_decay, true, 0);
+        let winning_stake = if outcome == 1 { state.total_yes_stake } else { state.total_no_stake };
+        prop_assume!(winning_stake > 0);
+
+        // Each of the three pools loses < 1 unit per recipient to integer division
+        let pool = state.total_yes_stake as u128 + state.total_no_stake as u128;
+        let paid: u128 = amounts.iter().map(|&amount| amount as u128).sum();
+        prop_assert!(pool - paid <= 3 * votes.len() as u128);
+    }
+
+    #[test]
+    fn fees_come_out_of_the_pool_before_payouts(
+        votes in prop::collection::vec(vote_strategy(), 1..40),
+        outcome in 0u8..=1,
+        time_decay in any::<bool>(),
+        fee_bps in 0u64..=2_000,
+    ) {
+        let (state, amounts) = payouts(&votes, outcome, time_decay, true, fee_bps);
+        let winning_stake = if outcome == 1 { state.total_yes_stake } else { state.total_no_stake };
+        prop_assume!(winning_stake > 0);
+
+        // The fee is charged on the plaintext total stake, abstentions included
+        let pool = state.total_yes_stake as u128 + state.total_no_stake as u128;
+        let market_total_stake: u128 = votes.iter().map(|vote| vote.stake_amount as u128).sum();
+        let fee = (market_total_stake * fee_bps as u128 / 10_000).min(pool);
+        let paid: u128 = amounts.iter().map(|&amount| amount as u128).sum();
+        prop_assert!(paid + fee <= pool, "paid {} plus fee {} from a pool of {}", paid, fee, pool);
+        prop_assert!(pool - fee - paid <= 3 * votes.len() as u128);
+    }
+
+    #[test]
+    fn hybrid_pools_pay_out_as_one(
+        votes in prop::collection::vec((vote_strategy(), any::<bool>()), 1..40),
+        outcome in 0u8..=1,
+        time_decay in any::<bool>(),
+        fee_bps in 0u64..=2_000,
+    ) {
+        // Splitting a market's votes between the sealed and public pools must not
+        // change anyone's payout
+        let all: Vec<VoteData> = votes.iter().map(|(vote, _)| vote.clone()).collect();
+        let (_, expected) = payouts(&all, outcome, time_decay, true, fee_bps);
+
+        let sealed: Vec<VoteData> = votes.iter().filter(|(_, public)| !public).map(|(vote, _)| vote.clone()).collect();
+        let public: Vec<VoteData> = votes.iter().filter(|(_, public)| *public).map(|(vote, _)| vote.clone()).collect();
+        let state = aggregate_all(&sealed, time_decay);
+        let pool = PublicPool::from(&aggregate_all(&public, time_decay));
+
+        let market_total_stake: u64 = all.iter().map(|vote| vote.stake_amount).sum();
+        let fee_amount = (market_total_stake as u128 * fee_bps as u128 / 10_000) as u64;
+        for (vote, expected) in all.iter().zip(expected) {
+            let data = PayoutData {
+                user: vote.voter,
+                market_id: vote.market_id,
+                user_stake: vote.stake_amount,
+                user_vote: vote.vote_choice,
+                user_probability: vote.predicted_probability,
+                user_conviction: vote.conviction_score,
+                user_timestamp: vote.timestamp,
+                market_outcome: outcome,
+                accuracy_bonus: market_total_stake / 100,
+                conviction_bonus: market_total_stake / 200,
+            };
+            let amount = calculate_payout_hybrid(&data, fee_amount, CREATED_AT, VOTING_ENDS_AT, time_decay, &state, &pool);
+            prop_assert_eq!(amount, expected);
+        }
+
+        let (_, _, participants, _, _) = calculate_market_odds_hybrid(&state, &pool);
+        prop_assert_eq!(participants as usize, votes.len());
+    }
+
+    #[test]
+    fn parlays_pay_only_when_every_leg_hits(
+        stake_amount in 1u64..=1_000_000_000_000,
+        legs in prop::collection::vec((0u8..=1, 0u8..=2, 0u8..=100, 0u8..=100), MAX_PARLAY_LEGS),
+    ) {
+        let mut picks = ParlayChoices::default();
+        let mut outcomes = [0u8; MAX_PARLAY_LEGS];
+        let mut odds_yes = [0u8; MAX_PARLAY_LEGS];
+        let mut odds_no = [0u8; MAX_PARLAY_LEGS];
+        for (i, &(pick, outcome, yes, no)) in legs.iter().enumerate() {
+            picks.choices[i] = pick;
+            outcomes[i] = outcome;
+            odds_yes[i] = yes;
+            odds_no[i] = no;
+        }
+
+        let payout = calculate_parlay_payout(&picks, stake_amount, &outcomes, &odds_yes, &odds_no);
+        let all_hit = (0..MAX_PARLAY_LEGS).all(|i| outcomes[i] == PARLAY_LEG_VOID || outcomes[i] == picks.choices[i]);
+        if all_hit {
+            // Odds are probabilities of at most 100%, so a winning parlay never loses
+            // money, and the pool never owes more than it reserved for it
+            prop_assert!(payout >= stake_amount);
+            prop_assert!(payout <= stake_amount * MAX_PARLAY_MULTIPLIER);
+        } else {
+            prop_assert_eq!(payout, 0);
+        }
+    }
+
+    #[test]
+    fn without_bonuses_or_decay_winners_split_pro_rata(
+        votes in prop::collection::vec(vote_strategy(), 1..40),
+        outcome in 0u8..=1,
+    ) {
+        let (state, amounts) = payouts(&votes, outcome, false, false, 0);
+        let winning_stake = if outcome == 1 { state.total_yes_stake } else { state.total_no_stake };
+        // With no winners the pool is shared by calibration instead
+        prop_assume!(winning_stake > 0);
+        let total_stake = state.total_yes_stake as u128 + state.total_no_stake as u128;
+
+        for (vote, amount) in votes.iter().zip(amounts) {
+            let expected = if vote.vote_choice == outcome {
+                (vote.stake_amount as u128 * total_stake / winning_stake as u128) as u64
+            } else {
+                0
+            };
+            prop_assert_eq!(amount, expected);
+        }
+    }
+
+    #[test]
+    fn better_calibration_never_earns_a_smaller_accuracy_bonus(
+        votes in prop::collection::vec(vote_strategy(), 1..20),
+        outcome in 0u8..=1,
+        p1 in 0u8..=100,
+        p2 in 0u8..=100,
+    ) {
+        // Losing votes only earn the accuracy bonus, so compare two losers
+        let state = aggregate_all(&votes, false);
+        let loser = |probability: u8| PayoutData {
+            user_stake: 1,
+            user_vote: 1 - outcome,
+            user_probability: probability,
+            user_conviction: 1,
+            user_timestamp: CREATED_AT,
+            market_outcome: outcome,
+            accuracy_bonus: 1_000_000,
+            ..Default::default()
+        };
+        let a = calculate_payout(&loser(p1), CREATED_AT, VOTING_ENDS_AT, false, &state);
+        let b = calculate_payout(&loser(p2), CREATED_AT, VOTING_ENDS_AT, false, &state);
+        if brier_score(p1, outcome) >= brier_score(p2, outcome) {
+            prop_assert!(a >= b);
+        } else {
+            prop_assert!(a <= b);
+        }
+    }
+
+    #[test]
+    fn odds_never_exceed_one_hundred_percent(votes in prop::collection::vec(vote_strategy(), 0..40)) {
+        let (yes, no, participants, _, herding) = calculate_market_odds(&aggregate_all(&votes, true));
+        prop_assert!(yes as u16 + no as u16 <= 100);
+        prop_assert!(herding <= 4);
+        prop_assert_eq!(participants as usize, votes.len());
+    }
+
+    #[test]
+    fn leaderboard_keeps_the_top_scores_sorted(scores in prop::collection::vec(0u16..=MAX_REPUTATION, 1..30)) {
+        let mut board: Option<LeaderboardScores> = None;
+        for &score in &scores {
+            let reputation = ReputationState { reputation_score: score, ..Default::default() };
+            let (updated, slot) = insert_leaderboard_entry(&reputation, board.as_ref());
+            if (slot as usize) < LEADERBOARD_SIZE {
+                prop_assert_eq!(updated.scores[slot as usize], score);
+            }
+            board = Some(updated);
+        }
+
+        let mut expected = scores.clone();
+        expected.sort_unstable_by(|a, b| b.cmp(a));
+        expected.resize(LEADERBOARD_SIZE, 0);
+        prop_assert_eq!(board.unwrap().scores.to_vec(), expected);
+    }
+
+    #[test]
+    fn reputation_streaks_are_consistent(results in prop::collection::vec(any::<bool>(), 1..50)) {
+        let mut reputation: Option<ReputationState> = None;
+        for &won in &results {
+            let vote = VoteData { vote_choice: if won { 1 } else { 0 }, ..Default::default() };
+            reputation = Some(update_reputation(&vote, 10, 1, reputation.as_ref()));
+        }
+
+        let reputation = reputation.unwrap();
+        let wins = results.iter().filter(|&&won| won).count() as u32;
+        prop_assert_eq!(reputation.correct_predictions, wins);
+        prop_assert_eq!(reputation.total_winnings, 10 * wins as u64);
+        prop_assert!(reputation.streak_current <= reputation.streak_best);
+        prop_assert!(reputation.reputation_score <= MAX_REPUTATION);
+    }
+}
@@ -33,6 +33,12 @@ mod circuits {
         pub conviction_weighted_yes: u64,
         pub conviction_weighted_no: u64,
         pub last_updated: u64,
+        pub brier_score_sum_yes: u64, // Sum of Brier scores if the market resolves Yes
+        pub brier_score_sum_no: u64,  // Sum of Brier scores if the market resolves No
+        pub time_weighted_yes_stake: u64, // Sum of stake * time weight (100-200)
+        pub time_weighted_no_stake: u64,
+        pub herding_aligned_votes: u32, // Votes that sided with the prevailing odds
+        pub herding_counted_votes: u32, // Votes cast while the odds favoured a side
     }
 
     // Payout calculation data - Remove #[derive(ArcisType)]
@@ -43,18 +49,165 @@ mod circuits {
         pub user_vote: u8,
         pub user_probability: u8,
         pub user_conviction: u16,
+        pub user_timestamp: u64,
         pub market_outcome: u8, // 0 = No, 1 = Yes
-        pub total_winning_stake: u64,
-        pub total_losing_stake: u64,
-        pub accuracy_bonus: u64,
-        pub conviction_bonus: u64,
+        pub accuracy_bonus: u64,   // Accuracy pool, shared by all Yes/No voters
+        pub conviction_bonus: u64, // Conviction pool, shared by winners
     }
 
-    // Private vote validation and submission
+    // Sealed reputation state stored on UserProfile - Remove #[derive(ArcisType)]
+    pub struct ReputationState {
+        pub correct_predictions: u32,
+        pub total_winnings: u64,
+        pub streak_current: u16,
+        pub streak_best: u16,
+        pub reputation_score: u16,
+    }
+
+    // A position's stored vote: VoteData minus the voter field, which the program
+    // derives from the signer - Remove #[derive(ArcisType)]
+    pub struct PositionVote {
+        pub market_id: u64,
+        pub vote_choice: u8,
+        pub stake_amount: u64,
+        pub predicted_probability: u8,
+        pub conviction_score: u16,
+        pub timestamp: u64,
+        pub nonce: u128,
+    }
+
+    // Plaintext pool of a hybrid market's public votes (PublicTally on-chain): the
+    // fields the odds and payout math read, passed to the hybrid circuits as scalars
+    pub struct PublicPool {
+        pub total_yes_stake: u64,
+        pub total_no_stake: u64,
+        pub total_participants: u32,
+        pub conviction_weighted_yes: u64,
+        pub conviction_weighted_no: u64,
+        pub brier_score_sum_yes: u64,
+        pub brier_score_sum_no: u64,
+        pub time_weighted_yes_stake: u64,
+        pub time_weighted_no_stake: u64,
+        pub herding_aligned_votes: u32,
+        pub herding_counted_votes: u32,
+    }
+
+    // Positions re-encrypted per reencrypt_positions computation
+    const REENCRYPT_BATCH_SIZE: usize = 4;
+
+    // Positions revealed per reveal_positions computation
+    const REVEAL_BATCH_SIZE: usize = 4;
+
+    // Number of ranked slots on a seasonal leaderboard
+    const LEADERBOARD_SIZE: usize = 10;
+
+    // Most markets a parlay can combine
+    const MAX_PARLAY_LEGS: usize = 4;
+
+    // Leg outcome of a cancelled market or an unused slot; the leg is dropped
+    const PARLAY_LEG_VOID: u8 = 2;
+
+    // Most a parlay pays, in multiples of its stake; the parlay pool reserves this much
+    const MAX_PARLAY_MULTIPLIER: u64 = 100;
+
+    // A parlay's pick on each leg (0 = No, 1 = Yes) - Remove #[derive(ArcisType)]
+    pub struct ParlayChoices {
+        pub choices: [u8; 4],
+    }
+
+    // Sealed top-N reputation scores, sorted descending - Remove #[derive(ArcisType)]
+    pub struct LeaderboardScores {
+        pub scores: [u16; 10],
+    }
+
+    // Starting reputation for a profile without sealed state yet
+    const BASE_REPUTATION: u16 = 1000;
+    const MAX_REPUTATION: u16 = 10000;
+
+    // Maximum allowed distance between a vote's timestamp and the Solana clock (seconds)
+    const MAX_TIMESTAMP_DRIFT: u64 = 300;
+
+    // Most a market takes in stakes (the program's MAX_STAKE, u64::MAX / 20_000)
+    const MAX_STAKE: u64 = 922337203685477;
+
+    // Time weight in percent: 100 without decay, otherwise from 200 for a vote cast
+    // at market creation down to 100 for one cast at the close
+    fn time_weight(timestamp: u64, created_at: u64, voting_ends_at: u64, time_decay: bool) -> u64 {
+        let duration = if voting_ends_at > created_at { voting_ends_at - created_at } else { 1 };
+        let remaining = if voting_ends_at > timestamp { voting_ends_at - timestamp } else { 0 };
+        let remaining = remaining.min(duration);
+        if time_decay {
+            100 + (remaining * 100) / duration
+        } else {
+            100
+        }
+    }
+
+    // Minimum number of counted votes before a herding index is reported
+    const MIN_HERDING_SAMPLE: u32 = 10;
+
+    // Coarse herding index (0-4) from the share of votes that followed the crowd.
+    // 50% alignment is what independent voters would produce; 100% is pure herding
+    fn herding_index(aligned: u32, counted: u32) -> u8 {
+        let alignment = if counted > 0 { (aligned as u64 * 100) / counted as u64 } else { 0 };
+        if counted < MIN_HERDING_SAMPLE || alignment <= 55 {
+            0
+        } else if alignment <= 65 {
+            1
+        } else if alignment <= 75 {
+            2
+        } else if alignment <= 85 {
+            3
+        } else {
+            4
+        }
+    }
+
+    // Maximum Brier score: a 100% forecast on the realised outcome
+    const MAX_BRIER_SCORE: u64 = 10000;
+
+    // Inverted Brier score on a 0-10000 scale (higher is better calibrated)
+    // score = 10000 - (100 * outcome - probability)^2
+    fn brier_score(probability: u8, outcome: u8) -> u64 {
+        let target = if outcome == 1 { 100u64 } else { 0u64 };
+        let p = probability as u64;
+        let error = if target > p { target - p } else { p - target };
+        MAX_BRIER_SCORE - error * error
+    }
+
+    // Fold a hybrid market's public pool into its sealed voting state. Stakes,
+    // weights and score sums are plain sums over votes, so the combined state pays
+    // out exactly as if every vote had been aggregated into one pool
+    fn combine_pools(state: MarketVotingState, pool: PublicPool) -> MarketVotingState {
+        MarketVotingState {
+            market_id: state.market_id,
+            total_yes_votes: state.total_yes_votes,
+            total_no_votes: state.total_no_votes,
+            total_skip_votes: state.total_skip_votes,
+            total_yes_stake: state.total_yes_stake + pool.total_yes_stake,
+            total_no_stake: state.total_no_stake + pool.total_no_stake,
+            total_participants: state.total_participants + pool.total_participants,
+            weighted_probability_sum: state.weighted_probability_sum,
+            conviction_weighted_yes: state.conviction_weighted_yes + pool.conviction_weighted_yes,
+            conviction_weighted_no: state.conviction_weighted_no + pool.conviction_weighted_no,
+            last_updated: state.last_updated,
+            brier_score_sum_yes: state.brier_score_sum_yes + pool.brier_score_sum_yes,
+            brier_score_sum_no: state.brier_score_sum_no + pool.brier_score_sum_no,
+            time_weighted_yes_stake: state.time_weighted_yes_stake + pool.time_weighted_yes_stake,
+            time_weighted_no_stake: state.time_weighted_no_stake + pool.time_weighted_no_stake,
+            herding_aligned_votes: state.herding_aligned_votes + pool.herding_aligned_votes,
+            herding_counted_votes: state.herding_counted_votes + pool.herding_counted_votes,
+        }
+    }
+
+    // Private vote validation and submission. The verdict is revealed so the
+    // program can act on it; it says nothing about the vote beyond its validity
     #[instruction]
-    pub fn submit_private_vote(
-        vote_ctxt: Enc<Shared, VoteData>
-    ) -> Enc<Shared, u8> {
+    pub fn submit_private_vote_v1(
+        vote_ctxt: Enc<Shared, VoteData>,
+        current_time: u64,
+        stake_amount: u64
+    ) -> u8 {
         let vote = vote_ctxt.to_arcis();
 
         // Validate vote data
@@ -65,8 +218,11 @@ mod circuits {
             is_valid = 0;
         }
 
-        // Check stake amount is positive
-        if vote.stake_amount == 0 {
+        // Check stake amount is positive, within the cap and the one actually staked
+        if vote.stake_amount == 0
+            || vote.stake_amount > MAX_STAKE
+            || vote.stake_amount != stake_amount
+        {
             is_valid = 0;
         }
 
@@ -75,8 +231,13 @@ mod circuits {
             is_valid = 0;
         }
 
-        // Check timestamp is reasonable (not too far in past/future)
-        if vote.timestamp == 0 {
+        // Check timestamp is reasonable (not too far in past/future of the Solana clock)
+        let drift = if vote.timestamp > current_time {
+            vote.timestamp - current_time
+        } else {
+            current_time - vote.timestamp
+        };
+        if vote.timestamp == 0 || drift > MAX_TIMESTAMP_DRIFT {
             is_valid = 0;
         }
 
@@ -90,28 +251,51 @@ mod circuits {
             is_valid = 0;
         }
 
-        vote_ctxt.owner.from_arcis(is_valid)
+        is_valid.reveal()
     }
 
-    // Aggregate single vote into market state
-    #[instruction]
-    pub fn aggregate_market_votes(
-        vote_ctxt: Enc<Shared, VoteData>,
-        current_state_ctxt: Enc<Mxe, MarketVotingState>
-    ) -> Enc<Shared, MarketVotingState> {
-        let vote = vote_ctxt.to_arcis();
-        let mut state = current_state_ctxt.to_arcis();
+    // Fold a single vote into the market state; shared by every aggregate version
+    fn fold_vote(
+        vote: VoteData,
+        mut state: MarketVotingState,
+        current_time: u64,
+        created_at: u64,
+        voting_ends_at: u64,
+        time_decay: bool,
+    ) -> MarketVotingState {
+        // Votes must have been cast inside the market's voting window
+        let in_window = vote.timestamp >= created_at && vote.timestamp <= voting_ends_at;
+        let weight = time_weight(vote.timestamp, created_at, voting_ends_at, time_decay);
+
+        // Prevailing odds before this vote, for the herding statistic
+        let total_weighted = state.time_weighted_yes_stake + state.time_weighted_no_stake;
+        let prevailing_yes = if total_weighted > 0 {
+            (state.time_weighted_yes_stake * 100) / total_weighted
+        } else {
+            50
+        };
 
         // Validate and aggregate single vote
-        if vote.vote_choice <= 2 && vote.stake_amount > 0 {
+        if vote.vote_choice <= 2 && vote.stake_amount > 0 && vote.predicted_probability <= 100 && in_window {
+            // Herding: does a Yes/No vote side with whichever outcome the crowd favours?
+            if vote.vote_choice != 2 && prevailing_yes != 50 {
+                state.herding_counted_votes += 1;
+                let crowd_says_yes = prevailing_yes > 50;
+                if (vote.vote_choice == 1) == crowd_says_yes {
+                    state.herding_aligned_votes += 1;
+                }
+            }
+
             // Update vote counts
             if vote.vote_choice == 1 {
                 state.total_yes_votes += 1;
                 state.total_yes_stake += vote.stake_amount;
+                state.time_weighted_yes_stake += vote.stake_amount * weight;
                 state.conviction_weighted_yes += vote.conviction_score as u64 * vote.stake_amount;
             } else if vote.vote_choice == 0 {
                 state.total_no_votes += 1;
                 state.total_no_stake += vote.stake_amount;
+                state.time_weighted_no_stake += vote.stake_amount * weight;
                 state.conviction_weighted_no += vote.conviction_score as u64 * vote.stake_amount;
             } else if vote.vote_choice == 2 {
                 state.total_skip_votes += 1;
@@ -121,75 +305,338 @@ mod circuits {
             // Update aggregated probability (weighted by stake)
             if vote.vote_choice != 2 { // Only count Yes/No votes for probability
                 state.weighted_probability_sum += vote.stake_amount * vote.predicted_probability as u64;
+
+                // Outcome is unknown until resolution, so track the score sum for both
+                state.brier_score_sum_yes += brier_score(vote.predicted_probability, 1);
+                state.brier_score_sum_no += brier_score(vote.predicted_probability, 0);
             }
 
             state.total_participants += 1;
         }
 
-        // Update last updated timestamp
-        state.last_updated = state.last_updated + 1; // Simplified timestamp increment
+        // Update last updated timestamp from the Solana clock
+        state.last_updated = current_time;
 
-        vote_ctxt.owner.from_arcis(state)
+        state
     }
 
-    // Calculate individual payout while preserving privacy
+    // Aggregate single vote into market state
     #[instruction]
-    pub fn calculate_payout(
-        payout_ctxt: Enc<Shared, PayoutData>
-    ) -> Enc<Shared, u64> {
-        let payout_data = payout_ctxt.to_arcis();
+    pub fn aggregate_market_votes_v1(
+        vote_ctxt: Enc<Shared, VoteData>,
+        current_time: u64,
+        created_at: u64,
+        voting_ends_at: u64,
+        time_decay: bool,
+        current_state_ctxt: Enc<Mxe, MarketVotingState>
+    ) -> Enc<Mxe, MarketVotingState> {
+        let vote = vote_ctxt.to_arcis();
+        let state = current_state_ctxt.to_arcis();
+
+        let state = fold_vote(vote, state, current_time, created_at, voting_ends_at, time_decay);
+        current_state_ctxt.owner.from_arcis(state)
+    }
+
+    // Aggregate single vote into market state, echoing the vote accumulator root
+    //
+    // The program passes the accumulator root recorded when this vote was
+    // committed; returning it from the computation ties the updated state to the
+    // set of votes it covers, so auditors can check the tally for completeness
+    #[instruction]
+    pub fn aggregate_market_votes_v2(
+        vote_ctxt: Enc<Shared, VoteData>,
+        current_time: u64,
+        created_at: u64,
+        voting_ends_at: u64,
+        time_decay: bool,
+        accumulator_root_hi: u128,
+        accumulator_root_lo: u128,
+        current_state_ctxt: Enc<Mxe, MarketVotingState>
+    ) -> (Enc<Mxe, MarketVotingState>, u128, u128) {
+        let vote = vote_ctxt.to_arcis();
+        let state = current_state_ctxt.to_arcis();
+
+        let state = fold_vote(vote, state, current_time, created_at, voting_ends_at, time_decay);
+        (
+            current_state_ctxt.owner.from_arcis(state),
+            accumulator_root_hi,
+            accumulator_root_lo,
+        )
+    }
+
+    // PayoutData for a position's stored vote, as the program forwards it, with
+    // the plaintext outcome and bonus pools
+    fn payout_data_for(
+        vote: VoteData,
+        market_outcome: u8,
+        accuracy_bonus: u64,
+        conviction_bonus: u64,
+    ) -> PayoutData {
+        PayoutData {
+            user: vote.voter,
+            market_id: vote.market_id,
+            user_stake: vote.stake_amount,
+            user_vote: vote.vote_choice,
+            user_probability: vote.predicted_probability,
+            user_conviction: vote.conviction_score,
+            user_timestamp: vote.timestamp,
+            market_outcome,
+            accuracy_bonus,
+            conviction_bonus,
+        }
+    }
+
+    // Calculate individual payout while preserving privacy
+    //
+    // Pools are carved out of the total stake so that the sum of all payouts
+    // never exceeds it:
+    // - prize pool (total - bonus pools) split pro-rata among winners by
+    //   time-weighted stake (equal to plain stake when time decay is off)
+    // - accuracy pool split among all Yes/No voters by Brier score, so a
+    //   well-calibrated loser still earns something and overconfidence doesn't pay
+    // - conviction pool split among winners by conviction-weighted stake
+    //
+    // Shared by every payout version; `fee_amount` (protocol + creator fees) is
+    // taken out of the total stake before any pool is carved
+    fn payout_for(
+        payout_data: PayoutData,
+        fee_amount: u64,
+        created_at: u64,
+        voting_ends_at: u64,
+        time_decay: bool,
+        state: MarketVotingState,
+    ) -> u64 {
         let mut final_payout = 0u64;
 
+        let outcome_is_yes = payout_data.market_outcome == 1;
+        let (total_winning_stake, total_losing_stake, winning_conviction, score_sum) = if outcome_is_yes {
+            (state.total_yes_stake, state.total_no_stake, state.conviction_weighted_yes, state.brier_score_sum_yes)
+        } else {
+            (state.total_no_stake, state.total_yes_stake, state.conviction_weighted_no, state.brier_score_sum_no)
+        };
+        let total_winning_weight = if outcome_is_yes {
+            state.time_weighted_yes_stake
+        } else {
+            state.time_weighted_no_stake
+        };
+
+        // Fees come off the top; bonus pools can never exceed what was actually
+        // aggregated
+        let total_stake = total_winning_stake + total_losing_stake;
+        let distributable = total_stake - fee_amount.min(total_stake);
+        // With no calibration score to share it by (every forecast maximally wrong),
+        // the accuracy pool stays in the prize pool instead of going unpaid. With no
+        // Yes/No voter on the winning side, the prize and conviction pools have nobody
+        // to go to, so everything is shared out by calibration score instead
+        let has_winners = total_winning_weight > 0;
+        let accuracy_pool = if score_sum == 0 {
+            0
+        } else if has_winners {
+            payout_data.accuracy_bonus.min(distributable)
+        } else {
+            distributable
+        };
+        let conviction_pool = if has_winners {
+            payout_data.conviction_bonus.min(distributable - accuracy_pool)
+        } else {
+            0
+        };
+        let prize_pool = distributable - accuracy_pool - conviction_pool;
+
+        // Only votes that aggregate_market_votes counted share in the pools
+        let counted = payout_data.user_vote <= 2
+            && payout_data.user_stake > 0
+            && payout_data.user_probability <= 100
+            && payout_data.user_timestamp >= created_at
+            && payout_data.user_timestamp <= voting_ends_at;
+
         // Check if user won the prediction
-        let user_won = payout_data.user_vote == payout_data.market_outcome;
+        let user_won = counted && payout_data.user_vote == payout_data.market_outcome;
 
         if user_won {
-            // Base payout: User's stake + proportional share of losing stakes
-            let losing_stake_share = if payout_data.total_winning_stake > 0 {
-                (payout_data.user_stake * payout_data.total_losing_stake) / payout_data.total_winning_stake
-            } else {
-                0
-            };
+            // Base payout: proportional share of the prize pool by time-weighted stake
+            if total_winning_weight > 0 {
+                let weight = time_weight(payout_data.user_timestamp, created_at, voting_ends_at, time_decay);
+                let user_weight = payout_data.user_stake as u128 * weight as u128;
+                final_payout += ((user_weight * prize_pool as u128) / total_winning_weight as u128) as u64;
+            }
+
+            // Conviction bonus: share of the pool weighted by conviction * stake
+            if winning_conviction > 0 {
+                let user_conviction_weight = payout_data.user_conviction as u128 * payout_data.user_stake as u128;
+                final_payout += ((conviction_pool as u128 * user_conviction_weight) / winning_conviction as u128) as u64;
+            }
+        }
 
-            final_payout = payout_data.user_stake + losing_stake_share;
+        // Accuracy bonus: proper scoring rule paid to every Yes/No voter, win or lose
+        if counted && payout_data.user_vote != 2 && score_sum > 0 {
+            let score = brier_score(payout_data.user_probability, payout_data.market_outcome);
+            final_payout += ((accuracy_pool as u128 * score as u128) / score_sum as u128) as u64;
+        }
 
-            // Accuracy bonus: Reward for prediction confidence matching outcome
-            let accuracy_factor = if payout_data.market_outcome == 1 {
-                payout_data.user_probability as u64 // Predicted Yes correctly
-            } else if payout_data.market_outcome == 0 {
-                (100 - payout_data.user_probability) as u64 // Predicted No correctly
-            } else {
-                50 // Unknown outcome
-            };
+        final_payout
+    }
 
-            let accuracy_bonus = (payout_data.accuracy_bonus * accuracy_factor) / 100;
-            final_payout += accuracy_bonus;
+    // Calculate individual payout with no fees
+    #[instruction]
+    pub fn calculate_payout_v1(
+        payout_ctxt: Enc<Shared, PayoutData>,
+        created_at: u64,
+        voting_ends_at: u64,
+        time_decay: bool,
+        state_ctxt: Enc<Mxe, MarketVotingState>
+    ) -> Enc<Shared, u64> {
+        let payout_data = payout_ctxt.to_arcis();
+        let state = state_ctxt.to_arcis();
 
-            // Conviction bonus: Reward for high conviction in correct predictions
-            let conviction_bonus = (payout_data.conviction_bonus * payout_data.user_conviction as u64) / 1000;
-            final_payout += conviction_bonus;
+        let payout = payout_for(payout_data, 0, created_at, voting_ends_at, time_decay, state);
+        payout_ctxt.owner.from_arcis(payout)
+    }
 
-        } else {
-            // User lost - no payout
-            final_payout = 0;
-        }
+    // Calculate individual payout after protocol and creator fees, from the
+    // position's stored vote
+    #[instruction]
+    pub fn calculate_payout_v2(
+        vote_ctxt: Enc<Shared, VoteData>,
+        market_outcome: u8,
+        accuracy_bonus: u64,
+        conviction_bonus: u64,
+        fee_amount: u64,
+        created_at: u64,
+        voting_ends_at: u64,
+        time_decay: bool,
+        state_ctxt: Enc<Mxe, MarketVotingState>
+    ) -> Enc<Shared, u64> {
+        let payout_data =
+            payout_data_for(vote_ctxt.to_arcis(), market_outcome, accuracy_bonus, conviction_bonus);
+        let state = state_ctxt.to_arcis();
 
-        payout_ctxt.owner.from_arcis(final_payout)
+        let payout = payout_for(payout_data, fee_amount, created_at, voting_ends_at, time_decay, state);
+        vote_ctxt.owner.from_arcis(payout)
     }
 
-    // Simple market odds calculation without complex byte packing
+    // Payout of an encrypted position in a hybrid market, after fees, from the
+    // sealed and public pools combined
     #[instruction]
-    pub fn calculate_market_odds(
-        state_ctxt: Enc<Shared, MarketVotingState>
-    ) -> Enc<Shared, (u8, u8, u32, bool)> { // (yes_prob, no_prob, participants, high_confidence)
+    pub fn calculate_payout_hybrid_v1(
+        vote_ctxt: Enc<Shared, VoteData>,
+        market_outcome: u8,
+        accuracy_bonus: u64,
+        conviction_bonus: u64,
+        fee_amount: u64,
+        created_at: u64,
+        voting_ends_at: u64,
+        time_decay: bool,
+        state_ctxt: Enc<Mxe, MarketVotingState>,
+        total_yes_stake: u64,
+        total_no_stake: u64,
+        total_participants: u32,
+        conviction_weighted_yes: u64,
+        conviction_weighted_no: u64,
+        brier_score_sum_yes: u64,
+        brier_score_sum_no: u64,
+        time_weighted_yes_stake: u64,
+        time_weighted_no_stake: u64,
+        herding_aligned_votes: u32,
+        herding_counted_votes: u32
+    ) -> Enc<Shared, u64> {
+        let payout_data =
+            payout_data_for(vote_ctxt.to_arcis(), market_outcome, accuracy_bonus, conviction_bonus);
+        let pool = PublicPool {
+            total_yes_stake,
+            total_no_stake,
+            total_participants,
+            conviction_weighted_yes,
+            conviction_weighted_no,
+            brier_score_sum_yes,
+            brier_score_sum_no,
+            time_weighted_yes_stake,
+            time_weighted_no_stake,
+            herding_aligned_votes,
+            herding_counted_votes,
+        };
+        let state = combine_pools(state_ctxt.to_arcis(), pool);
+
+        let payout = payout_for(payout_data, fee_amount, created_at, voting_ends_at, time_decay, state);
+        vote_ctxt.owner.from_arcis(payout)
+    }
+
+    // Payout of a public position in a hybrid market. The vote is plaintext, but
+    // the pools it is paid from include sealed stakes, so only the MXE can compute
+    // it; the amount is revealed since the position is public anyway
+    #[instruction]
+    pub fn calculate_public_payout_v1(
+        user_stake: u64,
+        user_vote: u8,
+        user_probability: u8,
+        user_conviction: u16,
+        user_timestamp: u64,
+        market_outcome: u8,
+        accuracy_bonus: u64,
+        conviction_bonus: u64,
+        fee_amount: u64,
+        created_at: u64,
+        voting_ends_at: u64,
+        time_decay: bool,
+        state_ctxt: Enc<Mxe, MarketVotingState>,
+        total_yes_stake: u64,
+        total_no_stake: u64,
+        total_participants: u32,
+        conviction_weighted_yes: u64,
+        conviction_weighted_no: u64,
+        brier_score_sum_yes: u64,
+        brier_score_sum_no: u64,
+        time_weighted_yes_stake: u64,
+        time_weighted_no_stake: u64,
+        herding_aligned_votes: u32,
+        herding_counted_votes: u32
+    ) -> u64 {
         let state = state_ctxt.to_arcis();
+        let payout_data = PayoutData {
+            user: [0u8; 32],
+            market_id: state.market_id,
+            user_stake,
+            user_vote,
+            user_probability,
+            user_conviction,
+            user_timestamp,
+            market_outcome,
+            accuracy_bonus,
+            conviction_bonus,
+        };
+        let pool = PublicPool {
+            total_yes_stake,
+            total_no_stake,
+            total_participants,
+            conviction_weighted_yes,
+            conviction_weighted_no,
+            brier_score_sum_yes,
+            brier_score_sum_no,
+            time_weighted_yes_stake,
+            time_weighted_no_stake,
+            herding_aligned_votes,
+            herding_counted_votes,
+        };
+        let state = combine_pools(state, pool);
+
+        let payout = payout_for(payout_data, fee_amount, created_at, voting_ends_at, time_decay, state);
+        payout.reveal()
+    }
+
+    // Simple market odds calculation without complex byte packing
+    //
+    // The spread widens by 2% per herding index step; returns
+    // (yes_prob, no_prob, participants, high_confidence, herding_index)
+    fn odds_for(state: MarketVotingState) -> (u8, u8, u32, bool, u8) {
+        let herding = herding_index(state.herding_aligned_votes, state.herding_counted_votes);
 
         let total_stake = state.total_yes_stake + state.total_no_stake;
+        let total_weighted = state.time_weighted_yes_stake + state.time_weighted_no_stake;
 
-        let (yes_probability, no_probability, high_confidence) = if total_stake > 0 {
-            // Calculate implied probabilities based on stake distribution
-            let yes_prob = (state.total_yes_stake * 100) / total_stake;
-            let no_prob = (state.total_no_stake * 100) / total_stake;
+        let (yes_probability, no_probability, high_confidence) = if total_weighted > 0 {
+            // Calculate implied probabilities based on time-weighted stake distribution
+            let yes_prob = (state.time_weighted_yes_stake * 100) / total_weighted;
+            let no_prob = (state.time_weighted_no_stake * 100) / total_weighted;
 
             // Apply liquidity adjustments and market maker spread
             let liquidity_factor = if total_stake > 10000 { // High liquidity
@@ -199,6 +646,7 @@ mod circuits {
             } else { // Low liquidity
                 85 // 15% spread
             };
+            let liquidity_factor = liquidity_factor - herding as u64 * 2;
 
             let adjusted_yes = (yes_prob * liquidity_factor) / 100;
             let adjusted_no = (no_prob * liquidity_factor) / 100;
@@ -209,7 +657,65 @@ mod circuits {
             (50u8, 50u8, false)
         };
 
-        state_ctxt.owner.from_arcis((yes_probability, no_probability, state.total_participants, high_confidence))
+        (yes_probability, no_probability, state.total_participants, high_confidence, herding)
+    }
+
+    // Reveals only aggregates
+    #[instruction]
+    pub fn calculate_market_odds_v1(
+        state_ctxt: Enc<Mxe, MarketVotingState>
+    ) -> (u8, u8, u32, bool, u8) { // (yes_prob, no_prob, participants, high_confidence, herding_index)
+        let (yes_probability, no_probability, participants, high_confidence, herding) =
+            odds_for(state_ctxt.to_arcis());
+
+        (
+            yes_probability.reveal(),
+            no_probability.reveal(),
+            participants.reveal(),
+            high_confidence.reveal(),
+            herding.reveal(),
+        )
+    }
+
+    // Odds of a hybrid market, over its sealed and public pools combined
+    #[instruction]
+    pub fn calculate_market_odds_hybrid_v1(
+        state_ctxt: Enc<Mxe, MarketVotingState>,
+        total_yes_stake: u64,
+        total_no_stake: u64,
+        total_participants: u32,
+        conviction_weighted_yes: u64,
+        conviction_weighted_no: u64,
+        brier_score_sum_yes: u64,
+        brier_score_sum_no: u64,
+        time_weighted_yes_stake: u64,
+        time_weighted_no_stake: u64,
+        herding_aligned_votes: u32,
+        herding_counted_votes: u32
+    ) -> (u8, u8, u32, bool, u8) { // (yes_prob, no_prob, participants, high_confidence, herding_index)
+        let pool = PublicPool {
+            total_yes_stake,
+            total_no_stake,
+            total_participants,
+            conviction_weighted_yes,
+            conviction_weighted_no,
+            brier_score_sum_yes,
+            brier_score_sum_no,
+            time_weighted_yes_stake,
+            time_weighted_no_stake,
+            herding_aligned_votes,
+            herding_counted_votes,
+        };
+        let (yes_probability, no_probability, participants, high_confidence, herding) =
+            odds_for(combine_pools(state_ctxt.to_arcis(), pool));
+
+        (
+            yes_probability.reveal(),
+            no_probability.reveal(),
+            participants.reveal(),
+            high_confidence.reveal(),
+            herding.reveal(),
+        )
     }
 
     // Anti-manipulation detection between two votes
@@ -256,4 +762,274 @@ mod circuits {
 
         vote1_ctxt.owner.from_arcis(manipulation_score)
     }
-}
\ No newline at end of file
+
+    // Private reputation update at claim time
+    //
+    // Determines win/loss from the user's encrypted vote and folds the result into
+    // the MXE-sealed reputation state, so the program never learns which side won
+    #[instruction]
+    pub fn update_reputation_v1(
+        vote_ctxt: Enc<Shared, VoteData>,
+        payout_ctxt: Enc<Shared, u64>,
+        market_outcome: u8,
+        has_state: bool,
+        reputation_ctxt: Enc<Mxe, ReputationState>
+    ) -> Enc<Mxe, ReputationState> {
+        let vote = vote_ctxt.to_arcis();
+        let payout = payout_ctxt.to_arcis();
+        let sealed = reputation_ctxt.to_arcis();
+
+        // A fresh profile has no valid ciphertext yet, so start from the defaults
+        let mut reputation = if has_state {
+            sealed
+        } else {
+            ReputationState {
+                correct_predictions: 0,
+                total_winnings: 0,
+                streak_current: 0,
+                streak_best: 0,
+                reputation_score: BASE_REPUTATION,
+            }
+        };
+
+        // Skip votes don't affect reputation
+        if vote.vote_choice != 2 {
+            if vote.vote_choice == market_outcome {
+                reputation.correct_predictions += 1;
+                reputation.total_winnings += payout;
+                reputation.streak_current += 1;
+                if reputation.streak_current > reputation.streak_best {
+                    reputation.streak_best = reputation.streak_current;
+                }
+                // Reward scales with the current streak, capped at +50
+                let gain = (10 + reputation.streak_current * 2).min(50);
+                reputation.reputation_score = (reputation.reputation_score + gain).min(MAX_REPUTATION);
+            } else {
+                reputation.streak_current = 0;
+                reputation.reputation_score = if reputation.reputation_score > 20 {
+                    reputation.reputation_score - 20
+                } else {
+                    0
+                };
+            }
+        }
+
+        reputation_ctxt.owner.from_arcis(reputation)
+    }
+
+    // Insert a user's sealed reputation score into the sealed top-N leaderboard
+    //
+    // Only the slot the user lands in is revealed (LEADERBOARD_SIZE if unranked);
+    // the scores themselves stay sealed until the season ends
+    #[instruction]
+    pub fn insert_leaderboard_entry(
+        reputation_ctxt: Enc<Mxe, ReputationState>,
+        has_scores: bool,
+        board_ctxt: Enc<Mxe, LeaderboardScores>
+    ) -> (Enc<Mxe, LeaderboardScores>, u8) {
+        let reputation = reputation_ctxt.to_arcis();
+        let sealed = board_ctxt.to_arcis();
+
+        // A fresh leaderboard has no valid ciphertext yet, so start empty
+        let board = if has_scores {
+            sealed
+        } else {
+            LeaderboardScores { scores: [0u16; 10] }
+        };
+        let score = reputation.reputation_score;
+
+        // Slot = number of entries ranked at or above this score
+        let mut slot = 0u8;
+        for i in 0..LEADERBOARD_SIZE {
+            if board.scores[i] >= score {
+                slot += 1;
+            }
+        }
+
+        // Shift lower-ranked entries down by one and insert
+        let mut updated = LeaderboardScores { scores: [0u16; 10] };
+        for i in 0..LEADERBOARD_SIZE {
+            let idx = i as u8;
+            let previous = if i == 0 { 0 } else { board.scores[i - 1] };
+            updated.scores[i] = if idx < slot {
+                board.scores[i]
+            } else if idx == slot {
+                score
+            } else {
+                previous
+            };
+        }
+
+        (board_ctxt.owner.from_arcis(updated), slot.reveal())
+    }
+
+    // Reveal the final leaderboard scores, rounded down to the nearest 10
+    #[instruction]
+    pub fn reveal_leaderboard(
+        board_ctxt: Enc<Mxe, LeaderboardScores>
+    ) -> [u16; 10] {
+        let board = board_ctxt.to_arcis();
+
+        let mut rounded = [0u16; 10];
+        for i in 0..LEADERBOARD_SIZE {
+            rounded[i] = (board.scores[i] / 10) * 10;
+        }
+
+        rounded.reveal()
+    }
+
+    // Re-seal a market's voting state under the current MXE key
+    //
+    // Run after a cluster key rotation: the state is decrypted under the old key and
+    // sealed again with a fresh nonce, without any value changing
+    #[instruction]
+    pub fn reencrypt_market_state_v1(
+        state_ctxt: Enc<Mxe, MarketVotingState>
+    ) -> Enc<Mxe, MarketVotingState> {
+        let state = state_ctxt.to_arcis();
+        state_ctxt.owner.from_arcis(state)
+    }
+
+    // Re-encrypt a batch of position votes after a cluster key rotation
+    //
+    // Each vote stays encrypted to its owner's x25519 key, now under the shared
+    // secret with the current MXE key. Unused slots repeat a real position and the
+    // program discards their output
+    #[instruction]
+    pub fn reencrypt_positions_v1(
+        vote0_ctxt: Enc<Shared, PositionVote>,
+        vote1_ctxt: Enc<Shared, PositionVote>,
+        vote2_ctxt: Enc<Shared, PositionVote>,
+        vote3_ctxt: Enc<Shared, PositionVote>
+    ) -> (
+        Enc<Shared, PositionVote>,
+        Enc<Shared, PositionVote>,
+        Enc<Shared, PositionVote>,
+        Enc<Shared, PositionVote>,
+    ) {
+        let vote0 = vote0_ctxt.to_arcis();
+        let vote1 = vote1_ctxt.to_arcis();
+        let vote2 = vote2_ctxt.to_arcis();
+        let vote3 = vote3_ctxt.to_arcis();
+
+        (
+            vote0_ctxt.owner.from_arcis(vote0),
+            vote1_ctxt.owner.from_arcis(vote1),
+            vote2_ctxt.owner.from_arcis(vote2),
+            vote3_ctxt.owner.from_arcis(vote3),
+        )
+    }
+
+    // Re-encrypt a position's vote to a third party chosen by the voter
+    //
+    // The recipient's x25519 key and nonce arrive as a Shared owner, so only they
+    // can decrypt the output; the vote itself is never revealed
+    #[instruction]
+    pub fn disclose_vote_v1(
+        vote_ctxt: Enc<Shared, PositionVote>,
+        recipient: Shared
+    ) -> Enc<Shared, PositionVote> {
+        let vote = vote_ctxt.to_arcis();
+        recipient.from_arcis(vote)
+    }
+
+    // Reveal a batch of votes after resolution on markets that opted into it
+    //
+    // Publishes each vote's choice, probability and conviction; stakes are already
+    // public. Unused slots repeat a real position and the program discards them
+    #[instruction]
+    pub fn reveal_votes_v1(
+        vote0_ctxt: Enc<Shared, PositionVote>,
+        vote1_ctxt: Enc<Shared, PositionVote>,
+        vote2_ctxt: Enc<Shared, PositionVote>,
+        vote3_ctxt: Enc<Shared, PositionVote>
+    ) -> ([u8; 4], [u8; 4], [u16; 4]) {
+        let votes = [
+            vote0_ctxt.to_arcis(),
+            vote1_ctxt.to_arcis(),
+            vote2_ctxt.to_arcis(),
+            vote3_ctxt.to_arcis(),
+        ];
+
+        let mut choices = [0u8; 4];
+        let mut probabilities = [0u8; 4];
+        let mut convictions = [0u16; 4];
+        for i in 0..REVEAL_BATCH_SIZE {
+            choices[i] = votes[i].vote_choice;
+            probabilities[i] = votes[i].predicted_probability;
+            convictions[i] = votes[i].conviction_score;
+        }
+
+        (choices.reveal(), probabilities.reveal(), convictions.reveal())
+    }
+
+    // Parlay payout: the stake multiplied by each leg's decimal odds (100 over the
+    // picked side's revealed probability, floored at 1%), or nothing unless every
+    // leg was picked right. Void legs count at even odds. Capped at
+    // MAX_PARLAY_MULTIPLIER times the stake
+    fn parlay_payout(
+        picks: ParlayChoices,
+        stake_amount: u64,
+        outcomes: [u8; 4],
+        odds_yes: [u8; 4],
+        odds_no: [u8; 4],
+    ) -> u64 {
+        let mut payout = stake_amount as u128;
+        let mut won = true;
+        for i in 0..MAX_PARLAY_LEGS {
+            if outcomes[i] != PARLAY_LEG_VOID {
+                let pick = picks.choices[i];
+                let odds = if pick == 1 { odds_yes[i] } else { odds_no[i] };
+                let odds = if odds == 0 { 1 } else { odds };
+                payout = (payout * 100) / odds as u128;
+                if pick != outcomes[i] {
+                    won = false;
+                }
+            }
+        }
+
+        let max_payout = stake_amount as u128 * MAX_PARLAY_MULTIPLIER as u128;
+        let payout = if payout > max_payout { max_payout } else { payout };
+
+        if !won {
+            0
+        } else if payout > u64::MAX as u128 {
+            u64::MAX
+        } else {
+            payout as u64
+        }
+    }
+
+    // Settle a parlay once every leg's market has resolved or been cancelled
+    //
+    // Leg outcomes and odds are public and the picks stay encrypted. The payout is
+    // revealed so the program can pay it out of the parlay pool; every leg has
+    // resolved by then
+    #[instruction]
+    pub fn calculate_parlay_payout_v1(
+        choices_ctxt: Enc<Shared, ParlayChoices>,
+        stake_amount: u64,
+        outcome0: u8,
+        odds_yes0: u8,
+        odds_no0: u8,
+        outcome1: u8,
+        odds_yes1: u8,
+        odds_no1: u8,
+        outcome2: u8,
+        odds_yes2: u8,
+        odds_no2: u8,
+        outcome3: u8,
+        odds_yes3: u8,
+        odds_no3: u8
+    ) -> u64 {
+        let picks = choices_ctxt.to_arcis();
+        let payout = parlay_payout(
+            picks,
+            stake_amount,
+            [outcome0, outcome1, outcome2, outcome3],
+            [odds_yes0, odds_yes1, odds_yes2, odds_yes3],
+            [odds_no0, odds_no1, odds_no2, odds_no3],
+        );
+        payout.reveal()
+    }
+}
@@ -0,0 +1,87 @@
+use anchor_lang::prelude::*;
+use arcium_client::idl::arcium::types::{CircuitSource, OffChainCircuitSource};
+
+use crate::ErrorCode;
+
+// Program-level registry of where each compiled circuit lives and what it hashes to.
+// The init_*_comp_def instructions read from here instead of hard-coding sources,
+// so a circuit can't be swapped without the admin publishing its new hash.
+
+/// Room for every circuit with headroom for new versions; registries created
+/// smaller grow to fit on their next set_circuit_source
+pub const MAX_CIRCUIT_ENTRIES: usize = 32;
+pub const MAX_CIRCUIT_SOURCE_LEN: usize = 200;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, InitSpace)]
+pub struct CircuitSourceEntry {
+    pub comp_def_offset: u32,   // comp_def_offset("<circuit name>")
+    #[max_len(MAX_CIRCUIT_SOURCE_LEN)]
+    pub source: String,         // URL of the compiled .arcis file
+    pub hash: [u8; 32],         // SHA-256 of the compiled .arcis file
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct CircuitRegistry {
+    pub version: u8,
+    pub admin: Pubkey, // Admin when created; checks use ProgramConfig.admin
+    #[max_len(MAX_CIRCUIT_ENTRIES)]
+    pub entries: Vec<CircuitSourceEntry>,
+    pub bump: u8,
+}
+
+impl CircuitRegistry {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+    pub const VERSION: u8 = 1;
+
+    pub fn entry(&self, comp_def_offset: u32) -> Option<&CircuitSourceEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.comp_def_offset == comp_def_offset)
+    }
+
+    /// Insert or replace the source for a circuit
+    pub fn set_entry(&mut self, comp_def_offset: u32, source: String, hash: [u8; 32]) -> Result<()> {
+        require!(
+            source.len() <= MAX_CIRCUIT_SOURCE_LEN,
+            ErrorCode::CircuitSourceTooLong
+        );
+        require!(hash != [0; 32], ErrorCode::InvalidCircuitHash);
+
+        match self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.comp_def_offset == comp_def_offset)
+        {
+            Some(entry) => {
+                entry.source = source;
+                entry.hash = hash;
+            }
+            None => {
+                require!(
+                    self.entries.len() < MAX_CIRCUIT_ENTRIES,
+                    ErrorCode::CircuitRegistryFull
+                );
+                self.entries.push(CircuitSourceEntry {
+                    comp_def_offset,
+                    source,
+                    hash,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Circuit source to pass to `init_comp_def`, failing if the admin hasn't registered one
+    pub fn circuit_source(&self, comp_def_offset: u32) -> Result<CircuitSource> {
+        let entry = self
+            .entry(comp_def_offset)
+            .ok_or(ErrorCode::CircuitNotRegistered)?;
+
+        Ok(CircuitSource::OffChain(OffChainCircuitSource {
+            source: entry.source.clone(),
+            hash: entry.hash,
+        }))
+    }
+}
@@ -0,0 +1,139 @@
+use anchor_lang::prelude::*;
+
+use crate::ComputationFailedEvent;
+
+// One record per queued MPC computation. Callbacks mark it succeeded or failed
+// instead of erroring out, so an aborted computation leaves a visible status that
+// can be retried or, for votes, rolled back.
+
+/// Attempts (first submission plus retries) before a failed vote can be rolled back
+pub const MAX_COMPUTATION_ATTEMPTS: u8 = 3;
+
+/// Seconds after queueing before a computation with no callback counts as timed out
+pub const COMPUTATION_TIMEOUT: i64 = 600;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, InitSpace)]
+pub enum ComputationKind {
+    SubmitVote,
+    AggregateVotes,
+    CalculatePayout,
+    UpdateReputation,
+    CalculateOdds,
+    InsertLeaderboardEntry,
+    RevealLeaderboard,
+    ReencryptMarketState,
+    ReencryptPositions,
+    DiscloseVote,
+    RevealVotes,
+    CalculateParlayPayout,
+    CalculateFinalOdds, // Odds of a resolved market, locked once revealed
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, InitSpace)]
+pub enum ComputationStatus {
+    Queued,
+    Succeeded,
+    Failed,
+    Retried,    // Failed, and a newer attempt was queued
+    RolledBack, // Failed or timed out vote whose position was unwound
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ComputationRecord {
+    pub version: u8,
+    pub bump: u8,
+    pub computation_offset: u64,
+    pub kind: ComputationKind,
+    pub target: Pubkey, // Position, market, profile or leaderboard the computation is for
+    pub payer: Pubkey,  // Paid for this record and gets its rent back
+    pub status: ComputationStatus,
+    pub attempts: u8,
+    pub queued_at: i64,
+    pub updated_at: i64,
+}
+
+impl ComputationRecord {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+    pub const VERSION: u8 = 1;
+
+    pub fn queued(
+        bump: u8,
+        computation_offset: u64,
+        kind: ComputationKind,
+        target: Pubkey,
+        payer: Pubkey,
+        attempts: u8,
+        now: i64,
+    ) -> Self {
+        Self {
+            version: Self::VERSION,
+            bump,
+            computation_offset,
+            kind,
+            target,
+            payer,
+            status: ComputationStatus::Queued,
+            attempts,
+            queued_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn mark_succeeded(&mut self, now: i64) {
+        self.status = ComputationStatus::Succeeded;
+        self.updated_at = now;
+    }
+
+    /// Mark the computation failed. Callbacks return this instead of an error,
+    /// which would roll the status change back along with the callback.
+    pub fn mark_failed(&mut self, now: i64) -> Result<()> {
+        self.status = ComputationStatus::Failed;
+        self.updated_at = now;
+
+        emit!(ComputationFailedEvent {
+            computation_offset: self.computation_offset,
+            kind: self.kind,
+            target: self.target,
+            attempts: self.attempts,
+        });
+
+        Ok(())
+    }
+
+    pub fn is_timed_out(&self, now: i64) -> bool {
+        self.status == ComputationStatus::Queued && now - self.queued_at > COMPUTATION_TIMEOUT
+    }
+
+    /// Finished records can be closed to reclaim rent
+    pub fn is_settled(&self) -> bool {
+        match self.status {
+            ComputationStatus::Queued => false,
+            // A failed vote record is still needed to retry or roll back the position
+            ComputationStatus::Failed => self.kind != ComputationKind::SubmitVote,
+            ComputationStatus::Succeeded
+            | ComputationStatus::Retried
+            | ComputationStatus::RolledBack => true,
+        }
+    }
+}
+
+/// Program-wide source of computation offsets. Every queue instruction must be
+/// called with `next_offset`, which it then advances, so concurrent clients get
+/// a clear StaleComputationOffset error instead of colliding on an offset.
+#[account]
+#[derive(InitSpace)]
+pub struct ComputationAllocator {
+    pub version: u8,
+    pub bump: u8,
+    pub next_offset: u64,
+}
+
+impl ComputationAllocator {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+    pub const VERSION: u8 = 1;
+
+    pub fn advance(&mut self) {
+        self.next_offset += 1;
+    }
+}
@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+
+use crate::{ConditionalMarketCancelledEvent, ErrorCode, MarketStatus, PredictionMarket};
+
+// Conditional markets ask "if the parent resolves to X, will Y happen?". A market
+// created with a parent records the parent's address and the outcome it's
+// conditioned on. It takes votes like any other market, but can only resolve once
+// the parent has resolved to that outcome. If the parent resolves the other way,
+// or is cancelled, the question is moot: the child is cancelled without slashing
+// (by anyone, or on the first refund claim), every stake is refunded with
+// claim_refund and the creator reclaims the bond.
+
+/// Where a market's parent stands relative to the outcome the market is conditioned on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParentCondition {
+    Pending, // Parent hasn't resolved yet
+    Met,     // Parent resolved to parent_outcome, or the market has no parent
+    Failed,  // Parent resolved the other way or was cancelled
+}
+
+/// Check `parent` is the market `market` is conditioned on and read where it stands
+pub fn parent_condition(
+    market: &PredictionMarket,
+    parent: Option<&Account<'_, PredictionMarket>>,
+) -> Result<ParentCondition> {
+    let Some(parent_key) = market.parent_market else {
+        return Ok(ParentCondition::Met);
+    };
+    let parent = parent.ok_or(error!(ErrorCode::InvalidParentMarket))?;
+    require_keys_eq!(parent.key(), parent_key, ErrorCode::InvalidParentMarket);
+
+    Ok(match parent.status {
+        MarketStatus::Resolved | MarketStatus::Settled
+            if parent.resolved_outcome == Some(market.parent_outcome) =>
+        {
+            ParentCondition::Met
+        }
+        MarketStatus::Resolved | MarketStatus::Settled | MarketStatus::Cancelled => {
+            ParentCondition::Failed
+        }
+        MarketStatus::Active | MarketStatus::Locked => ParentCondition::Pending,
+    })
+}
+
+/// Cancel an open conditional market whose parent went against its condition.
+/// The creation bond is left in the account for the creator to reclaim
+pub fn cancel_for_failed_condition(
+    market: &mut PredictionMarket,
+    parent: Option<&Account<'_, PredictionMarket>>,
+) -> Result<()> {
+    require!(
+        market.status == MarketStatus::Active || market.status == MarketStatus::Locked,
+        ErrorCode::CannotCancelMarket
+    );
+    require!(market.parent_market.is_some(), ErrorCode::InvalidParentMarket);
+    match parent_condition(market, parent)? {
+        ParentCondition::Failed => {}
+        ParentCondition::Pending => return err!(ErrorCode::ParentMarketUnresolved),
+        ParentCondition::Met => return err!(ErrorCode::ParentConditionMet),
+    }
+
+    let parent = parent.ok_or(error!(ErrorCode::InvalidParentMarket))?;
+    market.status = MarketStatus::Cancelled;
+
+    emit!(ConditionalMarketCancelledEvent {
+        market_id: market.market_id,
+        parent_market_id: parent.market_id,
+        parent_outcome: parent.resolved_outcome,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+
+use crate::{PredictionMarket, Treasury};
+
+// Creators post a bond in lamports when they open a market, sized per category
+// in ProgramConfig. The bond sits in the market account on top of its rent and
+// goes back to the creator once the market resolves. If the oracle cancels the
+// market as invalid, or the admin flags it as malformed, the bond is slashed to
+// the dispute winner, or to the treasury when there is none. The treasury counts
+// slashed bonds apart from fees: they're real lamports, fees are stake-denominated.
+
+/// Move the market's whole bond to `to`; returns the amount moved
+pub fn release_creation_bond<'info>(
+    market: &mut Account<'info, PredictionMarket>,
+    to: &AccountInfo<'info>,
+) -> Result<u64> {
+    let amount = market.creation_bond;
+    market.creation_bond = 0;
+
+    **market.to_account_info().try_borrow_mut_lamports()? -= amount;
+    **to.try_borrow_mut_lamports()? += amount;
+    Ok(amount)
+}
+
+/// Slash the bond to the dispute winner if there is one, else to the treasury;
+/// returns the amount and who received it
+pub fn slash_creation_bond<'info>(
+    market: &mut Account<'info, PredictionMarket>,
+    treasury: &mut Account<'info, Treasury>,
+    dispute_winner: Option<&AccountInfo<'info>>,
+) -> Result<(u64, Pubkey)> {
+    match dispute_winner {
+        Some(winner) => {
+            let amount = release_creation_bond(market, winner)?;
+            Ok((amount, winner.key()))
+        }
+        None => {
+            let amount = release_creation_bond(market, &treasury.to_account_info())?;
+            treasury.slashed_bonds_accrued += amount;
+            treasury.total_slashed_bonds += amount;
+            Ok((amount, treasury.key()))
+        }
+    }
+}
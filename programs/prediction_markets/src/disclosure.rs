@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+// On-chain log of a user revealing their vote to one chosen party. The vote is
+// re-encrypted inside MPC from the user's shared key to the recipient's x25519
+// key, so only the recipient can read `encrypted_vote`; everyone else only sees
+// that a disclosure happened, to whom, and when.
+
+#[account]
+#[derive(InitSpace)]
+pub struct DisclosureRecord {
+    pub version: u8,
+    pub bump: u8,
+    pub position: Pubkey,
+    pub user: Pubkey,
+    pub recipient_pubkey: [u8; 32], // Recipient's x25519 public key
+    pub index: u32,                 // Position's disclosure counter when requested
+    // Vote fields (market_id, vote_choice, stake, prob, conviction, timestamp, nonce)
+    // encrypted to the recipient
+    pub encrypted_vote: [[u8; 32]; 7],
+    pub vote_nonce: u128,
+    pub is_complete: bool,
+    pub requested_at: i64,
+}
+
+impl DisclosureRecord {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+    pub const VERSION: u8 = 1;
+}
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+
+use crate::ErrorCode;
+
+// Protocol and creator fees. The admin sets the protocol fee and a cap on creator
+// fees in ProgramConfig; each market snapshots both rates when it's created. At
+// resolution the fees are charged on the market's total stake and taken off the
+// top before calculate_payout_v2 carves the payout pools. The protocol share
+// accrues to the Treasury PDA and each market's creator share is held there until
+// the creator withdraws it. Like stakes and payouts, fees are only counters: stakes
+// aren't escrowed, so withdrawing settles the counter without moving lamports. The
+// treasury's lamports are slashed bonds and never pay out fees.
+
+/// Basis points in 100%
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Upper bound on the protocol fee and on the creator fee cap, each
+pub const MAX_FEE_BPS: u16 = 1_000;
+
+#[account]
+#[derive(InitSpace)]
+pub struct Treasury {
+    pub version: u8,
+    pub bump: u8,
+    pub protocol_fees_accrued: u64,   // Withdrawable by the config admin
+    pub creator_fees_accrued: u64,    // Owed to market creators, tracked per market
+    pub total_protocol_fees: u64,     // Lifetime protocol fees charged
+    pub total_slashed_bonds: u64,     // Lifetime creation bonds slashed to the treasury
+    pub slashed_bonds_accrued: u64,   // Slashed bonds held, withdrawable by the config admin
+}
+
+impl Treasury {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+    pub const VERSION: u8 = 2;
+}
+
+/// `bps` of `amount`, rounded down
+pub fn fee_amount(amount: u64, bps: u16) -> u64 {
+    (amount as u128 * bps as u128 / BPS_DENOMINATOR as u128) as u64
+}
+
+/// Move `amount` lamports out of the treasury, keeping it rent-exempt. Only for
+/// lamports the treasury actually received, never for stake-denominated fees
+pub fn pay_from_treasury<'info>(
+    treasury: &Account<'info, Treasury>,
+    to: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let treasury = treasury.to_account_info();
+    let rent_floor = Rent::get()?.minimum_balance(Treasury::LEN);
+    require!(
+        treasury.lamports().saturating_sub(rent_floor) >= amount,
+        ErrorCode::TreasuryUnderfunded
+    );
+
+    **treasury.try_borrow_mut_lamports()? -= amount;
+    **to.try_borrow_mut_lamports()? += amount;
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+// Tracks which MXE key generation the cluster is on. Markets and positions record
+// the epoch their ciphertexts were produced under; after a rotation, anything on an
+// older epoch is re-encrypted with reencrypt_market_state / reencrypt_positions
+// before it can be aggregated again.
+
+/// Positions re-encrypted per `reencrypt_positions` computation
+pub const REENCRYPT_BATCH_SIZE: usize = 4;
+
+#[account]
+#[derive(InitSpace)]
+pub struct MxeKeyEpoch {
+    pub version: u8,
+    pub bump: u8,
+    pub admin: Pubkey, // Admin when created; checks use ProgramConfig.admin
+    pub epoch: u32,
+    pub rotated_at: i64,
+}
+
+impl MxeKeyEpoch {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+    pub const VERSION: u8 = 1;
+
+    pub fn rotate(&mut self, now: i64) {
+        self.epoch += 1;
+        self.rotated_at = now;
+    }
+
+    /// Ciphertexts recorded under `epoch` need re-encrypting before use
+    pub fn is_stale(&self, epoch: u32) -> bool {
+        epoch < self.epoch
+    }
+}
@@ -1,16 +1,113 @@
 use anchor_lang::prelude::*;
 use arcium_anchor::prelude::*;
-use arcium_client::idl::arcium::types::{CallbackAccount, CircuitSource, OffChainCircuitSource};
+use arcium_client::idl::arcium::types::CallbackAccount;
 
 // Import oracle system
 pub mod oracle_system;
 pub use oracle_system::*;
 
+// Circuit sources and hashes for computation definitions
+pub mod circuit_registry;
+pub use circuit_registry::*;
+
+// Status tracking for queued MPC computations
+pub mod computation_record;
+pub use computation_record::*;
+
+// MXE key epochs and re-encryption after a cluster key rotation
+pub mod key_rotation;
+pub use key_rotation::*;
+
+// Votes disclosed to a chosen third party
+pub mod disclosure;
+pub use disclosure::*;
+
+// Merkle commitment to submitted votes, for auditing the tally
+pub mod vote_accumulator;
+pub use vote_accumulator::*;
+
+// Program-wide config: admin and guardian roles, emergency pause flags
+pub mod program_config;
+pub use program_config::*;
+
+// Protocol and creator fees and the treasury holding them
+pub mod fees;
+pub use fees::*;
+
+// Creator bonds posted at market creation, returned or slashed
+pub mod creation_bond;
+pub use creation_bond::*;
+
+// Off-chain market metadata committed to by URI and content hash
+pub mod metadata;
+pub use metadata::*;
+
+// Program-assigned market ids and per-category / per-creator market indexes
+pub mod market_registry;
+pub use market_registry::*;
+
+// Account layout versions and in-place migration of older accounts
+pub mod migration;
+pub use migration::*;
+
+// Plaintext copy of the circuits' tally, odds, payout and reputation math
+pub mod tally;
+
+// Plaintext voting, tallying and payouts for markets that run without the MXE
+pub mod public_markets;
+pub use public_markets::*;
+
+// Markets conditioned on another market's outcome
+pub mod conditional_markets;
+pub use conditional_markets::*;
+
+// Parlays: one bet across several markets, settled by MPC once every leg resolves
+pub mod parlays;
+pub use parlays::*;
+
+// Circuit versions
+//
+// Market-bound circuits are compiled under versioned names (`<circuit>_v<N>`).
+// Each market records the version it was created with and keeps settling on those
+// computation definitions after newer circuits ship. To add a version, compile the
+// new `_v<N+1>` circuits, add their offsets and instructions alongside the old ones
+// (checking `circuit_version` in the account constraints), and bump
+// CURRENT_CIRCUIT_VERSION so only new markets pick them up. Circuits a version
+// doesn't replace keep their old name and accept the newer versions too.
+//
+// V2 replaces aggregate_market_votes_v1 with aggregate_market_votes_v2, which
+// aggregates votes in accumulator order and echoes the accumulator root.
+// V3 replaces calculate_payout_v1 with calculate_payout_v2, which takes protocol
+// and creator fees off the total stake first.
+pub const CIRCUIT_VERSION_V1: u8 = 1;
+pub const CIRCUIT_VERSION_V2: u8 = 2;
+pub const CIRCUIT_VERSION_V3: u8 = 3;
+pub const CURRENT_CIRCUIT_VERSION: u8 = CIRCUIT_VERSION_V3;
+
 // Computation definition offsets for each encrypted instruction
-const COMP_DEF_OFFSET_SUBMIT_VOTE: u32 = comp_def_offset("submit_private_vote");
-const COMP_DEF_OFFSET_AGGREGATE: u32 = comp_def_offset("aggregate_market_votes");
-const COMP_DEF_OFFSET_CALCULATE_PAYOUT: u32 = comp_def_offset("calculate_payout");
-const COMP_DEF_OFFSET_CALCULATE_ODDS: u32 = comp_def_offset("calculate_market_odds");
+const COMP_DEF_OFFSET_SUBMIT_VOTE_V1: u32 = comp_def_offset("submit_private_vote_v1");
+const COMP_DEF_OFFSET_AGGREGATE_V1: u32 = comp_def_offset("aggregate_market_votes_v1");
+const COMP_DEF_OFFSET_AGGREGATE_V2: u32 = comp_def_offset("aggregate_market_votes_v2");
+const COMP_DEF_OFFSET_CALCULATE_PAYOUT_V1: u32 = comp_def_offset("calculate_payout_v1");
+const COMP_DEF_OFFSET_CALCULATE_PAYOUT_V2: u32 = comp_def_offset("calculate_payout_v2");
+const COMP_DEF_OFFSET_CALCULATE_ODDS_V1: u32 = comp_def_offset("calculate_market_odds_v1");
+const COMP_DEF_OFFSET_UPDATE_REPUTATION_V1: u32 = comp_def_offset("update_reputation_v1");
+const COMP_DEF_OFFSET_INSERT_LEADERBOARD: u32 = comp_def_offset("insert_leaderboard_entry");
+const COMP_DEF_OFFSET_REVEAL_LEADERBOARD: u32 = comp_def_offset("reveal_leaderboard");
+const COMP_DEF_OFFSET_REENCRYPT_MARKET_STATE_V1: u32 = comp_def_offset("reencrypt_market_state_v1");
+const COMP_DEF_OFFSET_REENCRYPT_POSITIONS_V1: u32 = comp_def_offset("reencrypt_positions_v1");
+const COMP_DEF_OFFSET_DISCLOSE_VOTE_V1: u32 = comp_def_offset("disclose_vote_v1");
+const COMP_DEF_OFFSET_REVEAL_VOTES_V1: u32 = comp_def_offset("reveal_votes_v1");
+const COMP_DEF_OFFSET_CALCULATE_PAYOUT_HYBRID_V1: u32 = comp_def_offset("calculate_payout_hybrid_v1");
+const COMP_DEF_OFFSET_CALCULATE_PUBLIC_PAYOUT_V1: u32 = comp_def_offset("calculate_public_payout_v1");
+const COMP_DEF_OFFSET_CALCULATE_ODDS_HYBRID_V1: u32 = comp_def_offset("calculate_market_odds_hybrid_v1");
+const COMP_DEF_OFFSET_CALCULATE_PARLAY_PAYOUT_V1: u32 = comp_def_offset("calculate_parlay_payout_v1");
+
+/// Number of ranked slots on a seasonal leaderboard (matches the circuit)
+pub const LEADERBOARD_SIZE: usize = 10;
+
+/// Positions revealed per `reveal_positions` computation (matches the circuit)
+pub const REVEAL_BATCH_SIZE: usize = 4;
 
 declare_id!("6crfTQztShryQeMRaPG5H5Uf7Zd69wyPRRF4AFBndh9F");
 
@@ -22,63 +119,175 @@ pub mod prediction_markets {
     // COMPUTATION DEFINITION INITIALIZATION
     // =====================================================================
 
-    /// Initialize computation definition for submit_private_vote with offchain circuit storage
+    /// Create the circuit registry; only the program config admin may, and becomes its admin
+    pub fn initialize_circuit_registry(ctx: Context<InitializeCircuitRegistry>) -> Result<()> {
+        let registry = &mut ctx.accounts.circuit_registry;
+        registry.admin = ctx.accounts.admin.key();
+        registry.entries = Vec::new();
+        registry.version = CircuitRegistry::VERSION;
+        registry.bump = ctx.bumps.circuit_registry;
+
+        Ok(())
+    }
+
+    /// Create the computation offset allocator; program config admin only
+    ///
+    /// # Arguments
+    /// * `first_offset` - First offset to hand out; pick one above any offsets
+    ///   clients used before the allocator existed
+    pub fn initialize_computation_allocator(
+        ctx: Context<InitializeComputationAllocator>,
+        first_offset: u64,
+    ) -> Result<()> {
+        let allocator = &mut ctx.accounts.computation_allocator;
+        allocator.version = ComputationAllocator::VERSION;
+        allocator.bump = ctx.bumps.computation_allocator;
+        allocator.next_offset = first_offset;
+
+        Ok(())
+    }
+
+    /// Register (or replace) the source URL and expected hash of a compiled circuit
+    ///
+    /// # Arguments
+    /// * `comp_def_offset` - `comp_def_offset("<circuit name>")` of the circuit
+    /// * `source` - URL of the compiled `.arcis` file (max 200 chars)
+    /// * `hash` - SHA-256 of the compiled `.arcis` file, as emitted by `npm run circuit-hashes`
+    pub fn set_circuit_source(
+        ctx: Context<SetCircuitSource>,
+        comp_def_offset: u32,
+        source: String,
+        hash: [u8; 32],
+    ) -> Result<()> {
+        ctx.accounts
+            .circuit_registry
+            .set_entry(comp_def_offset, source.clone(), hash)?;
+
+        emit!(CircuitSourceUpdatedEvent {
+            comp_def_offset,
+            source,
+            hash,
+        });
+
+        Ok(())
+    }
+
+    /// Initialize computation definition for submit_private_vote_v1 from the circuit registry
     pub fn init_submit_vote_comp_def(ctx: Context<InitSubmitVoteCompDef>) -> Result<()> {
-        init_comp_def(
-            ctx.accounts,
-            true,
-            0,
-            Some(CircuitSource::OffChain(OffChainCircuitSource {
-                source: "https://esuvpguudqdlgwxhweqp.supabase.co/storage/v1/object/public/arcium-circuits/submit_private_vote_testnet.arcis".to_string(),
-                hash: [0; 32], // Hash verification not enforced yet
-            })),
-            None,
-        )?;
+        let source = ctx.accounts.circuit_registry.circuit_source(COMP_DEF_OFFSET_SUBMIT_VOTE_V1)?;
+        init_comp_def(ctx.accounts, true, 0, Some(source), None)?;
         Ok(())
     }
 
-    /// Initialize computation definition for aggregate_market_votes with offchain circuit storage
+    /// Initialize computation definition for aggregate_market_votes_v1 from the circuit registry
     pub fn init_aggregate_comp_def(ctx: Context<InitAggregateCompDef>) -> Result<()> {
-        init_comp_def(
-            ctx.accounts,
-            true,
-            0,
-            Some(CircuitSource::OffChain(OffChainCircuitSource {
-                source: "https://esuvpguudqdlgwxhweqp.supabase.co/storage/v1/object/public/arcium-circuits/aggregate_market_votes_testnet.arcis".to_string(),
-                hash: [0; 32],
-            })),
-            None,
-        )?;
+        let source = ctx.accounts.circuit_registry.circuit_source(COMP_DEF_OFFSET_AGGREGATE_V1)?;
+        init_comp_def(ctx.accounts, true, 0, Some(source), None)?;
+        Ok(())
+    }
+
+    /// Initialize computation definition for aggregate_market_votes_v2 from the circuit registry
+    pub fn init_aggregate_v2_comp_def(ctx: Context<InitAggregateV2CompDef>) -> Result<()> {
+        let source = ctx.accounts.circuit_registry.circuit_source(COMP_DEF_OFFSET_AGGREGATE_V2)?;
+        init_comp_def(ctx.accounts, true, 0, Some(source), None)?;
         Ok(())
     }
 
-    /// Initialize computation definition for calculate_payout with offchain circuit storage
+    /// Initialize computation definition for calculate_payout_v1 from the circuit registry
     pub fn init_calculate_payout_comp_def(ctx: Context<InitCalculatePayoutCompDef>) -> Result<()> {
-        init_comp_def(
-            ctx.accounts,
-            true,
-            0,
-            Some(CircuitSource::OffChain(OffChainCircuitSource {
-                source: "https://esuvpguudqdlgwxhweqp.supabase.co/storage/v1/object/public/arcium-circuits/calculate_payout_testnet.arcis".to_string(),
-                hash: [0; 32],
-            })),
-            None,
-        )?;
+        let source = ctx.accounts.circuit_registry.circuit_source(COMP_DEF_OFFSET_CALCULATE_PAYOUT_V1)?;
+        init_comp_def(ctx.accounts, true, 0, Some(source), None)?;
+        Ok(())
+    }
+
+    /// Initialize computation definition for calculate_payout_v2 from the circuit registry
+    pub fn init_calculate_payout_v2_comp_def(ctx: Context<InitCalculatePayoutV2CompDef>) -> Result<()> {
+        let source = ctx.accounts.circuit_registry.circuit_source(COMP_DEF_OFFSET_CALCULATE_PAYOUT_V2)?;
+        init_comp_def(ctx.accounts, true, 0, Some(source), None)?;
         Ok(())
     }
 
-    /// Initialize computation definition for calculate_market_odds with offchain circuit storage
+    /// Initialize computation definition for calculate_market_odds_v1 from the circuit registry
     pub fn init_calculate_odds_comp_def(ctx: Context<InitCalculateOddsCompDef>) -> Result<()> {
-        init_comp_def(
-            ctx.accounts,
-            true,
-            0,
-            Some(CircuitSource::OffChain(OffChainCircuitSource {
-                source: "https://esuvpguudqdlgwxhweqp.supabase.co/storage/v1/object/public/arcium-circuits/calculate_market_odds_testnet.arcis".to_string(),
-                hash: [0; 32],
-            })),
-            None,
-        )?;
+        let source = ctx.accounts.circuit_registry.circuit_source(COMP_DEF_OFFSET_CALCULATE_ODDS_V1)?;
+        init_comp_def(ctx.accounts, true, 0, Some(source), None)?;
+        Ok(())
+    }
+
+    /// Initialize computation definition for update_reputation_v1 from the circuit registry
+    pub fn init_update_reputation_comp_def(ctx: Context<InitUpdateReputationCompDef>) -> Result<()> {
+        let source = ctx.accounts.circuit_registry.circuit_source(COMP_DEF_OFFSET_UPDATE_REPUTATION_V1)?;
+        init_comp_def(ctx.accounts, true, 0, Some(source), None)?;
+        Ok(())
+    }
+
+    /// Initialize computation definition for insert_leaderboard_entry from the circuit registry
+    pub fn init_insert_leaderboard_comp_def(ctx: Context<InitInsertLeaderboardCompDef>) -> Result<()> {
+        let source = ctx.accounts.circuit_registry.circuit_source(COMP_DEF_OFFSET_INSERT_LEADERBOARD)?;
+        init_comp_def(ctx.accounts, true, 0, Some(source), None)?;
+        Ok(())
+    }
+
+    /// Initialize computation definition for reveal_leaderboard from the circuit registry
+    pub fn init_reveal_leaderboard_comp_def(ctx: Context<InitRevealLeaderboardCompDef>) -> Result<()> {
+        let source = ctx.accounts.circuit_registry.circuit_source(COMP_DEF_OFFSET_REVEAL_LEADERBOARD)?;
+        init_comp_def(ctx.accounts, true, 0, Some(source), None)?;
+        Ok(())
+    }
+
+    /// Initialize computation definition for reencrypt_market_state_v1 from the circuit registry
+    pub fn init_reencrypt_market_state_comp_def(ctx: Context<InitReencryptMarketStateCompDef>) -> Result<()> {
+        let source = ctx.accounts.circuit_registry.circuit_source(COMP_DEF_OFFSET_REENCRYPT_MARKET_STATE_V1)?;
+        init_comp_def(ctx.accounts, true, 0, Some(source), None)?;
+        Ok(())
+    }
+
+    /// Initialize computation definition for reencrypt_positions_v1 from the circuit registry
+    pub fn init_reencrypt_positions_comp_def(ctx: Context<InitReencryptPositionsCompDef>) -> Result<()> {
+        let source = ctx.accounts.circuit_registry.circuit_source(COMP_DEF_OFFSET_REENCRYPT_POSITIONS_V1)?;
+        init_comp_def(ctx.accounts, true, 0, Some(source), None)?;
+        Ok(())
+    }
+
+    /// Initialize computation definition for disclose_vote_v1 from the circuit registry
+    pub fn init_disclose_vote_comp_def(ctx: Context<InitDiscloseVoteCompDef>) -> Result<()> {
+        let source = ctx.accounts.circuit_registry.circuit_source(COMP_DEF_OFFSET_DISCLOSE_VOTE_V1)?;
+        init_comp_def(ctx.accounts, true, 0, Some(source), None)?;
+        Ok(())
+    }
+
+    /// Initialize computation definition for reveal_votes_v1 from the circuit registry
+    pub fn init_reveal_votes_comp_def(ctx: Context<InitRevealVotesCompDef>) -> Result<()> {
+        let source = ctx.accounts.circuit_registry.circuit_source(COMP_DEF_OFFSET_REVEAL_VOTES_V1)?;
+        init_comp_def(ctx.accounts, true, 0, Some(source), None)?;
+        Ok(())
+    }
+
+    /// Initialize computation definition for calculate_payout_hybrid_v1 from the circuit registry
+    pub fn init_calculate_payout_hybrid_comp_def(ctx: Context<InitCalculatePayoutHybridCompDef>) -> Result<()> {
+        let source = ctx.accounts.circuit_registry.circuit_source(COMP_DEF_OFFSET_CALCULATE_PAYOUT_HYBRID_V1)?;
+        init_comp_def(ctx.accounts, true, 0, Some(source), None)?;
+        Ok(())
+    }
+
+    /// Initialize computation definition for calculate_public_payout_v1 from the circuit registry
+    pub fn init_calculate_public_payout_comp_def(ctx: Context<InitCalculatePublicPayoutCompDef>) -> Result<()> {
+        let source = ctx.accounts.circuit_registry.circuit_source(COMP_DEF_OFFSET_CALCULATE_PUBLIC_PAYOUT_V1)?;
+        init_comp_def(ctx.accounts, true, 0, Some(source), None)?;
+        Ok(())
+    }
+
+    /// Initialize computation definition for calculate_market_odds_hybrid_v1 from the circuit registry
+    pub fn init_calculate_odds_hybrid_comp_def(ctx: Context<InitCalculateOddsHybridCompDef>) -> Result<()> {
+        let source = ctx.accounts.circuit_registry.circuit_source(COMP_DEF_OFFSET_CALCULATE_ODDS_HYBRID_V1)?;
+        init_comp_def(ctx.accounts, true, 0, Some(source), None)?;
+        Ok(())
+    }
+
+    /// Initialize computation definition for calculate_parlay_payout_v1 from the circuit registry
+    pub fn init_calculate_parlay_payout_comp_def(ctx: Context<InitCalculateParlayPayoutCompDef>) -> Result<()> {
+        let source = ctx.accounts.circuit_registry.circuit_source(COMP_DEF_OFFSET_CALCULATE_PARLAY_PAYOUT_V1)?;
+        init_comp_def(ctx.accounts, true, 0, Some(source), None)?;
         Ok(())
     }
 
@@ -86,33 +295,116 @@ pub mod prediction_markets {
     // MARKET CREATION & MANAGEMENT
     // =====================================================================
 
+    /// Create the market registry that assigns market ids; program config admin only
+    ///
+    /// # Arguments
+    /// * `first_market_id` - First id to assign; pick one above any ids markets
+    ///   used before the registry existed
+    pub fn initialize_market_registry(
+        ctx: Context<InitializeMarketRegistry>,
+        first_market_id: u64,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.market_registry;
+        registry.version = MarketRegistry::VERSION;
+        registry.bump = ctx.bumps.market_registry;
+        registry.next_market_id = first_market_id;
+        registry.category_counts = [0; MARKET_CATEGORY_COUNT];
+
+        Ok(())
+    }
+
     /// Creates a new prediction market with encrypted voting
     ///
+    /// The market id comes from the market registry, and the market is listed
+    /// in its category's and creator's index pages.
+    ///
     /// # Arguments
-    /// * `market_id` - Unique identifier for this market
-    /// * `title` - Market question (max 200 chars)
-    /// * `description` - Detailed description (max 1000 chars)
-    /// * `image_url` - Market image URL (max 200 chars)
+    /// * `metadata_uri` - Where the market's metadata document is hosted (max 200 chars)
+    /// * `metadata_hash` - `MarketMetadata::content_hash` of that document
     /// * `category` - Market category (Sports, Politics, etc.)
     /// * `voting_ends_at` - Unix timestamp when voting closes
     /// * `oracle_type` - Type of oracle for resolution
+    /// * `time_decay_enabled` - Weight earlier votes higher in odds and payouts
+    /// * `reveal_after_resolution` - Publish every vote once the market resolves
+    /// * `creator_fee_bps` - Creator's cut of the total stake, up to the config's cap
+    /// * `privacy_mode` - Private for MXE-encrypted votes, Public for plaintext ones,
+    ///   Hybrid to let each voter choose
+    /// * `parent_outcome` - For a conditional market, the outcome of `parent_market`
+    ///   it's conditioned on; None for a standalone market
     pub fn create_market(
         ctx: Context<CreateMarket>,
-        market_id: u64,
-        title: String,
-        description: String,
-        image_url: String,
+        metadata_uri: String,
+        metadata_hash: [u8; 32],
         category: MarketCategory,
         voting_ends_at: i64,
         oracle_type: OracleType,
+        time_decay_enabled: bool,
+        reveal_after_resolution: bool,
+        creator_fee_bps: u16,
+        privacy_mode: PrivacyMode,
+        parent_outcome: Option<bool>,
     ) -> Result<()> {
-        require!(title.len() <= 200, ErrorCode::TitleTooLong);
-        require!(description.len() <= 1000, ErrorCode::DescriptionTooLong);
-        require!(image_url.len() <= 200, ErrorCode::ImageUrlTooLong);
+        validate_metadata_commitment(&metadata_uri, &metadata_hash)?;
         require!(
             voting_ends_at > Clock::get()?.unix_timestamp,
             ErrorCode::InvalidEndTime
         );
+        require!(
+            creator_fee_bps <= ctx.accounts.program_config.max_creator_fee_bps,
+            ErrorCode::CreatorFeeTooHigh
+        );
+
+        // A conditional market's parent has to still be open
+        let parent_market = match (&ctx.accounts.parent_market, parent_outcome) {
+            (Some(parent), Some(_)) => {
+                require!(
+                    parent.status == MarketStatus::Active || parent.status == MarketStatus::Locked,
+                    ErrorCode::InvalidParentMarket
+                );
+                Some(parent.key())
+            }
+            (None, None) => None,
+            _ => return err!(ErrorCode::InvalidParentMarket),
+        };
+
+        // The bond stays in the market account until resolution or slashing
+        let creation_bond = ctx.accounts.program_config.creation_bond(category);
+        if creation_bond > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.creator.to_account_info(),
+                        to: ctx.accounts.prediction_market.to_account_info(),
+                    },
+                ),
+                creation_bond,
+            )?;
+        }
+
+        // Assign the id and list the market under its category and creator
+        let market_key = ctx.accounts.prediction_market.key();
+        let market_id = ctx.accounts.market_registry.assign_market_id();
+        let category_count = ctx.accounts.market_registry.category_counts[category.slot()];
+        ctx.accounts.category_index_page.push(
+            ctx.bumps.category_index_page,
+            MarketIndexPage::page_for(category_count),
+            market_key,
+        )?;
+        ctx.accounts.market_registry.category_counts[category.slot()] += 1;
+
+        let creator_markets = &mut ctx.accounts.creator_markets;
+        if creator_markets.market_count == 0 {
+            creator_markets.version = CreatorMarkets::VERSION;
+            creator_markets.bump = ctx.bumps.creator_markets;
+            creator_markets.creator = ctx.accounts.creator.key();
+        }
+        ctx.accounts.creator_index_page.push(
+            ctx.bumps.creator_index_page,
+            MarketIndexPage::page_for(creator_markets.market_count),
+            market_key,
+        )?;
+        creator_markets.market_count += 1;
 
         let market = &mut ctx.accounts.prediction_market;
         market.market_id = market_id;
@@ -125,35 +417,89 @@ pub mod prediction_markets {
         market.oracle_type = oracle_type;
         market.oracle_pubkey = ctx.accounts.oracle.key();
         market.resolved_outcome = None;
+        market.time_decay_enabled = time_decay_enabled;
+        market.reveal_after_resolution = reveal_after_resolution;
+        market.privacy_mode = privacy_mode;
+        market.parent_market = parent_market;
+        market.parent_outcome = parent_outcome.unwrap_or_default();
+        market.circuit_version = CURRENT_CIRCUIT_VERSION;
+        market.key_epoch = ctx.accounts.mxe_key_epoch.epoch;
+        market.aggregated_vote_root = [0; 32];
+        market.aggregated_vote_count = 0;
+        market.protocol_fee_bps = ctx.accounts.program_config.protocol_fee_bps;
+        market.creator_fee_bps = creator_fee_bps;
+        market.fee_amount = 0;
+        market.creator_fees_owed = 0;
+        market.creation_bond = creation_bond;
+        market.version = PredictionMarket::VERSION;
         market.bump = ctx.bumps.prediction_market;
 
         // Initialize encrypted vote state (will be set after MPC initialization)
-        market.encrypted_vote_state = [[0; 32]; 11]; // 11 encrypted fields
+        market.encrypted_vote_state = [[0; 32]; 17]; // 17 encrypted fields
         market.nonce = 0;
 
-        // Store dynamic strings directly
-        market.title = title.clone();
-        market.description = description.clone();
-        market.image_url = image_url.clone();
+        market.metadata_hash = metadata_hash;
+        market.metadata_uri = metadata_uri.clone();
 
         market.total_stake = 0;
         market.yes_stake = 0;
         market.no_stake = 0;
         market.participant_count = 0;
 
+        market.odds_yes = 50;
+        market.odds_no = 50;
+        market.herding_index = 0;
+        market.odds_updated_at = 0;
+        market.final_odds_locked = false;
+
         emit!(MarketCreatedEvent {
             market_id,
             creator: market.creator,
-            title: title,
+            metadata_uri,
+            metadata_hash,
             category: category as u8,
             oracle_type: market.oracle_type as u8,
             voting_ends_at,
+            creation_bond,
+            privacy_mode: privacy_mode as u8,
+            parent_market,
             timestamp: market.created_at,
         });
 
         Ok(())
     }
 
+    /// Point a market at a new metadata document; only until the first vote
+    ///
+    /// # Arguments
+    /// * `metadata_uri` - Where the new document is hosted (max 200 chars)
+    /// * `metadata_hash` - `MarketMetadata::content_hash` of the new document
+    pub fn update_market_metadata(
+        ctx: Context<UpdateMarketMetadata>,
+        metadata_uri: String,
+        metadata_hash: [u8; 32],
+    ) -> Result<()> {
+        validate_metadata_commitment(&metadata_uri, &metadata_hash)?;
+        // The accumulator is created by the first vote and outlives rollbacks
+        require!(
+            ctx.accounts.prediction_market.participant_count == 0
+                && ctx.accounts.vote_accumulator.data_is_empty(),
+            ErrorCode::MetadataLocked
+        );
+
+        let market = &mut ctx.accounts.prediction_market;
+        market.metadata_hash = metadata_hash;
+        market.metadata_uri = metadata_uri.clone();
+
+        emit!(MarketMetadataUpdatedEvent {
+            market_id: market.market_id,
+            metadata_uri,
+            metadata_hash,
+        });
+
+        Ok(())
+    }
+
     /// Initialize user profile for tracking stats and reputation
     pub fn initialize_user_profile(ctx: Context<InitializeUserProfile>) -> Result<()> {
         let profile = &mut ctx.accounts.user_profile;
@@ -167,8 +513,16 @@ pub mod prediction_markets {
         profile.last_activity = Clock::get()?.unix_timestamp;
         profile.achievements = [0; 32];
         profile.preferred_categories = 0;
+        profile.version = UserProfile::VERSION;
         profile.bump = ctx.bumps.user_profile;
 
+        // Sealed reputation is populated by the first update_reputation callback
+        profile.encrypted_reputation = [[0; 32]; 5];
+        profile.reputation_nonce = 0;
+        profile.has_private_reputation = false;
+        profile.leaderboard_season = 0;
+        profile.parlay_count = 0;
+
         Ok(())
     }
 
@@ -202,7 +556,8 @@ pub mod prediction_markets {
             Clock::get()?.unix_timestamp < ctx.accounts.prediction_market.voting_ends_at,
             ErrorCode::VotingPeriodEnded
         );
-        require!(stake_amount > 0, ErrorCode::InvalidStakeAmount);
+        require!(stake_amount > 0 && stake_amount <= MAX_STAKE, ErrorCode::InvalidStakeAmount);
+        let total_stake = add_stake(ctx.accounts.prediction_market.total_stake, stake_amount)?;
 
         // Capture position key before mutable borrow
         let position_key = ctx.accounts.user_position.key();
@@ -220,8 +575,32 @@ pub mod prediction_markets {
         position.encrypted_vote_data = vote_data_encrypted;
         position.vote_pubkey = vote_encryption_pubkey;
         position.vote_nonce = vote_nonce;
+        position.is_payout_calculated = false;
+        position.reputation_recorded = false;
+        position.key_epoch = ctx.accounts.mxe_key_epoch.epoch;
+        position.disclosure_count = 0;
+        position.is_revealed = false;
+        position.is_public = false;
+        position.version = UserPosition::VERSION;
         position.bump = ctx.bumps.user_position;
 
+        // Commit the ciphertext to the market's vote accumulator
+        let accumulator = &mut ctx.accounts.vote_accumulator;
+        if accumulator.market == Pubkey::default() {
+            accumulator.version = VoteAccumulator::VERSION;
+            accumulator.bump = ctx.bumps.vote_accumulator;
+            accumulator.market = ctx.accounts.prediction_market.key();
+        }
+        let leaf = VoteAccumulator::vote_leaf(
+            &vote_data_encrypted,
+            &vote_encryption_pubkey,
+            vote_nonce,
+        );
+        let leaf_index = accumulator.append(leaf)?;
+        position.leaf_index = leaf_index;
+        position.vote_root = accumulator.root;
+        position.is_aggregated = false;
+
         // Queue MPC computation for vote validation
         // Note: voter field is derived from user pubkey on-chain, not passed as encrypted
         let args = vec![
@@ -235,8 +614,22 @@ pub mod prediction_markets {
             Argument::EncryptedU16(vote_data_encrypted[4]),       // conviction_score
             Argument::EncryptedU64(vote_data_encrypted[5]),       // timestamp
             Argument::EncryptedU128(vote_data_encrypted[6]),      // nonce
+            // Solana clock, used to reject votes with a stale or future timestamp
+            Argument::PlaintextU64(current_timestamp as u64),
+            // Stake actually taken, which the sealed stake_amount must match
+            Argument::PlaintextU64(stake_amount),
         ];
 
+        ctx.accounts.computation_allocator.advance();
+        ctx.accounts.computation_record.set_inner(ComputationRecord::queued(
+            ctx.bumps.computation_record,
+            computation_offset,
+            ComputationKind::SubmitVote,
+            position_key,
+            user_key,
+            1,
+            current_timestamp,
+        ));
         ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
 
         queue_computation(
@@ -244,17 +637,23 @@ pub mod prediction_markets {
             computation_offset,
             args,
             None,
-            vec![SubmitPrivateVoteCallback::callback_ix(&[CallbackAccount {
-                pubkey: position_key,
-                is_writable: true,
-            }])],
+            vec![SubmitPrivateVoteV1Callback::callback_ix(&[
+                CallbackAccount {
+                    pubkey: position_key,
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_record.key(),
+                    is_writable: true,
+                },
+            ])],
         )?;
 
         // Update market stats (public aggregates)
         let market = &mut ctx.accounts.prediction_market;
         let market_id = market.market_id;
         market.participant_count += 1;
-        market.total_stake += stake_amount;
+        market.total_stake = total_stake;
 
         // Update user profile
         let profile = &mut ctx.accounts.user_profile;
@@ -268,25 +667,36 @@ pub mod prediction_markets {
             timestamp: current_timestamp,
         });
 
+        emit!(VoteCommittedEvent {
+            market_id,
+            position: position_key,
+            leaf_index,
+            leaf,
+            root: ctx.accounts.vote_accumulator.root,
+        });
+
         Ok(())
     }
 
-    #[arcium_callback(encrypted_ix = "submit_private_vote")]
-    pub fn submit_private_vote_callback(
-        ctx: Context<SubmitPrivateVoteCallback>,
-        output: ComputationOutputs<SubmitPrivateVoteOutput>,
+    #[arcium_callback(encrypted_ix = "submit_private_vote_v1")]
+    pub fn submit_private_vote_v1_callback(
+        ctx: Context<SubmitPrivateVoteV1Callback>,
+        output: ComputationOutputs<SubmitPrivateVoteV1Output>,
     ) -> Result<()> {
-        let validation_result = match output {
-            ComputationOutputs::Success(SubmitPrivateVoteOutput { field_0 }) => field_0,
-            _ => return Err(ErrorCode::AbortedComputation.into()),
+        let now = Clock::get()?.unix_timestamp;
+        let is_valid = match output {
+            ComputationOutputs::Success(SubmitPrivateVoteV1Output { field_0 }) => field_0,
+            _ => return ctx.accounts.computation_record.mark_failed(now),
         };
 
-        // Check if vote was valid (1 = valid, 0 = invalid)
-        if validation_result.ciphertexts[0][0] == 0 {
-            return Err(ErrorCode::InvalidVoteData.into());
+        // Revealed verdict (1 = valid, 0 = invalid); an invalid vote is a failed
+        // attempt too, so it can be retried or rolled back
+        if is_valid != 1 {
+            return ctx.accounts.computation_record.mark_failed(now);
         }
 
         ctx.accounts.user_position.is_validated = true;
+        ctx.accounts.computation_record.mark_succeeded(now);
 
         Ok(())
     }
@@ -316,15 +726,26 @@ pub mod prediction_markets {
             Argument::EncryptedU16(position.encrypted_vote_data[4]),
             Argument::EncryptedU64(position.encrypted_vote_data[5]),
             Argument::EncryptedU128(position.encrypted_vote_data[6]),
+            // Solana clock and voting window for timestamp checks and time decay
+            Argument::PlaintextU64(Clock::get()?.unix_timestamp as u64),
+            Argument::PlaintextU64(market.created_at as u64),
+            Argument::PlaintextU64(market.voting_ends_at as u64),
+            Argument::PlaintextBool(market.time_decay_enabled),
             // Pass current encrypted market state
             Argument::PlaintextU128(market.nonce),
-            Argument::Account(
-                market.key(),
-                8 + 1,  // discriminator + bump
-                32 * 11, // encrypted_vote_state [[u8; 32]; 11] = 352 bytes
-            ),
+            Argument::Account(market.key(), MARKET_VOTE_STATE_OFFSET, MARKET_VOTE_STATE_LEN),
         ];
 
+        ctx.accounts.computation_allocator.advance();
+        ctx.accounts.computation_record.set_inner(ComputationRecord::queued(
+            ctx.bumps.computation_record,
+            computation_offset,
+            ComputationKind::AggregateVotes,
+            ctx.accounts.user_position.key(),
+            ctx.accounts.payer.key(),
+            1,
+            Clock::get()?.unix_timestamp,
+        ));
         ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
 
         queue_computation(
@@ -332,111 +753,240 @@ pub mod prediction_markets {
             computation_offset,
             args,
             None,
-            vec![AggregateMarketVotesCallback::callback_ix(&[CallbackAccount {
-                pubkey: ctx.accounts.prediction_market.key(),
-                is_writable: true,
-            }])],
+            vec![AggregateMarketVotesV1Callback::callback_ix(&[
+                CallbackAccount {
+                    pubkey: ctx.accounts.prediction_market.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.user_position.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_record.key(),
+                    is_writable: true,
+                },
+            ])],
         )?;
 
         Ok(())
     }
 
-    #[arcium_callback(encrypted_ix = "aggregate_market_votes")]
-    pub fn aggregate_market_votes_callback(
-        ctx: Context<AggregateMarketVotesCallback>,
-        output: ComputationOutputs<AggregateMarketVotesOutput>,
+    #[arcium_callback(encrypted_ix = "aggregate_market_votes_v1")]
+    pub fn aggregate_market_votes_v1_callback(
+        ctx: Context<AggregateMarketVotesV1Callback>,
+        output: ComputationOutputs<AggregateMarketVotesV1Output>,
     ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
         let market_state = match output {
-            ComputationOutputs::Success(AggregateMarketVotesOutput { field_0 }) => field_0,
-            _ => return Err(ErrorCode::AbortedComputation.into()),
+            ComputationOutputs::Success(AggregateMarketVotesV1Output { field_0 }) => field_0,
+            _ => return ctx.accounts.computation_record.mark_failed(now),
         };
 
-        // Update encrypted market state (11 fields from MarketVotingState)
-        let encrypted_state: [[u8; 32]; 11] = market_state.ciphertexts;
+        // Another aggregation of this vote landed first; keep the state as it was
+        if ctx.accounts.user_position.is_aggregated {
+            return ctx.accounts.computation_record.mark_failed(now);
+        }
+
+        // Update encrypted market state (17 fields from MarketVotingState)
+        let encrypted_state: [[u8; 32]; 17] = market_state.ciphertexts;
         ctx.accounts.prediction_market.encrypted_vote_state = encrypted_state;
         ctx.accounts.prediction_market.nonce = market_state.nonce;
+        ctx.accounts.user_position.is_aggregated = true;
+        ctx.accounts.computation_record.mark_succeeded(now);
 
         Ok(())
     }
 
-    // =====================================================================
-    // ORACLE RESOLUTION
-    // =====================================================================
-
-    /// Resolve market with oracle outcome
+    /// Aggregate the next validated vote in accumulator order
     ///
-    /// Only authorized oracle can call this to finalize the market
-    pub fn resolve_market(
-        ctx: Context<ResolveMarket>,
-        outcome: bool,
+    /// Passes the accumulator root recorded when the vote was committed; the
+    /// circuit echoes it back and the callback stores it as the market's
+    /// `aggregated_vote_root`, so the sealed tally provably covers exactly the
+    /// first `aggregated_vote_count` committed votes.
+    pub fn aggregate_votes_v2(
+        ctx: Context<AggregateVotesV2>,
+        computation_offset: u64,
     ) -> Result<()> {
         require!(
-            ctx.accounts.oracle.key() == ctx.accounts.prediction_market.oracle_pubkey,
-            ErrorCode::InvalidOracle
+            ctx.accounts.prediction_market.status == MarketStatus::Active,
+            ErrorCode::MarketNotActive
         );
+
+        let market = &ctx.accounts.prediction_market;
+        let position = &ctx.accounts.user_position;
         require!(
-            ctx.accounts.prediction_market.status == MarketStatus::Active
-                || ctx.accounts.prediction_market.status == MarketStatus::Locked,
-            ErrorCode::CannotResolveMarket
+            position.leaf_index == market.aggregated_vote_count,
+            ErrorCode::VoteOutOfOrder
         );
 
+        let (root_hi, root_lo) = position.vote_root.split_at(16);
+        let root_hi = u128::from_be_bytes(root_hi.try_into().unwrap());
+        let root_lo = u128::from_be_bytes(root_lo.try_into().unwrap());
+
+        let mut args = position.vote_arguments();
+        args.extend([
+            // Solana clock and voting window for timestamp checks and time decay
+            Argument::PlaintextU64(Clock::get()?.unix_timestamp as u64),
+            Argument::PlaintextU64(market.created_at as u64),
+            Argument::PlaintextU64(market.voting_ends_at as u64),
+            Argument::PlaintextBool(market.time_decay_enabled),
+            // Accumulator root after this vote was committed, echoed by the circuit
+            Argument::PlaintextU128(root_hi),
+            Argument::PlaintextU128(root_lo),
+            // Pass current encrypted market state
+            Argument::PlaintextU128(market.nonce),
+            Argument::Account(market.key(), MARKET_VOTE_STATE_OFFSET, MARKET_VOTE_STATE_LEN),
+        ]);
+
+        ctx.accounts.computation_allocator.advance();
+        ctx.accounts.computation_record.set_inner(ComputationRecord::queued(
+            ctx.bumps.computation_record,
+            computation_offset,
+            ComputationKind::AggregateVotes,
+            ctx.accounts.user_position.key(),
+            ctx.accounts.payer.key(),
+            1,
+            Clock::get()?.unix_timestamp,
+        ));
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![AggregateMarketVotesV2Callback::callback_ix(&[
+                CallbackAccount {
+                    pubkey: ctx.accounts.prediction_market.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.user_position.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_record.key(),
+                    is_writable: true,
+                },
+            ])],
+        )?;
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "aggregate_market_votes_v2")]
+    pub fn aggregate_market_votes_v2_callback(
+        ctx: Context<AggregateMarketVotesV2Callback>,
+        output: ComputationOutputs<AggregateMarketVotesV2Output>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let (market_state, root_hi, root_lo) = match output {
+            ComputationOutputs::Success(AggregateMarketVotesV2Output {
+                field_0:
+                    AggregateMarketVotesV2OutputStruct0 {
+                        field_0,
+                        field_1,
+                        field_2,
+                    },
+            }) => (field_0, field_1, field_2),
+            _ => return ctx.accounts.computation_record.mark_failed(now),
+        };
+
+        let mut root = [0u8; 32];
+        root[..16].copy_from_slice(&root_hi.to_be_bytes());
+        root[16..].copy_from_slice(&root_lo.to_be_bytes());
+
+        // Another aggregation of this vote landed first, or the echoed root
+        // isn't the one committed for it; keep the state as it was
         let market = &mut ctx.accounts.prediction_market;
-        market.status = MarketStatus::Resolved;
-        market.resolved_outcome = Some(outcome);
-        market.resolution_timestamp = Clock::get()?.unix_timestamp;
+        let position = &mut ctx.accounts.user_position;
+        if position.is_aggregated
+            || position.leaf_index != market.aggregated_vote_count
+            || root != position.vote_root
+        {
+            return ctx.accounts.computation_record.mark_failed(now);
+        }
 
-        emit!(MarketResolvedEvent {
+        market.encrypted_vote_state = market_state.ciphertexts;
+        market.nonce = market_state.nonce;
+        market.aggregated_vote_root = root;
+        market.aggregated_vote_count += 1;
+        position.is_aggregated = true;
+        ctx.accounts.computation_record.mark_succeeded(now);
+
+        emit!(VoteAggregatedEvent {
             market_id: market.market_id,
-            outcome,
-            resolution_timestamp: market.resolution_timestamp,
+            leaf_index: position.leaf_index,
+            aggregated_vote_root: root,
+            timestamp: now,
         });
 
         Ok(())
     }
 
     // =====================================================================
-    // PAYOUT CALCULATION & CLAIMS
+    // FAILED COMPUTATION RECOVERY
     // =====================================================================
 
-    /// Calculate payout for user using MPC
+    /// Re-queue vote validation for a position whose last attempt failed
     ///
-    /// This queues an encrypted computation to calculate the user's payout
-    /// based on their encrypted vote and the market outcome
-    pub fn calculate_user_payout(
-        ctx: Context<CalculateUserPayout>,
+    /// Only vote validation needs this: aggregation, payout, reputation and odds
+    /// computations don't create accounts, so they are retried by calling their
+    /// instruction again once the previous record shows `Failed`.
+    pub fn retry_computation(
+        ctx: Context<RetryComputation>,
         computation_offset: u64,
     ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let failed = &ctx.accounts.failed_record;
         require!(
-            ctx.accounts.prediction_market.status == MarketStatus::Resolved,
-            ErrorCode::MarketNotResolved
+            failed.status == ComputationStatus::Failed,
+            ErrorCode::ComputationNotFailed
         );
         require!(
-            !ctx.accounts.user_position.is_claimed,
-            ErrorCode::PayoutAlreadyClaimed
+            failed.attempts < MAX_COMPUTATION_ATTEMPTS,
+            ErrorCode::RetryLimitReached
+        );
+        require!(
+            ctx.accounts.prediction_market.status == MarketStatus::Active,
+            ErrorCode::MarketNotActive
+        );
+        require!(
+            now < ctx.accounts.prediction_market.voting_ends_at,
+            ErrorCode::VotingPeriodEnded
         );
 
-        let market = &ctx.accounts.prediction_market;
+        let attempts = failed.attempts + 1;
         let position = &ctx.accounts.user_position;
 
-        // Build encrypted PayoutData and queue MPC computation
         let args = vec![
             Argument::ArcisPubkey(position.vote_pubkey),
             Argument::PlaintextU128(position.vote_nonce),
-            // User's encrypted vote data (voter derived from position.user)
             Argument::EncryptedU64(position.encrypted_vote_data[0]),       // market_id
-            Argument::EncryptedU64(position.encrypted_vote_data[2]),       // user_stake
-            Argument::EncryptedU8(position.encrypted_vote_data[1]),        // user_vote
-            Argument::EncryptedU8(position.encrypted_vote_data[3]),        // user_probability
-            Argument::EncryptedU16(position.encrypted_vote_data[4]),       // user_conviction
-            // Market outcome (plaintext since it's public after resolution)
-            Argument::PlaintextU8(if market.resolved_outcome.unwrap() { 1 } else { 0 }),
-            Argument::PlaintextU64(market.yes_stake),
-            Argument::PlaintextU64(market.no_stake),
-            // Bonus pools (could be dynamic based on market size)
-            Argument::PlaintextU64(market.total_stake / 100), // 1% accuracy bonus pool
-            Argument::PlaintextU64(market.total_stake / 200), // 0.5% conviction bonus pool
+            Argument::EncryptedU8(position.encrypted_vote_data[1]),        // vote_choice
+            Argument::EncryptedU64(position.encrypted_vote_data[2]),       // stake_amount
+            Argument::EncryptedU8(position.encrypted_vote_data[3]),        // predicted_probability
+            Argument::EncryptedU16(position.encrypted_vote_data[4]),       // conviction_score
+            Argument::EncryptedU64(position.encrypted_vote_data[5]),       // timestamp
+            Argument::EncryptedU128(position.encrypted_vote_data[6]),      // nonce
+            // Check the vote timestamp against the clock at submission, not now
+            Argument::PlaintextU64(position.timestamp as u64),
+            Argument::PlaintextU64(position.stake_amount),
         ];
 
+        ctx.accounts.failed_record.status = ComputationStatus::Retried;
+        ctx.accounts.failed_record.updated_at = now;
+        ctx.accounts.computation_allocator.advance();
+        ctx.accounts.computation_record.set_inner(ComputationRecord::queued(
+            ctx.bumps.computation_record,
+            computation_offset,
+            ComputationKind::SubmitVote,
+            ctx.accounts.user_position.key(),
+            ctx.accounts.payer.key(),
+            attempts,
+            now,
+        ));
         ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
 
         queue_computation(
@@ -444,92 +994,155 @@ pub mod prediction_markets {
             computation_offset,
             args,
             None,
-            vec![CalculatePayoutCallback::callback_ix(&[CallbackAccount {
-                pubkey: ctx.accounts.user_position.key(),
-                is_writable: true,
-            }])],
+            vec![SubmitPrivateVoteV1Callback::callback_ix(&[
+                CallbackAccount {
+                    pubkey: ctx.accounts.user_position.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_record.key(),
+                    is_writable: true,
+                },
+            ])],
         )?;
 
-        Ok(())
-    }
-
-    #[arcium_callback(encrypted_ix = "calculate_payout")]
-    pub fn calculate_payout_callback(
-        ctx: Context<CalculatePayoutCallback>,
-        output: ComputationOutputs<CalculatePayoutOutput>,
-    ) -> Result<()> {
-        let payout_amount_encrypted = match output {
-            ComputationOutputs::Success(CalculatePayoutOutput { field_0 }) => field_0,
-            _ => return Err(ErrorCode::AbortedComputation.into()),
-        };
-
-        // Store encrypted payout amount
-        ctx.accounts.user_position.encrypted_payout = payout_amount_encrypted.ciphertexts[0];
-        ctx.accounts.user_position.payout_nonce = payout_amount_encrypted.nonce;
+        emit!(ComputationRetriedEvent {
+            failed_offset: ctx.accounts.failed_record.computation_offset,
+            computation_offset,
+            target: ctx.accounts.user_position.key(),
+            attempts,
+        });
 
         Ok(())
     }
 
-    /// Claim payout after calculation
+    /// Unwind a vote that never validated and refund the position's rent
     ///
-    /// User must decrypt their payout amount client-side and verify
-    pub fn claim_payout(ctx: Context<ClaimPayout>) -> Result<()> {
-        require!(
-            ctx.accounts.prediction_market.status == MarketStatus::Resolved,
-            ErrorCode::MarketNotResolved
-        );
+    /// Permissionless, so keepers can clean up. Allowed once the latest attempt has
+    /// failed MAX_COMPUTATION_ATTEMPTS times, failed after voting closed (so it can't
+    /// be retried), or has had no callback for COMPUTATION_TIMEOUT seconds.
+    pub fn rollback_failed_vote(ctx: Context<RollbackFailedVote>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let record = &ctx.accounts.computation_record;
+        let failed = record.status == ComputationStatus::Failed;
         require!(
-            !ctx.accounts.user_position.is_claimed,
-            ErrorCode::PayoutAlreadyClaimed
+            (failed && record.attempts >= MAX_COMPUTATION_ATTEMPTS)
+                || (failed && now >= ctx.accounts.prediction_market.voting_ends_at)
+                || record.is_timed_out(now),
+            ErrorCode::RollbackNotAllowed
         );
 
-        let position = &mut ctx.accounts.user_position;
-        position.is_claimed = true;
+        let stake_amount = ctx.accounts.user_position.stake_amount;
+        let leaf_index = ctx.accounts.user_position.leaf_index;
 
-        // In production, this would transfer tokens from market vault to user
-        // For now, just mark as claimed
+        // Stop counting the stake and participant in the public stats
+        let market = &mut ctx.accounts.prediction_market;
+        market.total_stake = market.total_stake.saturating_sub(stake_amount);
+        market.participant_count = market.participant_count.saturating_sub(1);
 
-        // Update user profile
-        let _profile = &mut ctx.accounts.user_profile;
-        // Payout amount would be decrypted client-side
-        // _profile.total_winnings += decrypted_payout;
+        // Markets that aggregate in accumulator order skip the rolled-back leaf,
+        // which has to be next in line so no later vote is left behind it
+        if market.circuit_version >= CIRCUIT_VERSION_V2 {
+            require!(
+                leaf_index == market.aggregated_vote_count,
+                ErrorCode::VoteOutOfOrder
+            );
+            market.aggregated_vote_root = ctx.accounts.user_position.vote_root;
+            market.aggregated_vote_count += 1;
+        }
 
-        emit!(PayoutClaimedEvent {
-            market_id: ctx.accounts.prediction_market.market_id,
-            user: position.user,
-            timestamp: Clock::get()?.unix_timestamp,
-        });
+        let profile = &mut ctx.accounts.user_profile;
+        profile.total_markets_participated = profile.total_markets_participated.saturating_sub(1);
+
+        let record = &mut ctx.accounts.computation_record;
+        record.status = ComputationStatus::RolledBack;
+        record.updated_at = now;
+
+        emit!(VoteRolledBackEvent {
+            market_id: market.market_id,
+            user: ctx.accounts.user.key(),
+            leaf_index,
+            stake_amount,
+            attempts: record.attempts,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Close a finished computation record and return its rent to whoever paid for it
+    pub fn close_computation_record(ctx: Context<CloseComputationRecord>) -> Result<()> {
+        require!(
+            ctx.accounts.computation_record.is_settled(),
+            ErrorCode::ComputationNotSettled
+        );
 
         Ok(())
     }
 
     // =====================================================================
-    // MARKET STATISTICS & ODDS
+    // MXE KEY ROTATION
     // =====================================================================
 
-    /// Calculate market odds using encrypted vote data
+    /// Create the MXE key epoch tracker at epoch 0; program config admin only
+    pub fn initialize_mxe_key_epoch(ctx: Context<InitializeMxeKeyEpoch>) -> Result<()> {
+        let key_epoch = &mut ctx.accounts.mxe_key_epoch;
+        key_epoch.version = MxeKeyEpoch::VERSION;
+        key_epoch.bump = ctx.bumps.mxe_key_epoch;
+        key_epoch.admin = ctx.accounts.admin.key();
+        key_epoch.epoch = 0;
+        key_epoch.rotated_at = Clock::get()?.unix_timestamp;
+
+        Ok(())
+    }
+
+    /// Record that the cluster rotated its MXE key
     ///
-    /// This reveals aggregated market odds without revealing individual votes
-    pub fn calculate_market_odds(
-        ctx: Context<CalculateMarketOdds>,
+    /// Markets and positions sealed under the previous epoch can't be aggregated
+    /// until they've been re-encrypted with `reencrypt_market_state` and
+    /// `reencrypt_positions`. Call this once the cluster has the new key live and
+    /// can still decrypt under the old one.
+    pub fn record_mxe_key_rotation(ctx: Context<RecordMxeKeyRotation>) -> Result<()> {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let key_epoch = &mut ctx.accounts.mxe_key_epoch;
+        key_epoch.rotate(timestamp);
+
+        emit!(MxeKeyRotatedEvent {
+            epoch: key_epoch.epoch,
+            timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Re-seal a market's encrypted vote state under the current MXE key
+    ///
+    /// Permissionless, so keepers can migrate every live market after a rotation.
+    pub fn reencrypt_market_state(
+        ctx: Context<ReencryptMarketState>,
         computation_offset: u64,
     ) -> Result<()> {
+        let market = &ctx.accounts.prediction_market;
         require!(
-            ctx.accounts.prediction_market.status == MarketStatus::Active,
-            ErrorCode::MarketNotActive
+            ctx.accounts.mxe_key_epoch.is_stale(market.key_epoch),
+            ErrorCode::KeyEpochCurrent
         );
 
-        let market = &ctx.accounts.prediction_market;
-
         let args = vec![
             Argument::PlaintextU128(market.nonce),
-            Argument::Account(
-                market.key(),
-                8 + 1,  // discriminator + bump
-                32 * 11, // encrypted_vote_state [[u8; 32]; 11] = 352 bytes
-            ),
+            Argument::Account(market.key(), MARKET_VOTE_STATE_OFFSET, MARKET_VOTE_STATE_LEN),
         ];
 
+        ctx.accounts.computation_allocator.advance();
+        ctx.accounts.computation_record.set_inner(ComputationRecord::queued(
+            ctx.bumps.computation_record,
+            computation_offset,
+            ComputationKind::ReencryptMarketState,
+            ctx.accounts.prediction_market.key(),
+            ctx.accounts.payer.key(),
+            1,
+            Clock::get()?.unix_timestamp,
+        ));
         ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
 
         queue_computation(
@@ -537,195 +1150,4632 @@ pub mod prediction_markets {
             computation_offset,
             args,
             None,
-            vec![CalculateMarketOddsCallback::callback_ix(&[])],
+            vec![ReencryptMarketStateV1Callback::callback_ix(&[
+                CallbackAccount {
+                    pubkey: ctx.accounts.prediction_market.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.mxe_key_epoch.key(),
+                    is_writable: false,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_record.key(),
+                    is_writable: true,
+                },
+            ])],
         )?;
 
         Ok(())
     }
 
-    #[arcium_callback(encrypted_ix = "calculate_market_odds")]
-    pub fn calculate_market_odds_callback(
-        ctx: Context<CalculateMarketOddsCallback>,
-        output: ComputationOutputs<CalculateMarketOddsOutput>,
+    #[arcium_callback(encrypted_ix = "reencrypt_market_state_v1")]
+    pub fn reencrypt_market_state_v1_callback(
+        ctx: Context<ReencryptMarketStateV1Callback>,
+        output: ComputationOutputs<ReencryptMarketStateV1Output>,
     ) -> Result<()> {
-        let _odds_tuple = match output {
-            ComputationOutputs::Success(CalculateMarketOddsOutput { field_0 }) => field_0,
-            _ => return Err(ErrorCode::AbortedComputation.into()),
+        let now = Clock::get()?.unix_timestamp;
+        let market_state = match output {
+            ComputationOutputs::Success(ReencryptMarketStateV1Output { field_0 }) => field_0,
+            _ => return ctx.accounts.computation_record.mark_failed(now),
         };
 
-        emit!(MarketOddsUpdatedEvent {
-            market_id: ctx.accounts.prediction_market.market_id,
-            // odds_tuple is (yes_prob, no_prob, participants, high_confidence)
-            timestamp: Clock::get()?.unix_timestamp,
+        let market = &mut ctx.accounts.prediction_market;
+        market.encrypted_vote_state = market_state.ciphertexts;
+        market.nonce = market_state.nonce;
+        market.key_epoch = ctx.accounts.mxe_key_epoch.epoch;
+        ctx.accounts.computation_record.mark_succeeded(now);
+
+        emit!(MarketStateReencryptedEvent {
+            market_id: market.market_id,
+            key_epoch: market.key_epoch,
+            timestamp: now,
         });
 
         Ok(())
     }
-}
 
-// =====================================================================
-// ACCOUNT STRUCTURES
-// =====================================================================
+    /// Re-encrypt up to REENCRYPT_BATCH_SIZE positions of one market under the
+    /// current MXE key
+    ///
+    /// Positions are passed as writable remaining accounts. Each vote stays
+    /// encrypted to its owner's x25519 key, so clients decrypt it with the shared
+    /// secret derived from the new MXE public key.
+    pub fn reencrypt_positions<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReencryptPositions<'info>>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        let positions = ctx.remaining_accounts;
+        require!(
+            !positions.is_empty() && positions.len() <= REENCRYPT_BATCH_SIZE,
+            ErrorCode::InvalidReencryptBatch
+        );
 
-/// Main prediction market account with encrypted vote state
-/// Note: encrypted_vote_state and nonce are placed early for fixed offset access
-#[account]
-pub struct PredictionMarket {
-    pub bump: u8,
-    // Encrypted vote aggregation state (stored at fixed offset for MPC)
-    // MarketVotingState has 11 u64 fields (market_id, yes/no counts, stakes, participants, etc.)
-    pub encrypted_vote_state: [[u8; 32]; 11],
-    pub nonce: u128,
-    // Market identifiers and metadata
-    pub market_id: u64,
-    pub creator: Pubkey,
-    pub category: MarketCategory,
-    pub status: MarketStatus,
-    pub created_at: i64,
-    pub voting_ends_at: i64,
-    pub resolution_timestamp: i64,
-    pub oracle_type: OracleType,
-    pub oracle_pubkey: Pubkey,
-    pub resolved_outcome: Option<bool>,
-    // Public aggregate stats (not encrypted)
-    pub total_stake: u64,
-    pub yes_stake: u64,
-    pub no_stake: u64,
-    pub participant_count: u32,
-    // Variable-length fields at end
-    pub title: String,           // Dynamic string (4 + len bytes)
-    pub description: String,     // Dynamic string (4 + len bytes)
-    pub image_url: String,       // Dynamic string (4 + len bytes)
-}
+        let market_key = ctx.accounts.prediction_market.key();
+        let mut args = Vec::with_capacity(REENCRYPT_BATCH_SIZE * 9);
+        let mut callback_accounts = vec![
+            CallbackAccount {
+                pubkey: ctx.accounts.mxe_key_epoch.key(),
+                is_writable: false,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.computation_record.key(),
+                is_writable: true,
+            },
+        ];
 
-/// User's encrypted position in a market
-#[account]
-pub struct UserPosition {
-    pub user: Pubkey,
-    pub market: Pubkey,
-    pub stake_amount: u64,
-    pub timestamp: i64,
-    pub is_claimed: bool,
-    pub is_validated: bool,
-    // Encrypted vote data (7 fields: market_id, vote_choice, stake, prob, conviction, timestamp, nonce)
-    // voter field is derived from user pubkey, not encrypted
-    pub encrypted_vote_data: [[u8; 32]; 7],
-    pub vote_pubkey: [u8; 32],
-    pub vote_nonce: u128,
-    // Encrypted payout result
-    pub encrypted_payout: [u8; 32],
-    pub payout_nonce: u128,
-    pub payout_amount: u64, // Decrypted amount (set client-side before claim)
-    pub bump: u8,
-}
+        for slot in 0..REENCRYPT_BATCH_SIZE {
+            // Unused slots repeat the last position; the callback discards them
+            let info = &positions[slot.min(positions.len() - 1)];
+            let position = Account::<UserPosition>::try_from(info)?;
+            require!(is_current(&position), ErrorCode::AccountNotMigrated);
+            require_keys_eq!(position.market, market_key, ErrorCode::InvalidReencryptBatch);
+            require!(!position.is_public, ErrorCode::InvalidReencryptBatch);
 
-/// User profile for reputation and statistics
-#[account]
-pub struct UserProfile {
-    pub user: Pubkey,
-    pub total_markets_participated: u32,
-    pub correct_predictions: u32,
-    pub total_winnings: u64,
-    pub reputation_score: u16,
-    pub streak_current: u16,
-    pub streak_best: u16,
-    pub last_activity: i64,
-    pub achievements: [u8; 32],
-    pub preferred_categories: u16,
-    pub bump: u8,
-}
+            if slot < positions.len() {
+                require!(
+                    ctx.accounts.mxe_key_epoch.is_stale(position.key_epoch),
+                    ErrorCode::KeyEpochCurrent
+                );
+                callback_accounts.push(CallbackAccount {
+                    pubkey: info.key(),
+                    is_writable: true,
+                });
+            }
 
-// =====================================================================
-// ENUMS
-// =====================================================================
+            args.extend(position.vote_arguments());
+        }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
-pub enum MarketStatus {
-    Active,
-    Locked,
-    Resolved,
-    Settled,
-    Cancelled,
-}
+        ctx.accounts.computation_allocator.advance();
+        ctx.accounts.computation_record.set_inner(ComputationRecord::queued(
+            ctx.bumps.computation_record,
+            computation_offset,
+            ComputationKind::ReencryptPositions,
+            market_key,
+            ctx.accounts.payer.key(),
+            1,
+            Clock::get()?.unix_timestamp,
+        ));
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
-pub enum MarketCategory {
-    Sports = 0,
-    Politics = 1,
-    Economics = 2,
-    Technology = 3,
-    Entertainment = 4,
-    Weather = 5,
-    Custom = 99,
-}
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![ReencryptPositionsV1Callback::callback_ix(&callback_accounts)],
+        )?;
 
-// =====================================================================
-// ACCOUNT VALIDATION CONTEXTS
-// =====================================================================
+        Ok(())
+    }
 
-#[derive(Accounts)]
-#[instruction(market_id: u64)]
-pub struct CreateMarket<'info> {
-    #[account(mut)]
-    pub creator: Signer<'info>,
-    #[account(
-        init,
-        payer = creator,
-        space = 8 + // discriminator
-                1 + // bump
-                352 + // encrypted_vote_state [[u8; 32]; 11]
-                16 + // nonce (u128)
-                8 + // market_id
-                32 + // creator
-                1 + // category
-                1 + // status
-                8 + // created_at
-                8 + // voting_ends_at
-                8 + // resolution_timestamp
-                1 + // oracle_type
-                32 + // oracle_pubkey
-                2 + // resolved_outcome (Option<bool>)
-                8 + // total_stake
-                8 + // yes_stake
-                8 + // no_stake
-                4 + // participant_count
-                4 + 200 + // title (String max 200)
-                4 + 1000 + // description (String max 1000)
-                4 + 200, // image_url (String max 200)
-        seeds = [b"market", market_id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub prediction_market: Account<'info, PredictionMarket>,
-    /// CHECK: Oracle address validation handled in instruction
-    pub oracle: UncheckedAccount<'info>,
-    pub system_program: Program<'info, System>,
-}
+    #[arcium_callback(encrypted_ix = "reencrypt_positions_v1")]
+    pub fn reencrypt_positions_v1_callback<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReencryptPositionsV1Callback<'info>>,
+        output: ComputationOutputs<ReencryptPositionsV1Output>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let votes = match output {
+            ComputationOutputs::Success(ReencryptPositionsV1Output {
+                field_0:
+                    ReencryptPositionsV1OutputStruct0 {
+                        field_0,
+                        field_1,
+                        field_2,
+                        field_3,
+                    },
+            }) => [field_0, field_1, field_2, field_3],
+            _ => return ctx.accounts.computation_record.mark_failed(now),
+        };
 
-#[derive(Accounts)]
-pub struct InitializeUserProfile<'info> {
+        let key_epoch = ctx.accounts.mxe_key_epoch.epoch;
+        let mut market = Pubkey::default();
+        for (info, vote) in ctx.remaining_accounts.iter().zip(votes) {
+            let mut position = Account::<UserPosition>::try_from(info)?;
+            position.encrypted_vote_data = vote.ciphertexts;
+            position.vote_nonce = vote.nonce;
+            position.key_epoch = key_epoch;
+            position.exit(&crate::ID)?;
+            market = position.market;
+        }
+        ctx.accounts.computation_record.mark_succeeded(now);
+
+        emit!(PositionsReencryptedEvent {
+            market,
+            key_epoch,
+            positions: ctx.remaining_accounts.len() as u8,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    // =====================================================================
+    // SELECTIVE DISCLOSURE
+    // =====================================================================
+
+    /// Disclose a position's vote to a chosen third party
+    ///
+    /// Queues an MPC computation that re-encrypts the vote from the user's shared
+    /// key to the recipient's x25519 key and writes it to a new DisclosureRecord,
+    /// which doubles as the public log of the disclosure.
+    ///
+    /// # Arguments
+    /// * `recipient_pubkey` - Recipient's x25519 public key
+    /// * `recipient_nonce` - Nonce for the recipient's ciphertext
+    pub fn disclose_position(
+        ctx: Context<DisclosePosition>,
+        computation_offset: u64,
+        recipient_pubkey: [u8; 32],
+        recipient_nonce: u128,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let position_key = ctx.accounts.user_position.key();
+        let disclosure_key = ctx.accounts.disclosure_record.key();
+        let position = &mut ctx.accounts.user_position;
+
+        let disclosure = &mut ctx.accounts.disclosure_record;
+        disclosure.version = DisclosureRecord::VERSION;
+        disclosure.bump = ctx.bumps.disclosure_record;
+        disclosure.position = position_key;
+        disclosure.user = position.user;
+        disclosure.recipient_pubkey = recipient_pubkey;
+        disclosure.index = position.disclosure_count;
+        disclosure.encrypted_vote = [[0; 32]; 7];
+        disclosure.vote_nonce = 0;
+        disclosure.is_complete = false;
+        disclosure.requested_at = now;
+
+        let mut args = position.vote_arguments();
+        // Recipient the output is encrypted to
+        args.push(Argument::ArcisPubkey(recipient_pubkey));
+        args.push(Argument::PlaintextU128(recipient_nonce));
+
+        position.disclosure_count += 1;
+
+        ctx.accounts.computation_allocator.advance();
+        ctx.accounts.computation_record.set_inner(ComputationRecord::queued(
+            ctx.bumps.computation_record,
+            computation_offset,
+            ComputationKind::DiscloseVote,
+            disclosure_key,
+            ctx.accounts.user.key(),
+            1,
+            now,
+        ));
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![DiscloseVoteV1Callback::callback_ix(&[
+                CallbackAccount {
+                    pubkey: disclosure_key,
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_record.key(),
+                    is_writable: true,
+                },
+            ])],
+        )?;
+
+        emit!(PositionDisclosedEvent {
+            position: position_key,
+            user: ctx.accounts.user.key(),
+            recipient_pubkey,
+            disclosure_index: ctx.accounts.disclosure_record.index,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "disclose_vote_v1")]
+    pub fn disclose_vote_v1_callback(
+        ctx: Context<DiscloseVoteV1Callback>,
+        output: ComputationOutputs<DiscloseVoteV1Output>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let vote = match output {
+            ComputationOutputs::Success(DiscloseVoteV1Output { field_0 }) => field_0,
+            _ => return ctx.accounts.computation_record.mark_failed(now),
+        };
+
+        let disclosure = &mut ctx.accounts.disclosure_record;
+        disclosure.encrypted_vote = vote.ciphertexts;
+        disclosure.vote_nonce = vote.nonce;
+        disclosure.is_complete = true;
+        ctx.accounts.computation_record.mark_succeeded(now);
+
+        Ok(())
+    }
+
+    // =====================================================================
+    // ORACLE RESOLUTION
+    // =====================================================================
+
+    /// Resolve market with oracle outcome
+    ///
+    /// Only authorized oracle can call this to finalize the market. A conditional
+    /// market also takes its parent, which must have resolved to `parent_outcome`
+    pub fn resolve_market(
+        ctx: Context<ResolveMarket>,
+        outcome: bool,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.oracle.key() == ctx.accounts.prediction_market.oracle_pubkey,
+            ErrorCode::InvalidOracle
+        );
+        require!(
+            ctx.accounts.prediction_market.status == MarketStatus::Active
+                || ctx.accounts.prediction_market.status == MarketStatus::Locked,
+            ErrorCode::CannotResolveMarket
+        );
+        match parent_condition(
+            &ctx.accounts.prediction_market,
+            ctx.accounts.parent_market.as_deref(),
+        )? {
+            ParentCondition::Met => {}
+            ParentCondition::Pending => return err!(ErrorCode::ParentMarketUnresolved),
+            ParentCondition::Failed => return err!(ErrorCode::ParentConditionFailed),
+        }
+
+        let market = &mut ctx.accounts.prediction_market;
+        market.status = MarketStatus::Resolved;
+        market.resolved_outcome = Some(outcome);
+        market.resolution_timestamp = Clock::get()?.unix_timestamp;
+        // Each vote moves a public market's odds, so they're already final
+        market.final_odds_locked = market.privacy_mode == PrivacyMode::Public;
+
+        emit!(MarketResolvedEvent {
+            market_id: market.market_id,
+            outcome,
+            resolution_timestamp: market.resolution_timestamp,
+        });
+
+        // Charge fees on the final stake; calculate_payout_v2 deducts them before
+        // splitting the pools
+        if market.circuit_version >= CIRCUIT_VERSION_V3 {
+            let protocol_fee = fee_amount(market.total_stake, market.protocol_fee_bps);
+            let creator_fee = fee_amount(market.total_stake, market.creator_fee_bps);
+            market.fee_amount = protocol_fee + creator_fee;
+            market.creator_fees_owed = creator_fee;
+
+            let treasury = &mut ctx.accounts.treasury;
+            treasury.protocol_fees_accrued += protocol_fee;
+            treasury.creator_fees_accrued += creator_fee;
+            treasury.total_protocol_fees += protocol_fee;
+
+            emit!(FeesChargedEvent {
+                market_id: market.market_id,
+                protocol_fee,
+                creator_fee,
+                timestamp: market.resolution_timestamp,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Cancel a market whose question can't be resolved, slashing the creator's bond
+    ///
+    /// Only the market's oracle can call this. The bond goes to `dispute_winner`
+    /// when one is passed, otherwise to the treasury.
+    pub fn cancel_market(ctx: Context<CancelMarket>) -> Result<()> {
+        require!(
+            ctx.accounts.prediction_market.status == MarketStatus::Active
+                || ctx.accounts.prediction_market.status == MarketStatus::Locked,
+            ErrorCode::CannotCancelMarket
+        );
+
+        let (bond_slashed, bond_recipient) = slash_creation_bond(
+            &mut ctx.accounts.prediction_market,
+            &mut ctx.accounts.treasury,
+            ctx.accounts.dispute_winner.as_ref().map(|winner| winner.as_ref()),
+        )?;
+        let market = &mut ctx.accounts.prediction_market;
+        market.status = MarketStatus::Cancelled;
+
+        emit!(MarketCancelledEvent {
+            market_id: market.market_id,
+            malformed: false,
+            bond_slashed,
+            bond_recipient,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Cancel a conditional market whose parent resolved against its condition or
+    /// was cancelled
+    ///
+    /// Permissionless. The bond isn't slashed; the creator reclaims it with
+    /// `reclaim_creation_bond` and voters get their stakes back with `claim_refund`.
+    pub fn cancel_conditional_market(ctx: Context<CancelConditionalMarket>) -> Result<()> {
+        cancel_for_failed_condition(
+            &mut ctx.accounts.prediction_market,
+            Some(ctx.accounts.parent_market.as_ref()),
+        )
+    }
+
+    // =====================================================================
+    // PAYOUT CALCULATION & CLAIMS
+    // =====================================================================
+
+    /// Calculate payout for user using MPC
+    ///
+    /// This queues an encrypted computation to calculate the user's payout
+    /// based on their encrypted vote and the market outcome
+    pub fn calculate_user_payout(
+        ctx: Context<CalculateUserPayout>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.prediction_market.status == MarketStatus::Resolved,
+            ErrorCode::MarketNotResolved
+        );
+        require!(
+            !ctx.accounts.user_position.is_claimed,
+            ErrorCode::PayoutAlreadyClaimed
+        );
+
+        let market = &ctx.accounts.prediction_market;
+        let position = &ctx.accounts.user_position;
+
+        // Build encrypted PayoutData and queue MPC computation
+        let args = vec![
+            Argument::ArcisPubkey(position.vote_pubkey),
+            Argument::PlaintextU128(position.vote_nonce),
+            // User's encrypted vote data (voter derived from position.user)
+            Argument::EncryptedU64(position.encrypted_vote_data[0]),       // market_id
+            Argument::EncryptedU64(position.encrypted_vote_data[2]),       // user_stake
+            Argument::EncryptedU8(position.encrypted_vote_data[1]),        // user_vote
+            Argument::EncryptedU8(position.encrypted_vote_data[3]),        // user_probability
+            Argument::EncryptedU16(position.encrypted_vote_data[4]),       // user_conviction
+            Argument::EncryptedU64(position.encrypted_vote_data[5]),       // user_timestamp
+            // Market outcome (plaintext since it's public after resolution)
+            Argument::PlaintextU8(if market.resolved_outcome.unwrap() { 1 } else { 0 }),
+            // Bonus pools, carved out of the total stake inside the circuit
+            Argument::PlaintextU64(market.total_stake / 100), // 1% accuracy bonus pool (Brier-scored)
+            Argument::PlaintextU64(market.total_stake / 200), // 0.5% conviction bonus pool
+            // Voting window for time-decay weighting
+            Argument::PlaintextU64(market.created_at as u64),
+            Argument::PlaintextU64(market.voting_ends_at as u64),
+            Argument::PlaintextBool(market.time_decay_enabled),
+            // Encrypted market totals and Brier score sums
+            Argument::PlaintextU128(market.nonce),
+            Argument::Account(market.key(), MARKET_VOTE_STATE_OFFSET, MARKET_VOTE_STATE_LEN),
+        ];
+
+        ctx.accounts.computation_allocator.advance();
+        ctx.accounts.computation_record.set_inner(ComputationRecord::queued(
+            ctx.bumps.computation_record,
+            computation_offset,
+            ComputationKind::CalculatePayout,
+            ctx.accounts.user_position.key(),
+            ctx.accounts.user.key(),
+            1,
+            Clock::get()?.unix_timestamp,
+        ));
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![CalculatePayoutV1Callback::callback_ix(&[
+                CallbackAccount {
+                    pubkey: ctx.accounts.user_position.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_record.key(),
+                    is_writable: true,
+                },
+            ])],
+        )?;
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "calculate_payout_v1")]
+    pub fn calculate_payout_v1_callback(
+        ctx: Context<CalculatePayoutV1Callback>,
+        output: ComputationOutputs<CalculatePayoutV1Output>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let payout_amount_encrypted = match output {
+            ComputationOutputs::Success(CalculatePayoutV1Output { field_0 }) => field_0,
+            _ => return ctx.accounts.computation_record.mark_failed(now),
+        };
+
+        // Store encrypted payout amount
+        ctx.accounts.user_position.encrypted_payout = payout_amount_encrypted.ciphertexts[0];
+        ctx.accounts.user_position.payout_nonce = payout_amount_encrypted.nonce;
+        ctx.accounts.user_position.is_payout_calculated = true;
+        ctx.accounts.computation_record.mark_succeeded(now);
+
+        Ok(())
+    }
+
+    /// Calculate payout for user using MPC, after protocol and creator fees
+    ///
+    /// Same as `calculate_user_payout`, for markets on circuit version 3 and up;
+    /// the fees charged at resolution come off the total stake before the pools
+    /// are split.
+    pub fn calculate_user_payout_v2(
+        ctx: Context<CalculateUserPayoutV2>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.prediction_market.status == MarketStatus::Resolved,
+            ErrorCode::MarketNotResolved
+        );
+        require!(
+            !ctx.accounts.user_position.is_claimed,
+            ErrorCode::PayoutAlreadyClaimed
+        );
+
+        let market = &ctx.accounts.prediction_market;
+        let position = &ctx.accounts.user_position;
+
+        // The circuit builds PayoutData from the stored vote (voter derived from
+        // position.user) and the plaintext outcome and pools
+        let mut args = position.vote_arguments();
+        args.extend([
+            // Market outcome (plaintext since it's public after resolution)
+            Argument::PlaintextU8(if market.resolved_outcome.unwrap() { 1 } else { 0 }),
+            // Bonus pools, carved out of the total stake inside the circuit
+            Argument::PlaintextU64(market.total_stake / 100), // 1% accuracy bonus pool (Brier-scored)
+            Argument::PlaintextU64(market.total_stake / 200), // 0.5% conviction bonus pool
+            // Protocol + creator fees charged at resolution
+            Argument::PlaintextU64(market.fee_amount),
+            // Voting window for time-decay weighting
+            Argument::PlaintextU64(market.created_at as u64),
+            Argument::PlaintextU64(market.voting_ends_at as u64),
+            Argument::PlaintextBool(market.time_decay_enabled),
+            // Encrypted market totals and Brier score sums
+            Argument::PlaintextU128(market.nonce),
+            Argument::Account(market.key(), MARKET_VOTE_STATE_OFFSET, MARKET_VOTE_STATE_LEN),
+        ]);
+
+        ctx.accounts.computation_allocator.advance();
+        ctx.accounts.computation_record.set_inner(ComputationRecord::queued(
+            ctx.bumps.computation_record,
+            computation_offset,
+            ComputationKind::CalculatePayout,
+            ctx.accounts.user_position.key(),
+            ctx.accounts.user.key(),
+            1,
+            Clock::get()?.unix_timestamp,
+        ));
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![CalculatePayoutV2Callback::callback_ix(&[
+                CallbackAccount {
+                    pubkey: ctx.accounts.user_position.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_record.key(),
+                    is_writable: true,
+                },
+            ])],
+        )?;
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "calculate_payout_v2")]
+    pub fn calculate_payout_v2_callback(
+        ctx: Context<CalculatePayoutV2Callback>,
+        output: ComputationOutputs<CalculatePayoutV2Output>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let payout_amount_encrypted = match output {
+            ComputationOutputs::Success(CalculatePayoutV2Output { field_0 }) => field_0,
+            _ => return ctx.accounts.computation_record.mark_failed(now),
+        };
+
+        ctx.accounts.user_position.encrypted_payout = payout_amount_encrypted.ciphertexts[0];
+        ctx.accounts.user_position.payout_nonce = payout_amount_encrypted.nonce;
+        ctx.accounts.user_position.is_payout_calculated = true;
+        ctx.accounts.computation_record.mark_succeeded(now);
+
+        Ok(())
+    }
+
+    /// Record the outcome of a position in the user's sealed reputation using MPC
+    ///
+    /// Win/loss is determined inside the circuit from the encrypted vote, so the
+    /// profile's reputation is updated without revealing which side the user took.
+    /// Must run after `calculate_user_payout` and before `claim_payout`.
+    pub fn record_private_reputation(
+        ctx: Context<RecordPrivateReputation>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.prediction_market.status == MarketStatus::Resolved,
+            ErrorCode::MarketNotResolved
+        );
+        require!(
+            ctx.accounts.user_position.is_payout_calculated,
+            ErrorCode::PayoutNotCalculated
+        );
+        require!(
+            !ctx.accounts.user_position.reputation_recorded,
+            ErrorCode::ReputationAlreadyRecorded
+        );
+
+        let market = &ctx.accounts.prediction_market;
+        let position = &ctx.accounts.user_position;
+        let profile = &ctx.accounts.user_profile;
+
+        // User's encrypted vote data
+        let mut args = position.vote_arguments();
+        args.extend([
+            // Encrypted payout from calculate_payout (same shared key as the vote)
+            Argument::ArcisPubkey(position.vote_pubkey),
+            Argument::PlaintextU128(position.payout_nonce),
+            Argument::EncryptedU64(position.encrypted_payout),
+            // Market outcome (plaintext since it's public after resolution)
+            Argument::PlaintextU8(if market.resolved_outcome.unwrap() { 1 } else { 0 }),
+            Argument::PlaintextBool(profile.has_private_reputation),
+            // Current sealed reputation state
+            Argument::PlaintextU128(profile.reputation_nonce),
+            Argument::Account(
+                profile.key(),
+                USER_PROFILE_REPUTATION_OFFSET,
+                32 * 5, // encrypted_reputation [[u8; 32]; 5] = 160 bytes
+            ),
+        ]);
+
+        ctx.accounts.computation_allocator.advance();
+        ctx.accounts.computation_record.set_inner(ComputationRecord::queued(
+            ctx.bumps.computation_record,
+            computation_offset,
+            ComputationKind::UpdateReputation,
+            ctx.accounts.user_position.key(),
+            ctx.accounts.user.key(),
+            1,
+            Clock::get()?.unix_timestamp,
+        ));
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![UpdateReputationV1Callback::callback_ix(&[
+                CallbackAccount {
+                    pubkey: ctx.accounts.user_profile.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.user_position.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_record.key(),
+                    is_writable: true,
+                },
+            ])],
+        )?;
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "update_reputation_v1")]
+    pub fn update_reputation_v1_callback(
+        ctx: Context<UpdateReputationV1Callback>,
+        output: ComputationOutputs<UpdateReputationV1Output>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let reputation_state = match output {
+            ComputationOutputs::Success(UpdateReputationV1Output { field_0 }) => field_0,
+            _ => return ctx.accounts.computation_record.mark_failed(now),
+        };
+
+        let profile = &mut ctx.accounts.user_profile;
+        profile.encrypted_reputation = reputation_state.ciphertexts;
+        profile.reputation_nonce = reputation_state.nonce;
+        profile.has_private_reputation = true;
+        profile.last_activity = now;
+
+        ctx.accounts.user_position.reputation_recorded = true;
+        ctx.accounts.computation_record.mark_succeeded(now);
+
+        Ok(())
+    }
+
+    /// Claim payout after calculation
+    ///
+    /// User must decrypt their payout amount client-side and verify
+    pub fn claim_payout(ctx: Context<ClaimPayout>) -> Result<()> {
+        require!(
+            ctx.accounts.prediction_market.status == MarketStatus::Resolved,
+            ErrorCode::MarketNotResolved
+        );
+        require!(
+            !ctx.accounts.user_position.is_claimed,
+            ErrorCode::PayoutAlreadyClaimed
+        );
+        require!(
+            ctx.accounts.user_position.reputation_recorded,
+            ErrorCode::ReputationNotRecorded
+        );
+
+        let position = &mut ctx.accounts.user_position;
+        position.is_claimed = true;
+
+        // In production, this would transfer tokens from market vault to user
+        // For now, just mark as claimed
+
+        // Update user profile
+        let _profile = &mut ctx.accounts.user_profile;
+        // Payout amount would be decrypted client-side
+        // _profile.total_winnings += decrypted_payout;
+
+        emit!(PayoutClaimedEvent {
+            market_id: ctx.accounts.prediction_market.market_id,
+            user: position.user,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Take back the stake of a position in a cancelled market
+    ///
+    /// Works for encrypted and public positions alike, since the stake is public.
+    /// For a conditional market that's still open, pass the parent: if it went
+    /// against the condition, the market is cancelled first.
+    pub fn claim_refund(ctx: Context<ClaimRefund>) -> Result<()> {
+        let market = &mut ctx.accounts.prediction_market;
+        let open = market.status == MarketStatus::Active || market.status == MarketStatus::Locked;
+        if open && market.parent_market.is_some() {
+            cancel_for_failed_condition(market, ctx.accounts.parent_market.as_deref())?;
+        }
+        require!(
+            market.status == MarketStatus::Cancelled,
+            ErrorCode::MarketNotCancelled
+        );
+        require!(
+            !ctx.accounts.user_position.is_claimed,
+            ErrorCode::PayoutAlreadyClaimed
+        );
+
+        let position = &mut ctx.accounts.user_position;
+        position.payout_amount = position.stake_amount;
+        position.is_payout_calculated = true;
+        position.is_claimed = true;
+
+        emit!(RefundClaimedEvent {
+            market_id: market.market_id,
+            user: position.user,
+            amount: position.stake_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // =====================================================================
+    // PUBLIC MARKETS
+    // =====================================================================
+
+    /// Submit a plaintext vote to a public or hybrid market
+    ///
+    /// No MPC involved: the vote is stored on the position and tallied right away.
+    /// In a public market the odds are updated in the same instruction
+    ///
+    /// # Arguments
+    /// * `vote_choice` - 0 = No, 1 = Yes, 2 = Skip
+    /// * `stake_amount` - Amount staked on this prediction
+    /// * `predicted_probability` - Forecast chance of Yes (0-100)
+    /// * `conviction_score` - Confidence in the call (1-1000)
+    pub fn submit_public_vote(
+        ctx: Context<SubmitPublicVote>,
+        vote_choice: u8,
+        stake_amount: u64,
+        predicted_probability: u8,
+        conviction_score: u16,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.prediction_market.status == MarketStatus::Active,
+            ErrorCode::MarketNotActive
+        );
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now < ctx.accounts.prediction_market.voting_ends_at,
+            ErrorCode::VotingPeriodEnded
+        );
+        validate_public_vote(vote_choice, stake_amount, predicted_probability, conviction_score)?;
+        let total_stake = add_stake(ctx.accounts.prediction_market.total_stake, stake_amount)?;
+
+        let user_key = ctx.accounts.user.key();
+        let market_key = ctx.accounts.prediction_market.key();
+
+        let position = &mut ctx.accounts.user_position;
+        position.version = UserPosition::VERSION;
+        position.user = user_key;
+        position.market = market_key;
+        position.stake_amount = stake_amount;
+        position.timestamp = now;
+        position.is_claimed = false;
+        position.payout_amount = 0;
+        position.is_payout_calculated = false;
+        position.reputation_recorded = false;
+        position.disclosure_count = 0;
+        position.bump = ctx.bumps.user_position;
+        // Public from the start; nothing to validate, aggregate or reveal later
+        position.is_validated = true;
+        position.is_aggregated = true;
+        position.is_revealed = true;
+        position.revealed_vote_choice = vote_choice;
+        position.revealed_probability = predicted_probability;
+        position.revealed_conviction = conviction_score;
+        position.is_public = true;
+
+        let tally = &mut ctx.accounts.public_tally;
+        tally.open(market_key, ctx.bumps.public_tally);
+        tally.record(&ctx.accounts.prediction_market, position, now);
+        let (yes_probability, no_probability, participants, high_confidence, herding_index) =
+            tally.odds(ctx.accounts.prediction_market.market_id);
+
+        // yes_stake / no_stake only ever count public votes
+        let market = &mut ctx.accounts.prediction_market;
+        market.participant_count += 1;
+        market.total_stake = total_stake;
+        match vote_choice {
+            1 => market.yes_stake = add_stake(market.yes_stake, stake_amount)?,
+            0 => market.no_stake = add_stake(market.no_stake, stake_amount)?,
+            _ => {}
+        }
+        let odds_updated = market.privacy_mode == PrivacyMode::Public;
+        if odds_updated {
+            market.odds_yes = yes_probability;
+            market.odds_no = no_probability;
+            market.herding_index = herding_index;
+            market.odds_updated_at = now;
+        }
+
+        let profile = &mut ctx.accounts.user_profile;
+        profile.total_markets_participated += 1;
+        profile.last_activity = now;
+
+        emit!(VoteSubmittedEvent {
+            market_id: market.market_id,
+            user: user_key,
+            stake_amount,
+            timestamp: now,
+        });
+
+        emit!(PublicVoteSubmittedEvent {
+            market_id: market.market_id,
+            user: user_key,
+            vote_choice,
+            stake_amount,
+            predicted_probability,
+            conviction_score,
+            timestamp: now,
+        });
+
+        // A hybrid market's odds also depend on the sealed pool; see calculate_market_odds_hybrid
+        if odds_updated {
+            emit!(MarketOddsUpdatedEvent {
+                market_id: market.market_id,
+                yes_probability,
+                no_probability,
+                participants,
+                high_confidence,
+                herding_index,
+                timestamp: now,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Settle a public position in a resolved public or hybrid market
+    ///
+    /// Computes the payout from the public tally after fees (in a hybrid market,
+    /// takes the one `calculate_public_payout` revealed), records the outcome in the
+    /// profile's plaintext reputation and marks the position claimed
+    pub fn claim_public_payout(ctx: Context<ClaimPublicPayout>) -> Result<()> {
+        require!(
+            ctx.accounts.prediction_market.status == MarketStatus::Resolved,
+            ErrorCode::MarketNotResolved
+        );
+        require!(
+            !ctx.accounts.user_position.is_claimed,
+            ErrorCode::PayoutAlreadyClaimed
+        );
+
+        let market = &ctx.accounts.prediction_market;
+        let payout_amount = if market.privacy_mode == PrivacyMode::Hybrid {
+            require!(
+                ctx.accounts.user_position.is_payout_calculated,
+                ErrorCode::PayoutNotCalculated
+            );
+            ctx.accounts.user_position.payout_amount
+        } else {
+            ctx.accounts
+                .public_tally
+                .payout(market, &ctx.accounts.user_position)
+        };
+
+        let position = &mut ctx.accounts.user_position;
+        position.payout_amount = payout_amount;
+        position.is_payout_calculated = true;
+        position.reputation_recorded = true;
+        position.is_claimed = true;
+
+        let now = Clock::get()?.unix_timestamp;
+        let profile = &mut ctx.accounts.user_profile;
+        let was_correct = profile.record_public_outcome(market, position, payout_amount);
+        profile.last_activity = now;
+
+        emit!(PayoutClaimedEvent {
+            market_id: market.market_id,
+            user: position.user,
+            timestamp: now,
+        });
+
+        emit!(PublicPayoutClaimedEvent {
+            market_id: market.market_id,
+            user: position.user,
+            payout_amount,
+            was_correct,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    // =====================================================================
+    // HYBRID MARKETS
+    // =====================================================================
+
+    /// Calculate payout for an encrypted position in a hybrid market using MPC
+    ///
+    /// Same as `calculate_user_payout_v2`, with the market's public tally added to
+    /// the sealed pool inside the circuit.
+    pub fn calculate_user_payout_hybrid(
+        ctx: Context<CalculateUserPayoutHybrid>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.prediction_market.status == MarketStatus::Resolved,
+            ErrorCode::MarketNotResolved
+        );
+        require!(
+            !ctx.accounts.user_position.is_claimed,
+            ErrorCode::PayoutAlreadyClaimed
+        );
+
+        let market_key = ctx.accounts.prediction_market.key();
+        ctx.accounts.public_tally.open(market_key, ctx.bumps.public_tally);
+
+        let market = &ctx.accounts.prediction_market;
+        let position = &ctx.accounts.user_position;
+
+        // User's encrypted vote data (voter derived from position.user)
+        let mut args = position.vote_arguments();
+        args.extend([
+            // Market outcome (plaintext since it's public after resolution)
+            Argument::PlaintextU8(if market.resolved_outcome.unwrap() { 1 } else { 0 }),
+            // Bonus pools, carved out of the total stake inside the circuit
+            Argument::PlaintextU64(market.total_stake / 100), // 1% accuracy bonus pool (Brier-scored)
+            Argument::PlaintextU64(market.total_stake / 200), // 0.5% conviction bonus pool
+            // Protocol + creator fees charged at resolution
+            Argument::PlaintextU64(market.fee_amount),
+            // Voting window for time-decay weighting
+            Argument::PlaintextU64(market.created_at as u64),
+            Argument::PlaintextU64(market.voting_ends_at as u64),
+            Argument::PlaintextBool(market.time_decay_enabled),
+            // Encrypted totals of the market's encrypted votes
+            Argument::PlaintextU128(market.nonce),
+            Argument::Account(market.key(), MARKET_VOTE_STATE_OFFSET, MARKET_VOTE_STATE_LEN),
+        ]);
+        // Plaintext totals of its public votes
+        args.extend(ctx.accounts.public_tally.pool_arguments());
+
+        ctx.accounts.computation_allocator.advance();
+        ctx.accounts.computation_record.set_inner(ComputationRecord::queued(
+            ctx.bumps.computation_record,
+            computation_offset,
+            ComputationKind::CalculatePayout,
+            ctx.accounts.user_position.key(),
+            ctx.accounts.user.key(),
+            1,
+            Clock::get()?.unix_timestamp,
+        ));
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![CalculatePayoutHybridV1Callback::callback_ix(&[
+                CallbackAccount {
+                    pubkey: ctx.accounts.user_position.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_record.key(),
+                    is_writable: true,
+                },
+            ])],
+        )?;
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "calculate_payout_hybrid_v1")]
+    pub fn calculate_payout_hybrid_v1_callback(
+        ctx: Context<CalculatePayoutHybridV1Callback>,
+        output: ComputationOutputs<CalculatePayoutHybridV1Output>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let payout_amount_encrypted = match output {
+            ComputationOutputs::Success(CalculatePayoutHybridV1Output { field_0 }) => field_0,
+            _ => return ctx.accounts.computation_record.mark_failed(now),
+        };
+
+        ctx.accounts.user_position.encrypted_payout = payout_amount_encrypted.ciphertexts[0];
+        ctx.accounts.user_position.payout_nonce = payout_amount_encrypted.nonce;
+        ctx.accounts.user_position.is_payout_calculated = true;
+        ctx.accounts.computation_record.mark_succeeded(now);
+
+        Ok(())
+    }
+
+    /// Calculate payout for a public position in a hybrid market using MPC
+    ///
+    /// The vote is plaintext, but the pools it is paid from hold sealed stakes, so
+    /// the MXE computes the payout and reveals it; `claim_public_payout` settles it
+    pub fn calculate_public_payout(
+        ctx: Context<CalculatePublicPayout>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.prediction_market.status == MarketStatus::Resolved,
+            ErrorCode::MarketNotResolved
+        );
+        require!(
+            !ctx.accounts.user_position.is_claimed,
+            ErrorCode::PayoutAlreadyClaimed
+        );
+
+        let market_key = ctx.accounts.prediction_market.key();
+        ctx.accounts.public_tally.open(market_key, ctx.bumps.public_tally);
+
+        let market = &ctx.accounts.prediction_market;
+        let position = &ctx.accounts.user_position;
+
+        let mut args = vec![
+            // The position's plaintext vote
+            Argument::PlaintextU64(position.stake_amount),
+            Argument::PlaintextU8(position.revealed_vote_choice),
+            Argument::PlaintextU8(position.revealed_probability),
+            Argument::PlaintextU16(position.revealed_conviction),
+            Argument::PlaintextU64(position.timestamp as u64),
+            Argument::PlaintextU8(if market.resolved_outcome.unwrap() { 1 } else { 0 }),
+            // Bonus pools, carved out of the total stake inside the circuit
+            Argument::PlaintextU64(market.total_stake / 100), // 1% accuracy bonus pool (Brier-scored)
+            Argument::PlaintextU64(market.total_stake / 200), // 0.5% conviction bonus pool
+            // Protocol + creator fees charged at resolution
+            Argument::PlaintextU64(market.fee_amount),
+            // Voting window for time-decay weighting
+            Argument::PlaintextU64(market.created_at as u64),
+            Argument::PlaintextU64(market.voting_ends_at as u64),
+            Argument::PlaintextBool(market.time_decay_enabled),
+            // Encrypted totals of the market's encrypted votes
+            Argument::PlaintextU128(market.nonce),
+            Argument::Account(market.key(), MARKET_VOTE_STATE_OFFSET, MARKET_VOTE_STATE_LEN),
+        ];
+        // Plaintext totals of its public votes
+        args.extend(ctx.accounts.public_tally.pool_arguments());
+
+        ctx.accounts.computation_allocator.advance();
+        ctx.accounts.computation_record.set_inner(ComputationRecord::queued(
+            ctx.bumps.computation_record,
+            computation_offset,
+            ComputationKind::CalculatePayout,
+            ctx.accounts.user_position.key(),
+            ctx.accounts.user.key(),
+            1,
+            Clock::get()?.unix_timestamp,
+        ));
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![CalculatePublicPayoutV1Callback::callback_ix(&[
+                CallbackAccount {
+                    pubkey: ctx.accounts.user_position.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_record.key(),
+                    is_writable: true,
+                },
+            ])],
+        )?;
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "calculate_public_payout_v1")]
+    pub fn calculate_public_payout_v1_callback(
+        ctx: Context<CalculatePublicPayoutV1Callback>,
+        output: ComputationOutputs<CalculatePublicPayoutV1Output>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let payout_amount = match output {
+            ComputationOutputs::Success(CalculatePublicPayoutV1Output { field_0 }) => field_0,
+            _ => return ctx.accounts.computation_record.mark_failed(now),
+        };
+
+        ctx.accounts.user_position.payout_amount = payout_amount;
+        ctx.accounts.user_position.is_payout_calculated = true;
+        ctx.accounts.computation_record.mark_succeeded(now);
+
+        Ok(())
+    }
+
+    /// Calculate a hybrid market's odds over its sealed and public pools using MPC
+    ///
+    /// As with calculate_market_odds, a resolved market reveals its final odds once
+    pub fn calculate_market_odds_hybrid(
+        ctx: Context<CalculateMarketOddsHybrid>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        let kind = match ctx.accounts.prediction_market.status {
+            MarketStatus::Active => ComputationKind::CalculateOdds,
+            MarketStatus::Resolved => ComputationKind::CalculateFinalOdds,
+            _ => return err!(ErrorCode::MarketNotActive),
+        };
+        require!(
+            !ctx.accounts.prediction_market.final_odds_locked,
+            ErrorCode::FinalOddsLocked
+        );
+
+        let market_key = ctx.accounts.prediction_market.key();
+        ctx.accounts.public_tally.open(market_key, ctx.bumps.public_tally);
+
+        let market = &ctx.accounts.prediction_market;
+
+        let mut args = vec![
+            Argument::PlaintextU128(market.nonce),
+            Argument::Account(market.key(), MARKET_VOTE_STATE_OFFSET, MARKET_VOTE_STATE_LEN),
+        ];
+        args.extend(ctx.accounts.public_tally.pool_arguments());
+
+        ctx.accounts.computation_allocator.advance();
+        ctx.accounts.computation_record.set_inner(ComputationRecord::queued(
+            ctx.bumps.computation_record,
+            computation_offset,
+            kind,
+            ctx.accounts.prediction_market.key(),
+            ctx.accounts.payer.key(),
+            1,
+            Clock::get()?.unix_timestamp,
+        ));
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![CalculateMarketOddsHybridV1Callback::callback_ix(&[
+                CallbackAccount {
+                    pubkey: ctx.accounts.prediction_market.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_record.key(),
+                    is_writable: true,
+                },
+            ])],
+        )?;
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "calculate_market_odds_hybrid_v1")]
+    pub fn calculate_market_odds_hybrid_v1_callback(
+        ctx: Context<CalculateMarketOddsHybridV1Callback>,
+        output: ComputationOutputs<CalculateMarketOddsHybridV1Output>,
+    ) -> Result<()> {
+        let timestamp = Clock::get()?.unix_timestamp;
+        // odds tuple is (yes_prob, no_prob, participants, high_confidence, herding_index)
+        let (yes_prob, no_prob, participants, high_confidence, herding_index) = match output {
+            ComputationOutputs::Success(CalculateMarketOddsHybridV1Output {
+                field_0:
+                    CalculateMarketOddsHybridV1OutputStruct0 {
+                        field_0,
+                        field_1,
+                        field_2,
+                        field_3,
+                        field_4,
+                    },
+            }) => (field_0, field_1, field_2, field_3, field_4),
+            _ => return ctx.accounts.computation_record.mark_failed(timestamp),
+        };
+
+        // Odds queued before the lock are stale and can't replace the final ones
+        if ctx.accounts.prediction_market.final_odds_locked {
+            return ctx.accounts.computation_record.mark_failed(timestamp);
+        }
+
+        ctx.accounts.computation_record.mark_succeeded(timestamp);
+        let market = &mut ctx.accounts.prediction_market;
+        market.odds_yes = yes_prob;
+        market.odds_no = no_prob;
+        market.herding_index = herding_index;
+        market.odds_updated_at = timestamp;
+        market.final_odds_locked =
+            ctx.accounts.computation_record.kind == ComputationKind::CalculateFinalOdds;
+
+        emit!(MarketOddsUpdatedEvent {
+            market_id: market.market_id,
+            yes_probability: yes_prob,
+            no_probability: no_prob,
+            participants,
+            high_confidence,
+            herding_index,
+            timestamp,
+        });
+
+        Ok(())
+    }
+
+    // =====================================================================
+    // PARLAYS
+    // =====================================================================
+
+    /// Place a parlay on 1 to MAX_PARLAY_LEGS open markets
+    ///
+    /// The leg markets are passed as remaining accounts, in the order of the picks.
+    /// Picks for unused slots are ignored. A pick other than 0 or 1 loses its leg.
+    /// The stake goes into the parlay pool, which must be able to cover
+    /// MAX_PARLAY_MULTIPLIER times it.
+    ///
+    /// # Arguments
+    /// * `choices_encrypted` - Encrypted ParlayChoices (32 bytes per slot)
+    /// * `choices_encryption_pubkey` - User's x25519 public key
+    /// * `choices_nonce` - Nonce for the choices' encryption
+    /// * `stake_amount` - Amount staked on the parlay
+    pub fn create_parlay<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateParlay<'info>>,
+        choices_encrypted: [[u8; 32]; MAX_PARLAY_LEGS],
+        choices_encryption_pubkey: [u8; 32],
+        choices_nonce: u128,
+        stake_amount: u64,
+    ) -> Result<()> {
+        require!(stake_amount > 0 && stake_amount <= MAX_STAKE, ErrorCode::InvalidStakeAmount);
+        let now = Clock::get()?.unix_timestamp;
+        let markets = Parlay::open_legs(ctx.remaining_accounts, now)?;
+
+        let profile = &mut ctx.accounts.user_profile;
+        let parlay_id = profile.parlay_count;
+        profile.parlay_count += 1;
+        profile.last_activity = now;
+
+        let parlay = &mut ctx.accounts.parlay;
+        parlay.version = Parlay::VERSION;
+        parlay.bump = ctx.bumps.parlay;
+        parlay.user = ctx.accounts.user.key();
+        parlay.parlay_id = parlay_id;
+        parlay.markets = markets;
+        parlay.leg_count = ctx.remaining_accounts.len() as u8;
+        parlay.stake_amount = stake_amount;
+        parlay.created_at = now;
+        parlay.encrypted_choices = choices_encrypted;
+        parlay.choices_pubkey = choices_encryption_pubkey;
+        parlay.choices_nonce = choices_nonce;
+        parlay.key_epoch = ctx.accounts.mxe_key_epoch.epoch;
+        parlay.is_settling = false;
+        parlay.is_settled = false;
+        parlay.payout_amount = 0;
+        parlay.is_claimed = false;
+
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.user.to_account_info(),
+                    to: ctx.accounts.parlay_pool.to_account_info(),
+                },
+            ),
+            stake_amount,
+        )?;
+        let max_payout = parlay.max_payout();
+        require!(
+            ParlayPool::available(&ctx.accounts.parlay_pool)? >= max_payout,
+            ErrorCode::ParlayPoolUnderfunded
+        );
+        ctx.accounts.parlay_pool.reserved += max_payout;
+
+        emit!(ParlayCreatedEvent {
+            parlay: parlay.key(),
+            user: parlay.user,
+            markets: parlay.markets,
+            leg_count: parlay.leg_count,
+            stake_amount,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Settle a parlay once every leg's market has resolved or been cancelled
+    ///
+    /// The leg markets are passed as remaining accounts in the parlay's order. Queues
+    /// `calculate_parlay_payout_v1`, which prices the parlay off each market's
+    /// revealed odds and reveals the payout. Only one settlement can be in flight.
+    pub fn settle_parlay<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SettleParlay<'info>>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        require!(!ctx.accounts.parlay.is_settled, ErrorCode::ParlayAlreadySettled);
+        require!(!ctx.accounts.parlay.is_settling, ErrorCode::ParlaySettlementPending);
+        let args = ctx.accounts.parlay.payout_arguments(ctx.remaining_accounts)?;
+        ctx.accounts.parlay.is_settling = true;
+
+        ctx.accounts.computation_allocator.advance();
+        ctx.accounts.computation_record.set_inner(ComputationRecord::queued(
+            ctx.bumps.computation_record,
+            computation_offset,
+            ComputationKind::CalculateParlayPayout,
+            ctx.accounts.parlay.key(),
+            ctx.accounts.user.key(),
+            1,
+            Clock::get()?.unix_timestamp,
+        ));
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![CalculateParlayPayoutV1Callback::callback_ix(&[
+                CallbackAccount {
+                    pubkey: ctx.accounts.parlay.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.parlay_pool.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_record.key(),
+                    is_writable: true,
+                },
+            ])],
+        )?;
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "calculate_parlay_payout_v1")]
+    pub fn calculate_parlay_payout_v1_callback(
+        ctx: Context<CalculateParlayPayoutV1Callback>,
+        output: ComputationOutputs<CalculateParlayPayoutV1Output>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let parlay = &mut ctx.accounts.parlay;
+        parlay.is_settling = false;
+        let payout_amount = match output {
+            ComputationOutputs::Success(CalculateParlayPayoutV1Output { field_0 }) => field_0,
+            _ => return ctx.accounts.computation_record.mark_failed(now),
+        };
+
+        // The circuit caps the payout at the reservation; release the rest
+        let pool = &mut ctx.accounts.parlay_pool;
+        pool.reserved = pool.reserved - parlay.max_payout() + payout_amount;
+        parlay.payout_amount = payout_amount;
+        parlay.is_settled = true;
+        ctx.accounts.computation_record.mark_succeeded(now);
+
+        emit!(ParlaySettledEvent {
+            parlay: parlay.key(),
+            user: parlay.user,
+            payout_amount,
+            timestamp: now,
+        });
+
+        Ok(())
+    }
+
+    /// Pay a settled parlay's payout out of the parlay pool to its owner
+    pub fn claim_parlay(ctx: Context<ClaimParlay>) -> Result<()> {
+        let parlay = &mut ctx.accounts.parlay;
+        require!(parlay.is_settled, ErrorCode::ParlayNotSettled);
+        require!(!parlay.is_claimed, ErrorCode::PayoutAlreadyClaimed);
+
+        let payout_amount = parlay.payout_amount;
+        ParlayPool::pay(
+            &ctx.accounts.parlay_pool,
+            &ctx.accounts.user.to_account_info(),
+            payout_amount,
+        )?;
+        ctx.accounts.parlay_pool.reserved -= payout_amount;
+        parlay.is_claimed = true;
+
+        emit!(ParlayClaimedEvent {
+            parlay: parlay.key(),
+            user: parlay.user,
+            payout_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Create the parlay pool; program config admin only
+    pub fn initialize_parlay_pool(ctx: Context<InitializeParlayPool>) -> Result<()> {
+        let pool = &mut ctx.accounts.parlay_pool;
+        pool.version = ParlayPool::VERSION;
+        pool.bump = ctx.bumps.parlay_pool;
+        pool.reserved = 0;
+
+        Ok(())
+    }
+
+    /// Add lamports to the parlay pool, so it can take bigger parlays
+    pub fn fund_parlay_pool(ctx: Context<FundParlayPool>, amount: u64) -> Result<()> {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.funder.to_account_info(),
+                    to: ctx.accounts.parlay_pool.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        emit!(ParlayPoolFundedEvent {
+            funder: ctx.accounts.funder.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Send lamports no parlay has a claim on to `recipient`; admin only
+    pub fn withdraw_parlay_pool(ctx: Context<WithdrawParlayPool>, amount: u64) -> Result<()> {
+        require!(
+            amount <= ParlayPool::available(&ctx.accounts.parlay_pool)?,
+            ErrorCode::ParlayPoolUnderfunded
+        );
+        ParlayPool::pay(
+            &ctx.accounts.parlay_pool,
+            &ctx.accounts.recipient.to_account_info(),
+            amount,
+        )?;
+
+        emit!(ParlayPoolWithdrawnEvent {
+            recipient: ctx.accounts.recipient.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // =====================================================================
+    // PROGRAM CONFIG & EMERGENCY PAUSE
+    // =====================================================================
+
+    /// Create the program config and treasury. Only the program's upgrade authority
+    /// may call this; it becomes the admin and the initial guardian. Create the
+    /// config first: every other singleton is initialized by its admin
+    ///
+    /// # Arguments
+    /// * `protocol_fee_bps` - Protocol's cut of each market's total stake
+    /// * `max_creator_fee_bps` - Highest creator fee a market may set
+    pub fn initialize_program_config(
+        ctx: Context<InitializeProgramConfig>,
+        protocol_fee_bps: u16,
+        max_creator_fee_bps: u16,
+    ) -> Result<()> {
+        require!(
+            protocol_fee_bps <= MAX_FEE_BPS && max_creator_fee_bps <= MAX_FEE_BPS,
+            ErrorCode::FeeTooHigh
+        );
+
+        let config = &mut ctx.accounts.program_config;
+        config.version = ProgramConfig::VERSION;
+        config.bump = ctx.bumps.program_config;
+        config.admin = ctx.accounts.admin.key();
+        config.protocol_fee_bps = protocol_fee_bps;
+        config.max_creator_fee_bps = max_creator_fee_bps;
+        config.pending_admin = None;
+        config.guardian = ctx.accounts.admin.key();
+        config.markets_paused = false;
+        config.votes_paused = false;
+        config.claims_paused = false;
+        config.creation_bonds = [0; MARKET_CATEGORY_COUNT];
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.version = Treasury::VERSION;
+        treasury.bump = ctx.bumps.treasury;
+        treasury.protocol_fees_accrued = 0;
+        treasury.creator_fees_accrued = 0;
+        treasury.total_protocol_fees = 0;
+        treasury.total_slashed_bonds = 0;
+        treasury.slashed_bonds_accrued = 0;
+
+        Ok(())
+    }
+
+    /// Propose a new admin; the role moves once `new_admin` calls accept_admin
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+        ctx.accounts.program_config.pending_admin = Some(new_admin);
+
+        emit!(AdminTransferProposedEvent {
+            admin: ctx.accounts.admin.key(),
+            pending_admin: new_admin,
+        });
+
+        Ok(())
+    }
+
+    /// Take over the admin role proposed to the signer
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let config = &mut ctx.accounts.program_config;
+        let previous_admin = config.admin;
+        config.admin = ctx.accounts.pending_admin.key();
+        config.pending_admin = None;
+
+        emit!(AdminTransferredEvent {
+            previous_admin,
+            admin: config.admin,
+        });
+
+        Ok(())
+    }
+
+    /// Appoint the guardian allowed to pause the program
+    pub fn set_guardian(ctx: Context<SetGuardian>, guardian: Pubkey) -> Result<()> {
+        ctx.accounts.program_config.guardian = guardian;
+
+        emit!(GuardianUpdatedEvent { guardian });
+
+        Ok(())
+    }
+
+    /// Set the emergency pause flags. The guardian may only pause; clearing a
+    /// flag takes the admin.
+    ///
+    /// # Arguments
+    /// * `markets_paused` - Block create_market
+    /// * `votes_paused` - Block submit_encrypted_vote
+    /// * `claims_paused` - Block payout calculation, claims and fee withdrawals
+    pub fn set_pause_flags(
+        ctx: Context<SetPauseFlags>,
+        markets_paused: bool,
+        votes_paused: bool,
+        claims_paused: bool,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.program_config;
+        let unpausing = (config.markets_paused && !markets_paused)
+            || (config.votes_paused && !votes_paused)
+            || (config.claims_paused && !claims_paused);
+        require!(
+            !unpausing || config.is_admin(&ctx.accounts.authority.key()),
+            ErrorCode::Unauthorized
+        );
+
+        config.markets_paused = markets_paused;
+        config.votes_paused = votes_paused;
+        config.claims_paused = claims_paused;
+
+        emit!(PauseFlagsUpdatedEvent {
+            authority: ctx.accounts.authority.key(),
+            markets_paused,
+            votes_paused,
+            claims_paused,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // =====================================================================
+    // FEES & TREASURY
+    // =====================================================================
+
+    /// Change the fee rates for markets created from now on
+    pub fn update_fee_config(
+        ctx: Context<UpdateFeeConfig>,
+        protocol_fee_bps: u16,
+        max_creator_fee_bps: u16,
+    ) -> Result<()> {
+        require!(
+            protocol_fee_bps <= MAX_FEE_BPS && max_creator_fee_bps <= MAX_FEE_BPS,
+            ErrorCode::FeeTooHigh
+        );
+
+        let config = &mut ctx.accounts.program_config;
+        config.protocol_fee_bps = protocol_fee_bps;
+        config.max_creator_fee_bps = max_creator_fee_bps;
+
+        emit!(FeeConfigUpdatedEvent {
+            protocol_fee_bps,
+            max_creator_fee_bps,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw accrued protocol fees to `recipient`
+    ///
+    /// Fees are charged on stake, which isn't escrowed yet, so this settles the
+    /// counter like `claim_payout` settles a payout; no lamports leave the treasury
+    pub fn withdraw_protocol_fees(ctx: Context<WithdrawProtocolFees>, amount: u64) -> Result<()> {
+        require!(
+            amount <= ctx.accounts.treasury.protocol_fees_accrued,
+            ErrorCode::InsufficientAccruedFees
+        );
+
+        ctx.accounts.treasury.protocol_fees_accrued -= amount;
+
+        emit!(FeesWithdrawnEvent {
+            market_id: None,
+            recipient: ctx.accounts.recipient.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw a market's creator fees to its creator
+    ///
+    /// Settles the counters only, as for protocol fees
+    pub fn withdraw_creator_fees(ctx: Context<WithdrawCreatorFees>) -> Result<()> {
+        let amount = ctx.accounts.prediction_market.creator_fees_owed;
+        require!(amount > 0, ErrorCode::InsufficientAccruedFees);
+
+        ctx.accounts.treasury.creator_fees_accrued -= amount;
+        ctx.accounts.prediction_market.creator_fees_owed = 0;
+
+        emit!(FeesWithdrawnEvent {
+            market_id: Some(ctx.accounts.prediction_market.market_id),
+            recipient: ctx.accounts.creator.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Send slashed creation bonds held by the treasury to `recipient`; admin only
+    ///
+    /// Unlike fees these are lamports the treasury actually received
+    pub fn withdraw_slashed_bonds(ctx: Context<WithdrawSlashedBonds>, amount: u64) -> Result<()> {
+        require!(
+            amount <= ctx.accounts.treasury.slashed_bonds_accrued,
+            ErrorCode::InsufficientSlashedBonds
+        );
+
+        pay_from_treasury(
+            &ctx.accounts.treasury,
+            &ctx.accounts.recipient.to_account_info(),
+            amount,
+        )?;
+        ctx.accounts.treasury.slashed_bonds_accrued -= amount;
+
+        emit!(SlashedBondsWithdrawnEvent {
+            recipient: ctx.accounts.recipient.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    // =====================================================================
+    // CREATION BONDS
+    // =====================================================================
+
+    /// Set the bond creators post to open a market, per category
+    ///
+    /// # Arguments
+    /// * `creation_bonds` - Lamports, indexed by `MarketCategory::slot`
+    pub fn set_creation_bonds(
+        ctx: Context<SetCreationBonds>,
+        creation_bonds: [u64; MARKET_CATEGORY_COUNT],
+    ) -> Result<()> {
+        ctx.accounts.program_config.creation_bonds = creation_bonds;
+
+        emit!(CreationBondsUpdatedEvent { creation_bonds });
+
+        Ok(())
+    }
+
+    /// Cancel a market with a malformed question, slashing the creator's bond
+    ///
+    /// Admin only. The bond goes to `dispute_winner` when one is passed,
+    /// otherwise to the treasury.
+    pub fn flag_malformed_market(ctx: Context<FlagMalformedMarket>) -> Result<()> {
+        require!(
+            ctx.accounts.prediction_market.status == MarketStatus::Active
+                || ctx.accounts.prediction_market.status == MarketStatus::Locked,
+            ErrorCode::CannotCancelMarket
+        );
+
+        let (bond_slashed, bond_recipient) = slash_creation_bond(
+            &mut ctx.accounts.prediction_market,
+            &mut ctx.accounts.treasury,
+            ctx.accounts.dispute_winner.as_ref().map(|winner| winner.as_ref()),
+        )?;
+        let market = &mut ctx.accounts.prediction_market;
+        market.status = MarketStatus::Cancelled;
+
+        emit!(MarketCancelledEvent {
+            market_id: market.market_id,
+            malformed: true,
+            bond_slashed,
+            bond_recipient,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Return the creation bond to the creator of a resolved market, or of a
+    /// conditional market cancelled because of its parent (other cancellations
+    /// slash the bond, so there's nothing left to reclaim)
+    pub fn reclaim_creation_bond(ctx: Context<ReclaimCreationBond>) -> Result<()> {
+        require!(
+            ctx.accounts.prediction_market.status == MarketStatus::Resolved
+                || ctx.accounts.prediction_market.status == MarketStatus::Settled
+                || ctx.accounts.prediction_market.status == MarketStatus::Cancelled,
+            ErrorCode::MarketNotResolved
+        );
+        require!(
+            ctx.accounts.prediction_market.creation_bond > 0,
+            ErrorCode::NoCreationBond
+        );
+
+        let amount = release_creation_bond(
+            &mut ctx.accounts.prediction_market,
+            &ctx.accounts.creator.to_account_info(),
+        )?;
+
+        emit!(CreationBondReturnedEvent {
+            market_id: ctx.accounts.prediction_market.market_id,
+            creator: ctx.accounts.creator.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    // =====================================================================
+    // POST-RESOLUTION TRANSPARENCY
+    // =====================================================================
+
+    /// Publish up to REVEAL_BATCH_SIZE positions' votes on a resolved market that
+    /// was created with `reveal_after_resolution`
+    ///
+    /// Positions are passed as writable remaining accounts; the callback writes
+    /// each vote's choice, probability and conviction into the position in
+    /// plaintext. Permissionless so anyone can complete the export.
+    pub fn reveal_positions<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RevealPositions<'info>>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        let market = &ctx.accounts.prediction_market;
+        require!(market.reveal_after_resolution, ErrorCode::RevealNotEnabled);
+        require!(
+            market.status == MarketStatus::Resolved,
+            ErrorCode::MarketNotResolved
+        );
+
+        let positions = ctx.remaining_accounts;
+        require!(
+            !positions.is_empty() && positions.len() <= REVEAL_BATCH_SIZE,
+            ErrorCode::InvalidRevealBatch
+        );
+
+        let market_key = market.key();
+        let mut args = Vec::with_capacity(REVEAL_BATCH_SIZE * 9);
+        let mut callback_accounts = vec![
+            CallbackAccount {
+                pubkey: market_key,
+                is_writable: false,
+            },
+            CallbackAccount {
+                pubkey: ctx.accounts.computation_record.key(),
+                is_writable: true,
+            },
+        ];
+
+        for slot in 0..REVEAL_BATCH_SIZE {
+            // Unused slots repeat the last position; the callback discards them
+            let info = &positions[slot.min(positions.len() - 1)];
+            let position = Account::<UserPosition>::try_from(info)?;
+            require!(is_current(&position), ErrorCode::AccountNotMigrated);
+            require_keys_eq!(position.market, market_key, ErrorCode::InvalidRevealBatch);
+
+            if slot < positions.len() {
+                require!(position.is_validated, ErrorCode::PositionNotValidated);
+                require!(!position.is_revealed, ErrorCode::PositionAlreadyRevealed);
+                require!(
+                    position.key_epoch == ctx.accounts.mxe_key_epoch.epoch,
+                    ErrorCode::StaleKeyEpoch
+                );
+                callback_accounts.push(CallbackAccount {
+                    pubkey: info.key(),
+                    is_writable: true,
+                });
+            }
+
+            args.extend(position.vote_arguments());
+        }
+
+        ctx.accounts.computation_allocator.advance();
+        ctx.accounts.computation_record.set_inner(ComputationRecord::queued(
+            ctx.bumps.computation_record,
+            computation_offset,
+            ComputationKind::RevealVotes,
+            market_key,
+            ctx.accounts.payer.key(),
+            1,
+            Clock::get()?.unix_timestamp,
+        ));
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![RevealVotesV1Callback::callback_ix(&callback_accounts)],
+        )?;
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "reveal_votes_v1")]
+    pub fn reveal_votes_v1_callback<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RevealVotesV1Callback<'info>>,
+        output: ComputationOutputs<RevealVotesV1Output>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let (choices, probabilities, convictions) = match output {
+            ComputationOutputs::Success(RevealVotesV1Output {
+                field_0:
+                    RevealVotesV1OutputStruct0 {
+                        field_0,
+                        field_1,
+                        field_2,
+                    },
+            }) => (field_0, field_1, field_2),
+            _ => return ctx.accounts.computation_record.mark_failed(now),
+        };
+
+        for (i, info) in ctx.remaining_accounts.iter().enumerate() {
+            let mut position = Account::<UserPosition>::try_from(info)?;
+            position.is_revealed = true;
+            position.revealed_vote_choice = choices[i];
+            position.revealed_probability = probabilities[i];
+            position.revealed_conviction = convictions[i];
+            position.exit(&crate::ID)?;
+
+            emit!(VoteRevealedEvent {
+                market_id: ctx.accounts.prediction_market.market_id,
+                user: position.user,
+                vote_choice: choices[i],
+                predicted_probability: probabilities[i],
+                conviction_score: convictions[i],
+                stake_amount: position.stake_amount,
+            });
+        }
+        ctx.accounts.computation_record.mark_succeeded(now);
+
+        Ok(())
+    }
+
+    // =====================================================================
+    // MARKET STATISTICS & ODDS
+    // =====================================================================
+
+    /// Calculate market odds using encrypted vote data
+    ///
+    /// This reveals aggregated market odds without revealing individual votes.
+    /// A resolved market takes no more votes, so the first odds revealed after it
+    /// resolves are final: they're locked and parlays settle on them
+    pub fn calculate_market_odds(
+        ctx: Context<CalculateMarketOdds>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        let kind = match ctx.accounts.prediction_market.status {
+            MarketStatus::Active => ComputationKind::CalculateOdds,
+            MarketStatus::Resolved => ComputationKind::CalculateFinalOdds,
+            _ => return err!(ErrorCode::MarketNotActive),
+        };
+        require!(
+            !ctx.accounts.prediction_market.final_odds_locked,
+            ErrorCode::FinalOddsLocked
+        );
+
+        let market = &ctx.accounts.prediction_market;
+
+        let args = vec![
+            Argument::PlaintextU128(market.nonce),
+            Argument::Account(market.key(), MARKET_VOTE_STATE_OFFSET, MARKET_VOTE_STATE_LEN),
+        ];
+
+        ctx.accounts.computation_allocator.advance();
+        ctx.accounts.computation_record.set_inner(ComputationRecord::queued(
+            ctx.bumps.computation_record,
+            computation_offset,
+            kind,
+            ctx.accounts.prediction_market.key(),
+            ctx.accounts.payer.key(),
+            1,
+            Clock::get()?.unix_timestamp,
+        ));
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![CalculateMarketOddsV1Callback::callback_ix(&[
+                CallbackAccount {
+                    pubkey: ctx.accounts.prediction_market.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_record.key(),
+                    is_writable: true,
+                },
+            ])],
+        )?;
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "calculate_market_odds_v1")]
+    pub fn calculate_market_odds_v1_callback(
+        ctx: Context<CalculateMarketOddsV1Callback>,
+        output: ComputationOutputs<CalculateMarketOddsV1Output>,
+    ) -> Result<()> {
+        let timestamp = Clock::get()?.unix_timestamp;
+        // odds tuple is (yes_prob, no_prob, participants, high_confidence, herding_index)
+        let (yes_prob, no_prob, participants, high_confidence, herding_index) = match output {
+            ComputationOutputs::Success(CalculateMarketOddsV1Output {
+                field_0:
+                    CalculateMarketOddsV1OutputStruct0 {
+                        field_0,
+                        field_1,
+                        field_2,
+                        field_3,
+                        field_4,
+                    },
+            }) => (field_0, field_1, field_2, field_3, field_4),
+            _ => return ctx.accounts.computation_record.mark_failed(timestamp),
+        };
+
+        // Odds queued before the lock are stale and can't replace the final ones
+        if ctx.accounts.prediction_market.final_odds_locked {
+            return ctx.accounts.computation_record.mark_failed(timestamp);
+        }
+
+        ctx.accounts.computation_record.mark_succeeded(timestamp);
+        let market = &mut ctx.accounts.prediction_market;
+        market.odds_yes = yes_prob;
+        market.odds_no = no_prob;
+        market.herding_index = herding_index;
+        market.odds_updated_at = timestamp;
+        market.final_odds_locked =
+            ctx.accounts.computation_record.kind == ComputationKind::CalculateFinalOdds;
+
+        emit!(MarketOddsUpdatedEvent {
+            market_id: market.market_id,
+            yes_probability: yes_prob,
+            no_probability: no_prob,
+            participants,
+            high_confidence,
+            herding_index,
+            timestamp,
+        });
+
+        Ok(())
+    }
+
+    // =====================================================================
+    // SEASONAL LEADERBOARD
+    // =====================================================================
+
+    /// Start a new leaderboard season
+    ///
+    /// Admin only. The caller becomes the season authority and is the only one who
+    /// can end it
+    pub fn start_leaderboard_season(
+        ctx: Context<StartLeaderboardSeason>,
+        season_id: u32,
+        starts_at: i64,
+        ends_at: i64,
+    ) -> Result<()> {
+        require!(season_id > 0, ErrorCode::InvalidSeason);
+        require!(ends_at > starts_at, ErrorCode::InvalidEndTime);
+        require!(
+            ends_at > Clock::get()?.unix_timestamp,
+            ErrorCode::InvalidEndTime
+        );
+
+        let leaderboard = &mut ctx.accounts.leaderboard;
+        leaderboard.version = Leaderboard::VERSION;
+        leaderboard.bump = ctx.bumps.leaderboard;
+        leaderboard.encrypted_scores = [[0; 32]; LEADERBOARD_SIZE];
+        leaderboard.scores_nonce = 0;
+        leaderboard.has_scores = false;
+        leaderboard.authority = ctx.accounts.authority.key();
+        leaderboard.season_id = season_id;
+        leaderboard.starts_at = starts_at;
+        leaderboard.ends_at = ends_at;
+        leaderboard.entry_count = 0;
+        leaderboard.insert_pending = false;
+        leaderboard.pending_offset = 0;
+        leaderboard.pending_since = 0;
+        leaderboard.is_finalized = false;
+        leaderboard.ranked_users = [Pubkey::default(); LEADERBOARD_SIZE];
+        leaderboard.revealed_scores = [0; LEADERBOARD_SIZE];
+
+        emit!(LeaderboardSeasonStartedEvent {
+            season_id,
+            authority: leaderboard.authority,
+            starts_at,
+            ends_at,
+        });
+
+        Ok(())
+    }
+
+    /// Submit a user's sealed reputation to the current season's leaderboard
+    ///
+    /// The MPC circuit inserts the score into the sealed top-N and reveals only
+    /// the rank the user lands in. One entry per user per season.
+    pub fn submit_leaderboard_entry(
+        ctx: Context<SubmitLeaderboardEntry>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let leaderboard = &ctx.accounts.leaderboard;
+        let profile = &ctx.accounts.user_profile;
+
+        require!(
+            now >= leaderboard.starts_at && now < leaderboard.ends_at && !leaderboard.is_finalized,
+            ErrorCode::SeasonNotActive
+        );
+        require!(!leaderboard.is_busy(now), ErrorCode::LeaderboardBusy);
+        require!(
+            profile.has_private_reputation,
+            ErrorCode::NoPrivateReputation
+        );
+        require!(
+            profile.leaderboard_season != leaderboard.season_id,
+            ErrorCode::AlreadyOnLeaderboard
+        );
+
+        let args = vec![
+            // User's sealed reputation
+            Argument::PlaintextU128(profile.reputation_nonce),
+            Argument::Account(
+                profile.key(),
+                USER_PROFILE_REPUTATION_OFFSET,
+                32 * 5, // encrypted_reputation [[u8; 32]; 5] = 160 bytes
+            ),
+            Argument::PlaintextBool(leaderboard.has_scores),
+            // Current sealed top-N scores
+            Argument::PlaintextU128(leaderboard.scores_nonce),
+            Argument::Account(
+                leaderboard.key(),
+                LEADERBOARD_SCORES_OFFSET,
+                32 * LEADERBOARD_SIZE as u32, // encrypted_scores [[u8; 32]; 10] = 320 bytes
+            ),
+        ];
+
+        ctx.accounts.computation_allocator.advance();
+        ctx.accounts.computation_record.set_inner(ComputationRecord::queued(
+            ctx.bumps.computation_record,
+            computation_offset,
+            ComputationKind::InsertLeaderboardEntry,
+            ctx.accounts.user_profile.key(),
+            ctx.accounts.user.key(),
+            1,
+            now,
+        ));
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![InsertLeaderboardEntryCallback::callback_ix(&[
+                CallbackAccount {
+                    pubkey: ctx.accounts.leaderboard.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.user_profile.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_record.key(),
+                    is_writable: true,
+                },
+            ])],
+        )?;
+
+        // Serialize inserts so each computation sees the latest sealed board. A lock
+        // older than COMPUTATION_TIMEOUT is taken over, and its callback is ignored
+        let leaderboard = &mut ctx.accounts.leaderboard;
+        leaderboard.insert_pending = true;
+        leaderboard.pending_offset = computation_offset;
+        leaderboard.pending_since = now;
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "insert_leaderboard_entry")]
+    pub fn insert_leaderboard_entry_callback(
+        ctx: Context<InsertLeaderboardEntryCallback>,
+        output: ComputationOutputs<InsertLeaderboardEntryOutput>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        // An insert whose lock timed out and was taken over would overwrite a newer board
+        let leaderboard = &ctx.accounts.leaderboard;
+        if !leaderboard.insert_pending
+            || leaderboard.pending_offset != ctx.accounts.computation_record.computation_offset
+        {
+            return ctx.accounts.computation_record.mark_failed(now);
+        }
+        let (sealed_scores, slot) = match output {
+            ComputationOutputs::Success(InsertLeaderboardEntryOutput {
+                field_0: InsertLeaderboardEntryOutputStruct0 { field_0, field_1 },
+            }) => (field_0, field_1),
+            _ => {
+                // Release the lock so the entry can be resubmitted
+                ctx.accounts.leaderboard.insert_pending = false;
+                return ctx.accounts.computation_record.mark_failed(now);
+            }
+        };
+        ctx.accounts.computation_record.mark_succeeded(now);
+
+        let user = ctx.accounts.user_profile.user;
+        let leaderboard = &mut ctx.accounts.leaderboard;
+        leaderboard.encrypted_scores = sealed_scores.ciphertexts;
+        leaderboard.scores_nonce = sealed_scores.nonce;
+        leaderboard.has_scores = true;
+        leaderboard.insert_pending = false;
+        leaderboard.entry_count += 1;
+
+        // Mirror the sealed insertion on the public ranking
+        let slot = slot as usize;
+        if slot < LEADERBOARD_SIZE {
+            for i in (slot + 1..LEADERBOARD_SIZE).rev() {
+                leaderboard.ranked_users[i] = leaderboard.ranked_users[i - 1];
+            }
+            leaderboard.ranked_users[slot] = user;
+        }
+
+        ctx.accounts.user_profile.leaderboard_season = leaderboard.season_id;
+
+        emit!(LeaderboardEntrySubmittedEvent {
+            season_id: leaderboard.season_id,
+            user,
+            rank: if slot < LEADERBOARD_SIZE { Some(slot as u8 + 1) } else { None },
+        });
+
+        Ok(())
+    }
+
+    /// End the season and reveal the (rounded) top-N scores
+    pub fn end_leaderboard_season(
+        ctx: Context<EndLeaderboardSeason>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        let leaderboard = &ctx.accounts.leaderboard;
+        require!(
+            ctx.accounts.authority.key() == leaderboard.authority,
+            ErrorCode::InvalidSeasonAuthority
+        );
+        require!(!leaderboard.is_finalized, ErrorCode::SeasonNotActive);
+        require!(
+            !leaderboard.is_busy(Clock::get()?.unix_timestamp),
+            ErrorCode::LeaderboardBusy
+        );
+        require!(leaderboard.has_scores, ErrorCode::EmptyLeaderboard);
+
+        let args = vec![
+            Argument::PlaintextU128(leaderboard.scores_nonce),
+            Argument::Account(
+                leaderboard.key(),
+                LEADERBOARD_SCORES_OFFSET,
+                32 * LEADERBOARD_SIZE as u32, // encrypted_scores [[u8; 32]; 10] = 320 bytes
+            ),
+        ];
+
+        ctx.accounts.computation_allocator.advance();
+        ctx.accounts.computation_record.set_inner(ComputationRecord::queued(
+            ctx.bumps.computation_record,
+            computation_offset,
+            ComputationKind::RevealLeaderboard,
+            ctx.accounts.leaderboard.key(),
+            ctx.accounts.authority.key(),
+            1,
+            Clock::get()?.unix_timestamp,
+        ));
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        queue_computation(
+            ctx.accounts,
+            computation_offset,
+            args,
+            None,
+            vec![RevealLeaderboardCallback::callback_ix(&[
+                CallbackAccount {
+                    pubkey: ctx.accounts.leaderboard.key(),
+                    is_writable: true,
+                },
+                CallbackAccount {
+                    pubkey: ctx.accounts.computation_record.key(),
+                    is_writable: true,
+                },
+            ])],
+        )?;
+
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "reveal_leaderboard")]
+    pub fn reveal_leaderboard_callback(
+        ctx: Context<RevealLeaderboardCallback>,
+        output: ComputationOutputs<RevealLeaderboardOutput>,
+    ) -> Result<()> {
+        let timestamp = Clock::get()?.unix_timestamp;
+        let rounded_scores = match output {
+            ComputationOutputs::Success(RevealLeaderboardOutput { field_0 }) => field_0,
+            _ => return ctx.accounts.computation_record.mark_failed(timestamp),
+        };
+        ctx.accounts.computation_record.mark_succeeded(timestamp);
+
+        let leaderboard = &mut ctx.accounts.leaderboard;
+        leaderboard.revealed_scores = rounded_scores;
+        leaderboard.is_finalized = true;
+
+        emit!(LeaderboardFinalizedEvent {
+            season_id: leaderboard.season_id,
+            ranked_users: leaderboard.ranked_users,
+            scores: rounded_scores,
+            timestamp,
+        });
+
+        Ok(())
+    }
+
+    // =====================================================================
+    // ACCOUNT MIGRATION
+    // =====================================================================
+
+    /// Upgrade a market account to the current layout in place
+    ///
+    /// Permissionless; the caller pays for any extra rent. The creation bond
+    /// stays in the account on top of its rent
+    pub fn migrate_market(ctx: Context<MigrateAccount>) -> Result<()> {
+        let account = ctx.accounts.account.to_account_info();
+        let (from_version, market) = upgrade_market(&account.try_borrow_data()?)?;
+        rewrite_account(
+            &account,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            &market,
+            PredictionMarket::LEN,
+            market.creation_bond,
+        )?;
+
+        emit!(AccountMigratedEvent {
+            account: account.key(),
+            from_version,
+            to_version: PredictionMarket::VERSION,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Upgrade a user position to the current layout in place
+    pub fn migrate_position(ctx: Context<MigrateAccount>) -> Result<()> {
+        let account = ctx.accounts.account.to_account_info();
+        let (from_version, position) = upgrade_position(&account.try_borrow_data()?)?;
+        rewrite_account(
+            &account,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            &position,
+            UserPosition::LEN,
+            0,
+        )?;
+
+        emit!(AccountMigratedEvent {
+            account: account.key(),
+            from_version,
+            to_version: UserPosition::VERSION,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Upgrade a user profile to the current layout in place
+    pub fn migrate_profile(ctx: Context<MigrateAccount>) -> Result<()> {
+        let account = ctx.accounts.account.to_account_info();
+        let (from_version, profile) = upgrade_profile(&account.try_borrow_data()?)?;
+        rewrite_account(
+            &account,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            &profile,
+            UserProfile::LEN,
+            0,
+        )?;
+
+        emit!(AccountMigratedEvent {
+            account: account.key(),
+            from_version,
+            to_version: UserProfile::VERSION,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Upgrade the treasury to the current layout in place; its lamports above
+    /// rent carry over as slashed bonds
+    pub fn migrate_treasury(ctx: Context<MigrateAccount>) -> Result<()> {
+        let account = ctx.accounts.account.to_account_info();
+        let held = account
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(Treasury::LEN));
+        let (from_version, treasury) = upgrade_treasury(&account.try_borrow_data()?, held)?;
+        rewrite_account(
+            &account,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            &treasury,
+            Treasury::LEN,
+            treasury.slashed_bonds_accrued,
+        )?;
+
+        emit!(AccountMigratedEvent {
+            account: account.key(),
+            from_version,
+            to_version: Treasury::VERSION,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+// =====================================================================
+// ACCOUNT STRUCTURES
+// =====================================================================
+
+/// Main prediction market account with encrypted vote state
+/// Note: encrypted_vote_state and nonce are placed early for fixed offset access
+#[account]
+#[derive(InitSpace)]
+pub struct PredictionMarket {
+    pub version: u8, // Layout version; migrate_market upgrades older accounts
+    pub bump: u8,
+    // Encrypted vote aggregation state (stored at fixed offset for MPC)
+    // MarketVotingState has 17 fields (market_id, yes/no counts, stakes, participants,
+    // Brier sums, time-weighted stakes, herding counters)
+    pub encrypted_vote_state: [[u8; 32]; 17],
+    pub nonce: u128,
+    // Market identifiers and metadata
+    pub market_id: u64,
+    pub creator: Pubkey,
+    pub category: MarketCategory,
+    pub status: MarketStatus,
+    pub created_at: i64,
+    pub voting_ends_at: i64,
+    pub resolution_timestamp: i64,
+    pub oracle_type: OracleType,
+    pub oracle_pubkey: Pubkey,
+    pub resolved_outcome: Option<bool>,
+    pub time_decay_enabled: bool, // Early votes weigh more in odds and payouts
+    pub reveal_after_resolution: bool, // Votes are published once the market resolves
+    pub privacy_mode: PrivacyMode,
+    // Market this one is conditioned on and the outcome it needs (see conditional_markets.rs)
+    pub parent_market: Option<Pubkey>,
+    pub parent_outcome: bool,
+    pub circuit_version: u8,      // Version of the circuits this market settles on
+    pub key_epoch: u32,           // MXE key epoch encrypted_vote_state is sealed under
+    // Vote accumulator root and leaf count the sealed tally covers (V2 markets)
+    pub aggregated_vote_root: [u8; 32],
+    pub aggregated_vote_count: u32,
+    // Fee rates snapshotted at creation; amounts charged at resolution
+    pub protocol_fee_bps: u16,
+    pub creator_fee_bps: u16,
+    pub fee_amount: u64,        // Protocol + creator fees deducted before payouts
+    pub creator_fees_owed: u64, // Creator fees still held in the treasury
+    pub creation_bond: u64,     // Lamports held in this account until resolution or slashing
+    // Public aggregate stats (not encrypted)
+    pub total_stake: u64,
+    pub yes_stake: u64,
+    pub no_stake: u64,
+    pub participant_count: u32,
+    // Last revealed odds (spread widened by herding_index, 0-4)
+    pub odds_yes: u8,
+    pub odds_no: u8,
+    pub herding_index: u8,
+    pub odds_updated_at: i64,
+    pub final_odds_locked: bool, // Odds cover every vote and can't be recomputed
+    // Off-chain metadata (title, description, resolution criteria, image)
+    pub metadata_hash: [u8; 32], // SHA-256 of the canonical MarketMetadata JSON
+    #[max_len(MAX_METADATA_URI_LEN)]
+    pub metadata_uri: String,    // Variable length, kept last (4 + len bytes)
+}
+
+impl PredictionMarket {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+    pub const VERSION: u8 = 2;
+}
+
+/// User's encrypted position in a market
+#[account]
+#[derive(InitSpace)]
+pub struct UserPosition {
+    pub version: u8, // Layout version; migrate_position upgrades older accounts
+    pub user: Pubkey,
+    pub market: Pubkey,
+    pub stake_amount: u64,
+    pub timestamp: i64,
+    pub is_claimed: bool,
+    pub is_validated: bool,
+    // Encrypted vote data (7 fields: market_id, vote_choice, stake, prob, conviction, timestamp, nonce)
+    // voter field is derived from user pubkey, not encrypted
+    pub encrypted_vote_data: [[u8; 32]; 7],
+    pub vote_pubkey: [u8; 32],
+    pub vote_nonce: u128,
+    // Encrypted payout result
+    pub encrypted_payout: [u8; 32],
+    pub payout_nonce: u128,
+    pub payout_amount: u64, // Decrypted amount (set client-side before claim)
+    pub bump: u8,
+    pub is_payout_calculated: bool,
+    pub reputation_recorded: bool,
+    pub key_epoch: u32, // MXE key epoch encrypted_vote_data was encrypted under
+    pub disclosure_count: u32, // Seeds the next DisclosureRecord
+    // Plaintext vote, published after resolution on reveal_after_resolution markets
+    pub is_revealed: bool,
+    pub revealed_vote_choice: u8,
+    pub revealed_probability: u8,
+    pub revealed_conviction: u16,
+    // Place in the market's vote accumulator and the root right after it
+    pub leaf_index: u32,
+    pub vote_root: [u8; 32],
+    pub is_aggregated: bool,
+    pub is_public: bool, // Cast with submit_public_vote; the vote sits in the revealed_* fields
+}
+
+impl UserPosition {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+    pub const VERSION: u8 = 2;
+
+    /// Encrypted vote as circuit arguments: owner pubkey, nonce, then the 7 fields
+    pub fn vote_arguments(&self) -> Vec<Argument> {
+        vec![
+            Argument::ArcisPubkey(self.vote_pubkey),
+            Argument::PlaintextU128(self.vote_nonce),
+            Argument::EncryptedU64(self.encrypted_vote_data[0]),  // market_id
+            Argument::EncryptedU8(self.encrypted_vote_data[1]),   // vote_choice
+            Argument::EncryptedU64(self.encrypted_vote_data[2]),  // stake_amount
+            Argument::EncryptedU8(self.encrypted_vote_data[3]),   // predicted_probability
+            Argument::EncryptedU16(self.encrypted_vote_data[4]),  // conviction_score
+            Argument::EncryptedU64(self.encrypted_vote_data[5]),  // timestamp
+            Argument::EncryptedU128(self.encrypted_vote_data[6]), // nonce
+        ]
+    }
+}
+
+/// User profile for reputation and statistics
+#[account]
+#[derive(InitSpace)]
+pub struct UserProfile {
+    pub version: u8,
+    pub user: Pubkey,
+    pub total_markets_participated: u32,
+    pub correct_predictions: u32,
+    pub total_winnings: u64,
+    pub reputation_score: u16,
+    pub streak_current: u16,
+    pub streak_best: u16,
+    pub last_activity: i64,
+    pub achievements: [u8; 32],
+    pub preferred_categories: u16,
+    pub bump: u8,
+    // MXE-sealed ReputationState (correct_predictions, total_winnings, streaks, score)
+    // updated privately by update_reputation; the plaintext stats above stay zero
+    // for encrypted markets
+    pub encrypted_reputation: [[u8; 32]; 5],
+    pub reputation_nonce: u128,
+    pub has_private_reputation: bool,
+    pub leaderboard_season: u32, // Last season this profile entered
+    pub parlay_count: u32,       // Seeds the next Parlay
+}
+
+impl UserProfile {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+    pub const VERSION: u8 = 2;
+}
+
+/// Seasonal leaderboard of sealed reputation scores
+/// Note: encrypted_scores is placed right after bump for fixed offset access
+#[account]
+#[derive(InitSpace)]
+pub struct Leaderboard {
+    pub version: u8,
+    pub bump: u8,
+    // Sealed top-N reputation scores, sorted descending
+    pub encrypted_scores: [[u8; 32]; 10],
+    pub scores_nonce: u128,
+    pub has_scores: bool,
+    pub authority: Pubkey,
+    pub season_id: u32,
+    pub starts_at: i64,
+    pub ends_at: i64,
+    pub entry_count: u32,
+    // Insert in flight; pending_offset is its computation, queued at pending_since
+    pub insert_pending: bool,
+    pub pending_offset: u64,
+    pub pending_since: i64,
+    pub is_finalized: bool,
+    // Public ranking: rank i is ranked_users[i]; scores revealed at season end
+    pub ranked_users: [Pubkey; 10],
+    pub revealed_scores: [u16; 10],
+}
+
+impl Leaderboard {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+    pub const VERSION: u8 = 1;
+
+    /// An insert is in flight and hasn't yet timed out without a callback
+    pub fn is_busy(&self, now: i64) -> bool {
+        self.insert_pending && now - self.pending_since <= COMPUTATION_TIMEOUT
+    }
+}
+
+/// Byte offset of `PredictionMarket.encrypted_vote_state` (discriminator + version + bump)
+pub const MARKET_VOTE_STATE_OFFSET: u32 = 8 + 1 + 1;
+
+/// Byte length of `PredictionMarket.encrypted_vote_state` (17 sealed MarketVotingState fields)
+pub const MARKET_VOTE_STATE_LEN: u32 = 32 * 17;
+
+/// Byte offset of `Leaderboard.encrypted_scores` (discriminator + version + bump)
+pub const LEADERBOARD_SCORES_OFFSET: u32 = 8 + 1 + 1;
+
+/// Byte offset of `UserProfile.encrypted_reputation` (discriminator + fixed fields before it)
+pub const USER_PROFILE_REPUTATION_OFFSET: u32 =
+    8 + 1 + 32 + 4 + 4 + 8 + 2 + 2 + 2 + 8 + 32 + 2 + 1;
+
+// =====================================================================
+// ENUMS
+// =====================================================================
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, InitSpace)]
+pub enum MarketStatus {
+    Active,
+    Locked,
+    Resolved,
+    Settled,
+    Cancelled,
+}
+
+/// Whether a market's votes go through the MXE, are tallied in the clear, or both
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, InitSpace)]
+pub enum PrivacyMode {
+    Private, // Encrypted votes, MPC tally and payouts
+    Public,  // Plaintext votes and on-chain payouts, see public_markets.rs
+    Hybrid,  // Each voter picks; public votes are tallied in the clear, encrypted ones by the MXE
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, InitSpace)]
+pub enum MarketCategory {
+    Sports = 0,
+    Politics = 1,
+    Economics = 2,
+    Technology = 3,
+    Entertainment = 4,
+    Weather = 5,
+    Custom = 99,
+}
+
+pub const MARKET_CATEGORY_COUNT: usize = 7;
+
+impl MarketCategory {
+    /// Index of this category in per-category arrays and index PDAs
+    pub fn slot(self) -> usize {
+        match self {
+            MarketCategory::Sports => 0,
+            MarketCategory::Politics => 1,
+            MarketCategory::Economics => 2,
+            MarketCategory::Technology => 3,
+            MarketCategory::Entertainment => 4,
+            MarketCategory::Weather => 5,
+            MarketCategory::Custom => 6,
+        }
+    }
+}
+
+// =====================================================================
+// ACCOUNT VALIDATION CONTEXTS
+// =====================================================================
+
+#[derive(Accounts)]
+pub struct InitializeMarketRegistry<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = MarketRegistry::LEN,
+        seeds = [b"market_registry"],
+        bump
+    )]
+    pub market_registry: Account<'info, MarketRegistry>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.is_admin(&payer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(metadata_uri: String, metadata_hash: [u8; 32], category: MarketCategory)]
+pub struct CreateMarket<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    #[account(mut, seeds = [b"market_registry"], bump = market_registry.bump)]
+    pub market_registry: Box<Account<'info, MarketRegistry>>,
+    #[account(
+        init,
+        payer = creator,
+        space = PredictionMarket::LEN,
+        seeds = [b"market", market_registry.next_market_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    /// CHECK: Oracle address validation handled in instruction
+    pub oracle: UncheckedAccount<'info>,
+    #[account(seeds = [b"mxe_key_epoch"], bump = mxe_key_epoch.bump)]
+    pub mxe_key_epoch: Account<'info, MxeKeyEpoch>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.markets_paused @ ErrorCode::MarketCreationPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = MarketIndexPage::LEN,
+        seeds = [
+            b"category_index",
+            &[category.slot() as u8],
+            MarketIndexPage::page_for(market_registry.category_counts[category.slot()])
+                .to_le_bytes()
+                .as_ref()
+        ],
+        bump
+    )]
+    pub category_index_page: Box<Account<'info, MarketIndexPage>>,
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = CreatorMarkets::LEN,
+        seeds = [b"creator_markets", creator.key().as_ref()],
+        bump
+    )]
+    pub creator_markets: Box<Account<'info, CreatorMarkets>>,
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = MarketIndexPage::LEN,
+        seeds = [
+            b"creator_index",
+            creator.key().as_ref(),
+            MarketIndexPage::page_for(creator_markets.market_count).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub creator_index_page: Box<Account<'info, MarketIndexPage>>,
+    #[account(constraint = is_current(&parent_market) @ ErrorCode::AccountNotMigrated)]
+    pub parent_market: Option<Box<Account<'info, PredictionMarket>>>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMarketMetadata<'info> {
+    pub creator: Signer<'info>,
+    #[account(
+        mut,
+        has_one = creator @ ErrorCode::Unauthorized,
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated,
+        constraint = prediction_market.status == MarketStatus::Active @ ErrorCode::MarketNotActive
+    )]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(
+        seeds = [b"vote_accumulator", prediction_market.key().as_ref()],
+        bump
+    )]
+    /// CHECK: only checked for existence; created by the market's first vote
+    pub vote_accumulator: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeUserProfile<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        init,
+        payer = user,
+        space = UserProfile::LEN,
+        seeds = [b"profile", user.key().as_ref()],
+        bump
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    pub system_program: Program<'info, System>,
+}
+
+#[queue_computation_accounts("submit_private_vote_v1", user)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct SubmitEncryptedVote<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = user,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: mempool_account, checked by the arcium program
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: executing_pool, checked by the arcium program
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: computation_account, checked by the arcium program
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_SUBMIT_VOTE_V1))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        mut,
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated,
+        constraint = prediction_market.circuit_version >= CIRCUIT_VERSION_V1 @ ErrorCode::CircuitVersionMismatch,
+        constraint = prediction_market.privacy_mode != PrivacyMode::Public @ ErrorCode::PrivacyModeMismatch
+    )]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(
+        init,
+        payer = user,
+        space = UserPosition::LEN,
+        seeds = [b"position", user.key().as_ref(), prediction_market.key().as_ref()],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(
+        mut,
+        seeds = [b"profile", user.key().as_ref()],
+        bump = user_profile.bump,
+        constraint = is_current(&user_profile) @ ErrorCode::AccountNotMigrated
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(seeds = [b"mxe_key_epoch"], bump = mxe_key_epoch.bump)]
+    pub mxe_key_epoch: Account<'info, MxeKeyEpoch>,
+    // Created by the market's first vote
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = VoteAccumulator::LEN,
+        seeds = [b"vote_accumulator", prediction_market.key().as_ref()],
+        bump
+    )]
+    pub vote_accumulator: Box<Account<'info, VoteAccumulator>>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.votes_paused @ ErrorCode::VotingPaused
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+    #[account(
+        mut,
+        seeds = [b"computation_allocator"],
+        bump = computation_allocator.bump,
+        constraint = computation_allocator.next_offset == computation_offset @ ErrorCode::StaleComputationOffset
+    )]
+    pub computation_allocator: Account<'info, ComputationAllocator>,
+    #[account(
+        init,
+        payer = user,
+        space = ComputationRecord::LEN,
+        seeds = [b"computation", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[callback_accounts("submit_private_vote_v1")]
+#[derive(Accounts)]
+pub struct SubmitPrivateVoteV1Callback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_SUBMIT_VOTE_V1))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(mut)]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[queue_computation_accounts("submit_private_vote_v1", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct RetryComputation<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: mempool_account, checked by the arcium program
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: executing_pool, checked by the arcium program
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: computation_account, checked by the arcium program
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_SUBMIT_VOTE_V1))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated,
+        constraint = prediction_market.circuit_version >= CIRCUIT_VERSION_V1 @ ErrorCode::CircuitVersionMismatch
+    )]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(
+        constraint = is_current(&user_position) @ ErrorCode::AccountNotMigrated,
+        constraint = user_position.market == prediction_market.key() @ ErrorCode::InvalidComputationRecord,
+        constraint = !user_position.is_validated @ ErrorCode::InvalidComputationRecord
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(
+        mut,
+        seeds = [b"computation", failed_record.computation_offset.to_le_bytes().as_ref()],
+        bump = failed_record.bump,
+        constraint = failed_record.kind == ComputationKind::SubmitVote @ ErrorCode::InvalidComputationRecord,
+        constraint = failed_record.target == user_position.key() @ ErrorCode::InvalidComputationRecord
+    )]
+    pub failed_record: Account<'info, ComputationRecord>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.votes_paused @ ErrorCode::VotingPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [b"computation_allocator"],
+        bump = computation_allocator.bump,
+        constraint = computation_allocator.next_offset == computation_offset @ ErrorCode::StaleComputationOffset
+    )]
+    pub computation_allocator: Account<'info, ComputationAllocator>,
+    #[account(
+        init,
+        payer = payer,
+        space = ComputationRecord::LEN,
+        seeds = [b"computation", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[derive(Accounts)]
+pub struct RollbackFailedVote<'info> {
+    pub caller: Signer<'info>,
+    #[account(
+        mut,
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated
+    )]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"position", user.key().as_ref(), prediction_market.key().as_ref()],
+        bump = user_position.bump,
+        constraint = is_current(&user_position) @ ErrorCode::AccountNotMigrated,
+        constraint = !user_position.is_validated @ ErrorCode::RollbackNotAllowed
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(mut, address = user_position.user)]
+    pub user: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"profile", user.key().as_ref()],
+        bump = user_profile.bump,
+        constraint = is_current(&user_profile) @ ErrorCode::AccountNotMigrated
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        mut,
+        seeds = [b"computation", computation_record.computation_offset.to_le_bytes().as_ref()],
+        bump = computation_record.bump,
+        constraint = computation_record.kind == ComputationKind::SubmitVote @ ErrorCode::InvalidComputationRecord,
+        constraint = computation_record.target == user_position.key() @ ErrorCode::InvalidComputationRecord
+    )]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[derive(Accounts)]
+pub struct CloseComputationRecord<'info> {
+    #[account(mut, address = computation_record.payer @ ErrorCode::Unauthorized)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"computation", computation_record.computation_offset.to_le_bytes().as_ref()],
+        bump = computation_record.bump
+    )]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[queue_computation_accounts("aggregate_market_votes_v1", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct AggregateVotes<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: mempool_account, checked by the arcium program
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: executing_pool, checked by the arcium program
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: computation_account, checked by the arcium program
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_AGGREGATE_V1))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        mut,
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated,
+        constraint = prediction_market.circuit_version == CIRCUIT_VERSION_V1 @ ErrorCode::CircuitVersionMismatch
+    )]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    // Only validated votes enter the tally, and each of them once
+    #[account(
+        constraint = is_current(&user_position) @ ErrorCode::AccountNotMigrated,
+        constraint = user_position.market == prediction_market.key() @ ErrorCode::InvalidVoteData,
+        constraint = user_position.is_validated @ ErrorCode::PositionNotValidated,
+        constraint = !user_position.is_aggregated @ ErrorCode::VoteOutOfOrder
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    // Sealed state from before a key rotation must be re-encrypted first
+    #[account(
+        seeds = [b"mxe_key_epoch"],
+        bump = mxe_key_epoch.bump,
+        constraint = prediction_market.key_epoch == mxe_key_epoch.epoch @ ErrorCode::StaleKeyEpoch,
+        constraint = user_position.key_epoch == mxe_key_epoch.epoch @ ErrorCode::StaleKeyEpoch
+    )]
+    pub mxe_key_epoch: Account<'info, MxeKeyEpoch>,
+    #[account(
+        mut,
+        seeds = [b"computation_allocator"],
+        bump = computation_allocator.bump,
+        constraint = computation_allocator.next_offset == computation_offset @ ErrorCode::StaleComputationOffset
+    )]
+    pub computation_allocator: Account<'info, ComputationAllocator>,
+    #[account(
+        init,
+        payer = payer,
+        space = ComputationRecord::LEN,
+        seeds = [b"computation", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[callback_accounts("aggregate_market_votes_v1")]
+#[derive(Accounts)]
+pub struct AggregateMarketVotesV1Callback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_AGGREGATE_V1))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(mut)]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(mut)]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[queue_computation_accounts("aggregate_market_votes_v2", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct AggregateVotesV2<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: mempool_account, checked by the arcium program
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: executing_pool, checked by the arcium program
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: computation_account, checked by the arcium program
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_AGGREGATE_V2))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        mut,
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated,
+        constraint = prediction_market.circuit_version >= CIRCUIT_VERSION_V2 @ ErrorCode::CircuitVersionMismatch
+    )]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    // Only validated votes enter the tally; leaf order is checked in the instruction
+    #[account(
+        constraint = is_current(&user_position) @ ErrorCode::AccountNotMigrated,
+        constraint = user_position.market == prediction_market.key() @ ErrorCode::InvalidVoteData,
+        constraint = user_position.is_validated @ ErrorCode::PositionNotValidated,
+        constraint = !user_position.is_aggregated @ ErrorCode::VoteOutOfOrder
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    // Sealed state from before a key rotation must be re-encrypted first
+    #[account(
+        seeds = [b"mxe_key_epoch"],
+        bump = mxe_key_epoch.bump,
+        constraint = prediction_market.key_epoch == mxe_key_epoch.epoch @ ErrorCode::StaleKeyEpoch,
+        constraint = user_position.key_epoch == mxe_key_epoch.epoch @ ErrorCode::StaleKeyEpoch
+    )]
+    pub mxe_key_epoch: Account<'info, MxeKeyEpoch>,
+    #[account(
+        mut,
+        seeds = [b"computation_allocator"],
+        bump = computation_allocator.bump,
+        constraint = computation_allocator.next_offset == computation_offset @ ErrorCode::StaleComputationOffset
+    )]
+    pub computation_allocator: Account<'info, ComputationAllocator>,
+    #[account(
+        init,
+        payer = payer,
+        space = ComputationRecord::LEN,
+        seeds = [b"computation", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[callback_accounts("aggregate_market_votes_v2")]
+#[derive(Accounts)]
+pub struct AggregateMarketVotesV2Callback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_AGGREGATE_V2))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(mut)]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(mut)]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveMarket<'info> {
+    #[account(mut)]
+    pub oracle: Signer<'info>,
+    #[account(
+        mut,
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated
+    )]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(mut, seeds = [b"treasury"], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+    #[account(constraint = is_current(&parent_market) @ ErrorCode::AccountNotMigrated)]
+    pub parent_market: Option<Box<Account<'info, PredictionMarket>>>,
+}
+
+#[derive(Accounts)]
+pub struct CancelMarket<'info> {
+    pub oracle: Signer<'info>,
+    #[account(
+        mut,
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated,
+        constraint = prediction_market.oracle_pubkey == oracle.key() @ ErrorCode::InvalidOracle
+    )]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(mut, seeds = [b"treasury"], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+    #[account(mut)]
+    pub dispute_winner: Option<SystemAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct CancelConditionalMarket<'info> {
+    #[account(
+        mut,
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated
+    )]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(constraint = is_current(&parent_market) @ ErrorCode::AccountNotMigrated)]
+    pub parent_market: Box<Account<'info, PredictionMarket>>,
+}
+
+#[queue_computation_accounts("calculate_payout_v1", user)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct CalculateUserPayout<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = user,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: mempool_account, checked by the arcium program
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: executing_pool, checked by the arcium program
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: computation_account, checked by the arcium program
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_CALCULATE_PAYOUT_V1))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated,
+        constraint = prediction_market.circuit_version <= CIRCUIT_VERSION_V2 @ ErrorCode::CircuitVersionMismatch,
+        constraint = prediction_market.privacy_mode == PrivacyMode::Private @ ErrorCode::PrivacyModeMismatch
+    )]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(
+        mut,
+        seeds = [b"position", user.key().as_ref(), prediction_market.key().as_ref()],
+        bump = user_position.bump,
+        constraint = is_current(&user_position) @ ErrorCode::AccountNotMigrated
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.claims_paused @ ErrorCode::ClaimsPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [b"computation_allocator"],
+        bump = computation_allocator.bump,
+        constraint = computation_allocator.next_offset == computation_offset @ ErrorCode::StaleComputationOffset
+    )]
+    pub computation_allocator: Account<'info, ComputationAllocator>,
+    #[account(
+        init,
+        payer = user,
+        space = ComputationRecord::LEN,
+        seeds = [b"computation", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[callback_accounts("calculate_payout_v1")]
+#[derive(Accounts)]
+pub struct CalculatePayoutV1Callback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_CALCULATE_PAYOUT_V1))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(mut)]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[queue_computation_accounts("calculate_payout_v2", user)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct CalculateUserPayoutV2<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = user,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: mempool_account, checked by the arcium program
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: executing_pool, checked by the arcium program
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: computation_account, checked by the arcium program
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_CALCULATE_PAYOUT_V2))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated,
+        constraint = prediction_market.circuit_version >= CIRCUIT_VERSION_V3 @ ErrorCode::CircuitVersionMismatch,
+        constraint = prediction_market.privacy_mode == PrivacyMode::Private @ ErrorCode::PrivacyModeMismatch
+    )]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(
+        mut,
+        seeds = [b"position", user.key().as_ref(), prediction_market.key().as_ref()],
+        bump = user_position.bump,
+        constraint = is_current(&user_position) @ ErrorCode::AccountNotMigrated
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.claims_paused @ ErrorCode::ClaimsPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [b"computation_allocator"],
+        bump = computation_allocator.bump,
+        constraint = computation_allocator.next_offset == computation_offset @ ErrorCode::StaleComputationOffset
+    )]
+    pub computation_allocator: Account<'info, ComputationAllocator>,
+    #[account(
+        init,
+        payer = user,
+        space = ComputationRecord::LEN,
+        seeds = [b"computation", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[callback_accounts("calculate_payout_v2")]
+#[derive(Accounts)]
+pub struct CalculatePayoutV2Callback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_CALCULATE_PAYOUT_V2))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(mut)]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[queue_computation_accounts("update_reputation_v1", user)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct RecordPrivateReputation<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = user,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: mempool_account, checked by the arcium program
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: executing_pool, checked by the arcium program
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: computation_account, checked by the arcium program
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_UPDATE_REPUTATION_V1))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated,
+        constraint = prediction_market.circuit_version >= CIRCUIT_VERSION_V1 @ ErrorCode::CircuitVersionMismatch,
+        constraint = prediction_market.privacy_mode != PrivacyMode::Public @ ErrorCode::PrivacyModeMismatch
+    )]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(
+        seeds = [b"position", user.key().as_ref(), prediction_market.key().as_ref()],
+        bump = user_position.bump,
+        constraint = is_current(&user_position) @ ErrorCode::AccountNotMigrated,
+        constraint = !user_position.is_public @ ErrorCode::PrivacyModeMismatch
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(
+        seeds = [b"profile", user.key().as_ref()],
+        bump = user_profile.bump,
+        constraint = is_current(&user_profile) @ ErrorCode::AccountNotMigrated
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.claims_paused @ ErrorCode::ClaimsPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [b"computation_allocator"],
+        bump = computation_allocator.bump,
+        constraint = computation_allocator.next_offset == computation_offset @ ErrorCode::StaleComputationOffset
+    )]
+    pub computation_allocator: Account<'info, ComputationAllocator>,
+    #[account(
+        init,
+        payer = user,
+        space = ComputationRecord::LEN,
+        seeds = [b"computation", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[callback_accounts("update_reputation_v1")]
+#[derive(Accounts)]
+pub struct UpdateReputationV1Callback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_UPDATE_REPUTATION_V1))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(mut)]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(mut)]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[derive(Accounts)]
+#[instruction(season_id: u32)]
+pub struct StartLeaderboardSeason<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = Leaderboard::LEN,
+        seeds = [b"leaderboard", season_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.is_admin(&authority.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[queue_computation_accounts("insert_leaderboard_entry", user)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct SubmitLeaderboardEntry<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = user,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: mempool_account, checked by the arcium program
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: executing_pool, checked by the arcium program
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: computation_account, checked by the arcium program
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_INSERT_LEADERBOARD))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        mut,
+        seeds = [b"leaderboard", leaderboard.season_id.to_le_bytes().as_ref()],
+        bump = leaderboard.bump
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+    #[account(
+        seeds = [b"profile", user.key().as_ref()],
+        bump = user_profile.bump,
+        constraint = is_current(&user_profile) @ ErrorCode::AccountNotMigrated
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.claims_paused @ ErrorCode::ClaimsPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [b"computation_allocator"],
+        bump = computation_allocator.bump,
+        constraint = computation_allocator.next_offset == computation_offset @ ErrorCode::StaleComputationOffset
+    )]
+    pub computation_allocator: Account<'info, ComputationAllocator>,
+    #[account(
+        init,
+        payer = user,
+        space = ComputationRecord::LEN,
+        seeds = [b"computation", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[callback_accounts("insert_leaderboard_entry")]
+#[derive(Accounts)]
+pub struct InsertLeaderboardEntryCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_INSERT_LEADERBOARD))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub leaderboard: Account<'info, Leaderboard>,
+    #[account(mut)]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(mut)]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[queue_computation_accounts("reveal_leaderboard", authority)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct EndLeaderboardSeason<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = authority,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: mempool_account, checked by the arcium program
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: executing_pool, checked by the arcium program
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: computation_account, checked by the arcium program
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_REVEAL_LEADERBOARD))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        seeds = [b"leaderboard", leaderboard.season_id.to_le_bytes().as_ref()],
+        bump = leaderboard.bump
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+    #[account(
+        mut,
+        seeds = [b"computation_allocator"],
+        bump = computation_allocator.bump,
+        constraint = computation_allocator.next_offset == computation_offset @ ErrorCode::StaleComputationOffset
+    )]
+    pub computation_allocator: Account<'info, ComputationAllocator>,
+    #[account(
+        init,
+        payer = authority,
+        space = ComputationRecord::LEN,
+        seeds = [b"computation", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[callback_accounts("reveal_leaderboard")]
+#[derive(Accounts)]
+pub struct RevealLeaderboardCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_REVEAL_LEADERBOARD))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub leaderboard: Account<'info, Leaderboard>,
+    #[account(mut)]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimPayout<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated,
+        constraint = prediction_market.privacy_mode != PrivacyMode::Public @ ErrorCode::PrivacyModeMismatch
+    )]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(
+        mut,
+        seeds = [b"position", user.key().as_ref(), prediction_market.key().as_ref()],
+        bump = user_position.bump,
+        constraint = is_current(&user_position) @ ErrorCode::AccountNotMigrated,
+        constraint = !user_position.is_public @ ErrorCode::PrivacyModeMismatch
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(
+        mut,
+        seeds = [b"profile", user.key().as_ref()],
+        bump = user_profile.bump,
+        constraint = is_current(&user_profile) @ ErrorCode::AccountNotMigrated
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.claims_paused @ ErrorCode::ClaimsPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRefund<'info> {
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated
+    )]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(
+        mut,
+        seeds = [b"position", user.key().as_ref(), prediction_market.key().as_ref()],
+        bump = user_position.bump,
+        constraint = is_current(&user_position) @ ErrorCode::AccountNotMigrated,
+        constraint = user_position.is_validated @ ErrorCode::PositionNotValidated
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.claims_paused @ ErrorCode::ClaimsPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(constraint = is_current(&parent_market) @ ErrorCode::AccountNotMigrated)]
+    pub parent_market: Option<Box<Account<'info, PredictionMarket>>>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitPublicVote<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated,
+        constraint = prediction_market.privacy_mode != PrivacyMode::Private @ ErrorCode::PrivacyModeMismatch
+    )]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(
+        init,
+        payer = user,
+        space = UserPosition::LEN,
+        seeds = [b"position", user.key().as_ref(), prediction_market.key().as_ref()],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(
+        mut,
+        seeds = [b"profile", user.key().as_ref()],
+        bump = user_profile.bump,
+        constraint = is_current(&user_profile) @ ErrorCode::AccountNotMigrated
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    // Created by the market's first vote
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = PublicTally::LEN,
+        seeds = [b"public_tally", prediction_market.key().as_ref()],
+        bump
+    )]
+    pub public_tally: Account<'info, PublicTally>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.votes_paused @ ErrorCode::VotingPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimPublicPayout<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated,
+        constraint = prediction_market.privacy_mode != PrivacyMode::Private @ ErrorCode::PrivacyModeMismatch
+    )]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(
+        mut,
+        seeds = [b"position", user.key().as_ref(), prediction_market.key().as_ref()],
+        bump = user_position.bump,
+        constraint = is_current(&user_position) @ ErrorCode::AccountNotMigrated,
+        constraint = user_position.is_public @ ErrorCode::PrivacyModeMismatch
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(
+        mut,
+        seeds = [b"profile", user.key().as_ref()],
+        bump = user_profile.bump,
+        constraint = is_current(&user_profile) @ ErrorCode::AccountNotMigrated
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(
+        seeds = [b"public_tally", prediction_market.key().as_ref()],
+        bump = public_tally.bump
+    )]
+    pub public_tally: Account<'info, PublicTally>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.claims_paused @ ErrorCode::ClaimsPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+#[queue_computation_accounts("calculate_payout_hybrid_v1", user)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct CalculateUserPayoutHybrid<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = user,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: mempool_account, checked by the arcium program
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: executing_pool, checked by the arcium program
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: computation_account, checked by the arcium program
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_CALCULATE_PAYOUT_HYBRID_V1))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated,
+        constraint = prediction_market.circuit_version >= CIRCUIT_VERSION_V3 @ ErrorCode::CircuitVersionMismatch,
+        constraint = prediction_market.privacy_mode == PrivacyMode::Hybrid @ ErrorCode::PrivacyModeMismatch
+    )]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(
+        mut,
+        seeds = [b"position", user.key().as_ref(), prediction_market.key().as_ref()],
+        bump = user_position.bump,
+        constraint = is_current(&user_position) @ ErrorCode::AccountNotMigrated,
+        constraint = !user_position.is_public @ ErrorCode::PrivacyModeMismatch
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    // Created here if the market has no public votes yet, so it reads as zero
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = PublicTally::LEN,
+        seeds = [b"public_tally", prediction_market.key().as_ref()],
+        bump
+    )]
+    pub public_tally: Account<'info, PublicTally>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.claims_paused @ ErrorCode::ClaimsPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [b"computation_allocator"],
+        bump = computation_allocator.bump,
+        constraint = computation_allocator.next_offset == computation_offset @ ErrorCode::StaleComputationOffset
+    )]
+    pub computation_allocator: Account<'info, ComputationAllocator>,
+    #[account(
+        init,
+        payer = user,
+        space = ComputationRecord::LEN,
+        seeds = [b"computation", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[callback_accounts("calculate_payout_hybrid_v1")]
+#[derive(Accounts)]
+pub struct CalculatePayoutHybridV1Callback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_CALCULATE_PAYOUT_HYBRID_V1))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(mut)]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[queue_computation_accounts("calculate_public_payout_v1", user)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct CalculatePublicPayout<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = user,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: mempool_account, checked by the arcium program
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: executing_pool, checked by the arcium program
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: computation_account, checked by the arcium program
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_CALCULATE_PUBLIC_PAYOUT_V1))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated,
+        constraint = prediction_market.circuit_version >= CIRCUIT_VERSION_V3 @ ErrorCode::CircuitVersionMismatch,
+        constraint = prediction_market.privacy_mode == PrivacyMode::Hybrid @ ErrorCode::PrivacyModeMismatch
+    )]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(
+        mut,
+        seeds = [b"position", user.key().as_ref(), prediction_market.key().as_ref()],
+        bump = user_position.bump,
+        constraint = is_current(&user_position) @ ErrorCode::AccountNotMigrated,
+        constraint = user_position.is_public @ ErrorCode::PrivacyModeMismatch
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    // Created here if the market has no public votes yet, so it reads as zero
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = PublicTally::LEN,
+        seeds = [b"public_tally", prediction_market.key().as_ref()],
+        bump
+    )]
+    pub public_tally: Account<'info, PublicTally>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.claims_paused @ ErrorCode::ClaimsPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [b"computation_allocator"],
+        bump = computation_allocator.bump,
+        constraint = computation_allocator.next_offset == computation_offset @ ErrorCode::StaleComputationOffset
+    )]
+    pub computation_allocator: Account<'info, ComputationAllocator>,
+    #[account(
+        init,
+        payer = user,
+        space = ComputationRecord::LEN,
+        seeds = [b"computation", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[callback_accounts("calculate_public_payout_v1")]
+#[derive(Accounts)]
+pub struct CalculatePublicPayoutV1Callback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_CALCULATE_PUBLIC_PAYOUT_V1))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub user_position: Account<'info, UserPosition>,
+    #[account(mut)]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[queue_computation_accounts("calculate_market_odds_hybrid_v1", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct CalculateMarketOddsHybrid<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: mempool_account, checked by the arcium program
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: executing_pool, checked by the arcium program
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: computation_account, checked by the arcium program
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_CALCULATE_ODDS_HYBRID_V1))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated,
+        constraint = prediction_market.privacy_mode == PrivacyMode::Hybrid @ ErrorCode::PrivacyModeMismatch
+    )]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    // Created here if the market has no public votes yet, so it reads as zero
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = PublicTally::LEN,
+        seeds = [b"public_tally", prediction_market.key().as_ref()],
+        bump
+    )]
+    pub public_tally: Account<'info, PublicTally>,
+    #[account(
+        mut,
+        seeds = [b"computation_allocator"],
+        bump = computation_allocator.bump,
+        constraint = computation_allocator.next_offset == computation_offset @ ErrorCode::StaleComputationOffset
+    )]
+    pub computation_allocator: Account<'info, ComputationAllocator>,
+    #[account(
+        init,
+        payer = payer,
+        space = ComputationRecord::LEN,
+        seeds = [b"computation", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[callback_accounts("calculate_market_odds_hybrid_v1")]
+#[derive(Accounts)]
+pub struct CalculateMarketOddsHybridV1Callback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_CALCULATE_ODDS_HYBRID_V1))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(mut)]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[derive(Accounts)]
+pub struct CreateParlay<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        init,
+        payer = user,
+        space = Parlay::LEN,
+        seeds = [b"parlay", user.key().as_ref(), user_profile.parlay_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub parlay: Account<'info, Parlay>,
+    #[account(mut, seeds = [b"parlay_pool"], bump = parlay_pool.bump)]
+    pub parlay_pool: Account<'info, ParlayPool>,
+    #[account(
+        mut,
+        seeds = [b"profile", user.key().as_ref()],
+        bump = user_profile.bump,
+        constraint = is_current(&user_profile) @ ErrorCode::AccountNotMigrated
+    )]
+    pub user_profile: Account<'info, UserProfile>,
+    #[account(seeds = [b"mxe_key_epoch"], bump = mxe_key_epoch.bump)]
+    pub mxe_key_epoch: Account<'info, MxeKeyEpoch>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.votes_paused @ ErrorCode::VotingPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[queue_computation_accounts("calculate_parlay_payout_v1", user)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct SettleParlay<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = user,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: mempool_account, checked by the arcium program
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: executing_pool, checked by the arcium program
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: computation_account, checked by the arcium program
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_CALCULATE_PARLAY_PAYOUT_V1))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        mut,
+        has_one = user @ ErrorCode::Unauthorized,
+        constraint = parlay.key_epoch == mxe_key_epoch.epoch @ ErrorCode::StaleKeyEpoch
+    )]
+    pub parlay: Account<'info, Parlay>,
+    #[account(seeds = [b"parlay_pool"], bump = parlay_pool.bump)]
+    pub parlay_pool: Account<'info, ParlayPool>,
+    #[account(seeds = [b"mxe_key_epoch"], bump = mxe_key_epoch.bump)]
+    pub mxe_key_epoch: Account<'info, MxeKeyEpoch>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.claims_paused @ ErrorCode::ClaimsPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [b"computation_allocator"],
+        bump = computation_allocator.bump,
+        constraint = computation_allocator.next_offset == computation_offset @ ErrorCode::StaleComputationOffset
+    )]
+    pub computation_allocator: Account<'info, ComputationAllocator>,
+    #[account(
+        init,
+        payer = user,
+        space = ComputationRecord::LEN,
+        seeds = [b"computation", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[callback_accounts("calculate_parlay_payout_v1")]
+#[derive(Accounts)]
+pub struct CalculateParlayPayoutV1Callback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_CALCULATE_PARLAY_PAYOUT_V1))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub parlay: Account<'info, Parlay>,
+    #[account(mut)]
+    pub parlay_pool: Account<'info, ParlayPool>,
+    #[account(mut)]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimParlay<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(mut, has_one = user @ ErrorCode::Unauthorized)]
+    pub parlay: Account<'info, Parlay>,
+    #[account(mut, seeds = [b"parlay_pool"], bump = parlay_pool.bump)]
+    pub parlay_pool: Account<'info, ParlayPool>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.claims_paused @ ErrorCode::ClaimsPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeParlayPool<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = ParlayPool::LEN,
+        seeds = [b"parlay_pool"],
+        bump
+    )]
+    pub parlay_pool: Account<'info, ParlayPool>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.is_admin(&payer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundParlayPool<'info> {
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    #[account(mut, seeds = [b"parlay_pool"], bump = parlay_pool.bump)]
+    pub parlay_pool: Account<'info, ParlayPool>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawParlayPool<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized,
+        constraint = !program_config.claims_paused @ ErrorCode::ClaimsPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(mut, seeds = [b"parlay_pool"], bump = parlay_pool.bump)]
+    pub parlay_pool: Account<'info, ParlayPool>,
+    #[account(mut)]
+    pub recipient: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeProgramConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    // The program's upgrade authority, so deployment can't be front-run
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = anchor_lang::solana_program::bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(admin.key())
+            @ ErrorCode::Unauthorized
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    #[account(
+        init,
+        payer = admin,
+        space = ProgramConfig::LEN,
+        seeds = [b"program_config"],
+        bump
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        init,
+        payer = admin,
+        space = Treasury::LEN,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    pub pending_admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.pending_admin == Some(pending_admin.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetPauseFlags<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.can_pause(&authority.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateFeeConfig<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawProtocolFees<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized,
+        constraint = !program_config.claims_paused @ ErrorCode::ClaimsPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(mut, seeds = [b"treasury"], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+    pub recipient: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawSlashedBonds<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized,
+        constraint = !program_config.claims_paused @ ErrorCode::ClaimsPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(mut, seeds = [b"treasury"], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+    #[account(mut)]
+    pub recipient: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawCreatorFees<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    #[account(
+        mut,
+        has_one = creator @ ErrorCode::Unauthorized,
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated
+    )]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(mut, seeds = [b"treasury"], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.claims_paused @ ErrorCode::ClaimsPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct SetCreationBonds<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct FlagMalformedMarket<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        has_one = admin @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated
+    )]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(mut, seeds = [b"treasury"], bump = treasury.bump)]
+    pub treasury: Account<'info, Treasury>,
+    #[account(mut)]
+    pub dispute_winner: Option<SystemAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct ReclaimCreationBond<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    #[account(
+        mut,
+        has_one = creator @ ErrorCode::Unauthorized,
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated
+    )]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.claims_paused @ ErrorCode::ClaimsPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+#[queue_computation_accounts("calculate_market_odds_v1", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct CalculateMarketOdds<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: mempool_account, checked by the arcium program
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: executing_pool, checked by the arcium program
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: computation_account, checked by the arcium program
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_CALCULATE_ODDS_V1))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated,
+        constraint = prediction_market.circuit_version >= CIRCUIT_VERSION_V1 @ ErrorCode::CircuitVersionMismatch,
+        constraint = prediction_market.privacy_mode == PrivacyMode::Private @ ErrorCode::PrivacyModeMismatch
+    )]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(
+        mut,
+        seeds = [b"computation_allocator"],
+        bump = computation_allocator.bump,
+        constraint = computation_allocator.next_offset == computation_offset @ ErrorCode::StaleComputationOffset
+    )]
+    pub computation_allocator: Account<'info, ComputationAllocator>,
+    #[account(
+        init,
+        payer = payer,
+        space = ComputationRecord::LEN,
+        seeds = [b"computation", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[callback_accounts("calculate_market_odds_v1")]
+#[derive(Accounts)]
+pub struct CalculateMarketOddsV1Callback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_CALCULATE_ODDS_V1))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(mut)]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeMxeKeyEpoch<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        payer = admin,
+        space = MxeKeyEpoch::LEN,
+        seeds = [b"mxe_key_epoch"],
+        bump
+    )]
+    pub mxe_key_epoch: Account<'info, MxeKeyEpoch>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.is_admin(&admin.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecordMxeKeyRotation<'info> {
+    pub admin: Signer<'info>,
+    #[account(mut, seeds = [b"mxe_key_epoch"], bump = mxe_key_epoch.bump)]
+    pub mxe_key_epoch: Account<'info, MxeKeyEpoch>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.is_admin(&admin.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+#[queue_computation_accounts("reencrypt_market_state_v1", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct ReencryptMarketState<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, SignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!())]
+    /// CHECK: mempool_account, checked by the arcium program
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!())]
+    /// CHECK: executing_pool, checked by the arcium program
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset))]
+    /// CHECK: computation_account, checked by the arcium program
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_REENCRYPT_MARKET_STATE_V1))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated,
+        constraint = prediction_market.circuit_version >= CIRCUIT_VERSION_V1 @ ErrorCode::CircuitVersionMismatch
+    )]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(seeds = [b"mxe_key_epoch"], bump = mxe_key_epoch.bump)]
+    pub mxe_key_epoch: Account<'info, MxeKeyEpoch>,
+    #[account(
+        mut,
+        seeds = [b"computation_allocator"],
+        bump = computation_allocator.bump,
+        constraint = computation_allocator.next_offset == computation_offset @ ErrorCode::StaleComputationOffset
+    )]
+    pub computation_allocator: Account<'info, ComputationAllocator>,
+    #[account(
+        init,
+        payer = payer,
+        space = ComputationRecord::LEN,
+        seeds = [b"computation", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[callback_accounts("reencrypt_market_state_v1")]
+#[derive(Accounts)]
+pub struct ReencryptMarketStateV1Callback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_REENCRYPT_MARKET_STATE_V1))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar, checked by the account constraint
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub prediction_market: Account<'info, PredictionMarket>,
+    pub mxe_key_epoch: Account<'info, MxeKeyEpoch>,
     #[account(mut)]
-    pub user: Signer<'info>,
-    #[account(
-        init,
-        payer = user,
-        space = 8 + 120,
-        seeds = [b"profile", user.key().as_ref()],
-        bump
-    )]
-    pub user_profile: Account<'info, UserProfile>,
-    pub system_program: Program<'info, System>,
+    pub computation_record: Account<'info, ComputationRecord>,
 }
 
-#[queue_computation_accounts("submit_private_vote", user)]
+// Positions to re-encrypt are passed as remaining accounts
+#[queue_computation_accounts("reencrypt_positions_v1", payer)]
 #[derive(Accounts)]
 #[instruction(computation_offset: u64)]
-pub struct SubmitEncryptedVote<'info> {
+pub struct ReencryptPositions<'info> {
     #[account(mut)]
-    pub user: Signer<'info>,
+    pub payer: Signer<'info>,
     #[account(
         init_if_needed,
         space = 9,
-        payer = user,
+        payer = payer,
         seeds = [&SIGN_PDA_SEED],
         bump,
         address = derive_sign_pda!(),
@@ -742,7 +5792,7 @@ pub struct SubmitEncryptedVote<'info> {
     #[account(mut, address = derive_comp_pda!(computation_offset))]
     /// CHECK: computation_account, checked by the arcium program
     pub computation_account: UncheckedAccount<'info>,
-    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_SUBMIT_VOTE))]
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_REENCRYPT_POSITIONS_V1))]
     pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
     #[account(mut, address = derive_cluster_pda!(mxe_account))]
     pub cluster_account: Account<'info, Cluster>,
@@ -752,47 +5802,55 @@ pub struct SubmitEncryptedVote<'info> {
     pub clock_account: Account<'info, ClockAccount>,
     pub system_program: Program<'info, System>,
     pub arcium_program: Program<'info, Arcium>,
-    #[account(mut)]
-    pub prediction_market: Account<'info, PredictionMarket>,
     #[account(
-        init,
-        payer = user,
-        space = 8 + 600,
-        seeds = [b"position", user.key().as_ref(), prediction_market.key().as_ref()],
-        bump
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated,
+        constraint = prediction_market.circuit_version >= CIRCUIT_VERSION_V1 @ ErrorCode::CircuitVersionMismatch
     )]
-    pub user_position: Account<'info, UserPosition>,
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(seeds = [b"mxe_key_epoch"], bump = mxe_key_epoch.bump)]
+    pub mxe_key_epoch: Account<'info, MxeKeyEpoch>,
     #[account(
         mut,
-        seeds = [b"profile", user.key().as_ref()],
-        bump = user_profile.bump
+        seeds = [b"computation_allocator"],
+        bump = computation_allocator.bump,
+        constraint = computation_allocator.next_offset == computation_offset @ ErrorCode::StaleComputationOffset
     )]
-    pub user_profile: Account<'info, UserProfile>,
+    pub computation_allocator: Account<'info, ComputationAllocator>,
+    #[account(
+        init,
+        payer = payer,
+        space = ComputationRecord::LEN,
+        seeds = [b"computation", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_record: Account<'info, ComputationRecord>,
 }
 
-#[callback_accounts("submit_private_vote")]
+// Re-encrypted positions follow as remaining accounts, in batch order
+#[callback_accounts("reencrypt_positions_v1")]
 #[derive(Accounts)]
-pub struct SubmitPrivateVoteCallback<'info> {
+pub struct ReencryptPositionsV1Callback<'info> {
     pub arcium_program: Program<'info, Arcium>,
-    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_SUBMIT_VOTE))]
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_REENCRYPT_POSITIONS_V1))]
     pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
     #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
     /// CHECK: instructions_sysvar, checked by the account constraint
     pub instructions_sysvar: AccountInfo<'info>,
+    pub mxe_key_epoch: Account<'info, MxeKeyEpoch>,
     #[account(mut)]
-    pub user_position: Account<'info, UserPosition>,
+    pub computation_record: Account<'info, ComputationRecord>,
 }
 
-#[queue_computation_accounts("aggregate_market_votes", payer)]
+#[queue_computation_accounts("disclose_vote_v1", user)]
 #[derive(Accounts)]
 #[instruction(computation_offset: u64)]
-pub struct AggregateVotes<'info> {
+pub struct DisclosePosition<'info> {
     #[account(mut)]
-    pub payer: Signer<'info>,
+    pub user: Signer<'info>,
     #[account(
         init_if_needed,
         space = 9,
-        payer = payer,
+        payer = user,
         seeds = [&SIGN_PDA_SEED],
         bump,
         address = derive_sign_pda!(),
@@ -809,7 +5867,7 @@ pub struct AggregateVotes<'info> {
     #[account(mut, address = derive_comp_pda!(computation_offset))]
     /// CHECK: computation_account, checked by the arcium program
     pub computation_account: UncheckedAccount<'info>,
-    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_AGGREGATE))]
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_DISCLOSE_VOTE_V1))]
     pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
     #[account(mut, address = derive_cluster_pda!(mxe_account))]
     pub cluster_account: Account<'info, Cluster>,
@@ -819,42 +5877,89 @@ pub struct AggregateVotes<'info> {
     pub clock_account: Account<'info, ClockAccount>,
     pub system_program: Program<'info, System>,
     pub arcium_program: Program<'info, Arcium>,
-    #[account(mut)]
+    #[account(
+        address = user_position.market,
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated,
+        constraint = prediction_market.circuit_version >= CIRCUIT_VERSION_V1 @ ErrorCode::CircuitVersionMismatch,
+        constraint = prediction_market.privacy_mode != PrivacyMode::Public @ ErrorCode::PrivacyModeMismatch
+    )]
     pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(
+        mut,
+        seeds = [b"position", user.key().as_ref(), prediction_market.key().as_ref()],
+        bump = user_position.bump,
+        constraint = is_current(&user_position) @ ErrorCode::AccountNotMigrated,
+        constraint = user_position.is_validated @ ErrorCode::PositionNotValidated,
+        constraint = !user_position.is_public @ ErrorCode::PrivacyModeMismatch
+    )]
     pub user_position: Account<'info, UserPosition>,
+    #[account(
+        init,
+        payer = user,
+        space = DisclosureRecord::LEN,
+        seeds = [
+            b"disclosure",
+            user_position.key().as_ref(),
+            user_position.disclosure_count.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub disclosure_record: Account<'info, DisclosureRecord>,
+    #[account(
+        seeds = [b"mxe_key_epoch"],
+        bump = mxe_key_epoch.bump,
+        constraint = user_position.key_epoch == mxe_key_epoch.epoch @ ErrorCode::StaleKeyEpoch
+    )]
+    pub mxe_key_epoch: Account<'info, MxeKeyEpoch>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.claims_paused @ ErrorCode::ClaimsPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        seeds = [b"computation_allocator"],
+        bump = computation_allocator.bump,
+        constraint = computation_allocator.next_offset == computation_offset @ ErrorCode::StaleComputationOffset
+    )]
+    pub computation_allocator: Account<'info, ComputationAllocator>,
+    #[account(
+        init,
+        payer = user,
+        space = ComputationRecord::LEN,
+        seeds = [b"computation", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_record: Account<'info, ComputationRecord>,
 }
 
-#[callback_accounts("aggregate_market_votes")]
+#[callback_accounts("disclose_vote_v1")]
 #[derive(Accounts)]
-pub struct AggregateMarketVotesCallback<'info> {
+pub struct DiscloseVoteV1Callback<'info> {
     pub arcium_program: Program<'info, Arcium>,
-    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_AGGREGATE))]
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_DISCLOSE_VOTE_V1))]
     pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
     #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
     /// CHECK: instructions_sysvar, checked by the account constraint
     pub instructions_sysvar: AccountInfo<'info>,
     #[account(mut)]
-    pub prediction_market: Account<'info, PredictionMarket>,
-}
-
-#[derive(Accounts)]
-pub struct ResolveMarket<'info> {
-    #[account(mut)]
-    pub oracle: Signer<'info>,
+    pub disclosure_record: Account<'info, DisclosureRecord>,
     #[account(mut)]
-    pub prediction_market: Account<'info, PredictionMarket>,
+    pub computation_record: Account<'info, ComputationRecord>,
 }
 
-#[queue_computation_accounts("calculate_payout", user)]
+// Positions to reveal are passed as remaining accounts
+#[queue_computation_accounts("reveal_votes_v1", payer)]
 #[derive(Accounts)]
 #[instruction(computation_offset: u64)]
-pub struct CalculateUserPayout<'info> {
+pub struct RevealPositions<'info> {
     #[account(mut)]
-    pub user: Signer<'info>,
+    pub payer: Signer<'info>,
     #[account(
         init_if_needed,
         space = 9,
-        payer = user,
+        payer = payer,
         seeds = [&SIGN_PDA_SEED],
         bump,
         address = derive_sign_pda!(),
@@ -871,7 +5976,7 @@ pub struct CalculateUserPayout<'info> {
     #[account(mut, address = derive_comp_pda!(computation_offset))]
     /// CHECK: computation_account, checked by the arcium program
     pub computation_account: UncheckedAccount<'info>,
-    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_CALCULATE_PAYOUT))]
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_REVEAL_VOTES_V1))]
     pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
     #[account(mut, address = derive_cluster_pda!(mxe_account))]
     pub cluster_account: Account<'info, Cluster>,
@@ -881,102 +5986,405 @@ pub struct CalculateUserPayout<'info> {
     pub clock_account: Account<'info, ClockAccount>,
     pub system_program: Program<'info, System>,
     pub arcium_program: Program<'info, Arcium>,
+    #[account(
+        constraint = is_current(&prediction_market) @ ErrorCode::AccountNotMigrated,
+        constraint = prediction_market.circuit_version >= CIRCUIT_VERSION_V1 @ ErrorCode::CircuitVersionMismatch
+    )]
     pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(seeds = [b"mxe_key_epoch"], bump = mxe_key_epoch.bump)]
+    pub mxe_key_epoch: Account<'info, MxeKeyEpoch>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = !program_config.claims_paused @ ErrorCode::ClaimsPaused
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
     #[account(
         mut,
-        seeds = [b"position", user.key().as_ref(), prediction_market.key().as_ref()],
-        bump = user_position.bump
+        seeds = [b"computation_allocator"],
+        bump = computation_allocator.bump,
+        constraint = computation_allocator.next_offset == computation_offset @ ErrorCode::StaleComputationOffset
     )]
-    pub user_position: Account<'info, UserPosition>,
+    pub computation_allocator: Account<'info, ComputationAllocator>,
+    #[account(
+        init,
+        payer = payer,
+        space = ComputationRecord::LEN,
+        seeds = [b"computation", computation_offset.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub computation_record: Account<'info, ComputationRecord>,
 }
 
-#[callback_accounts("calculate_payout")]
+// Revealed positions follow as remaining accounts, in batch order
+#[callback_accounts("reveal_votes_v1")]
 #[derive(Accounts)]
-pub struct CalculatePayoutCallback<'info> {
+pub struct RevealVotesV1Callback<'info> {
     pub arcium_program: Program<'info, Arcium>,
-    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_CALCULATE_PAYOUT))]
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_REVEAL_VOTES_V1))]
     pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
     #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
     /// CHECK: instructions_sysvar, checked by the account constraint
     pub instructions_sysvar: AccountInfo<'info>,
+    pub prediction_market: Account<'info, PredictionMarket>,
+    #[account(mut)]
+    pub computation_record: Account<'info, ComputationRecord>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeCircuitRegistry<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        payer = admin,
+        space = CircuitRegistry::LEN,
+        seeds = [b"circuit_registry"],
+        bump
+    )]
+    pub circuit_registry: Account<'info, CircuitRegistry>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.is_admin(&admin.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeComputationAllocator<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init,
+        payer = payer,
+        space = ComputationAllocator::LEN,
+        seeds = [b"computation_allocator"],
+        bump
+    )]
+    pub computation_allocator: Account<'info, ComputationAllocator>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.is_admin(&payer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetCircuitSource<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"circuit_registry"],
+        bump = circuit_registry.bump,
+        realloc = CircuitRegistry::LEN,
+        realloc::payer = admin,
+        realloc::zero = false
+    )]
+    pub circuit_registry: Account<'info, CircuitRegistry>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.is_admin(&admin.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    pub system_program: Program<'info, System>,
+}
+
+// Init comp def contexts; only the program config admin may initialize
+#[init_computation_definition_accounts("submit_private_vote_v1", payer)]
+#[derive(Accounts)]
+pub struct InitSubmitVoteCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"circuit_registry"], bump = circuit_registry.bump)]
+    pub circuit_registry: Account<'info, CircuitRegistry>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.is_admin(&payer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+#[init_computation_definition_accounts("aggregate_market_votes_v1", payer)]
+#[derive(Accounts)]
+pub struct InitAggregateCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"circuit_registry"], bump = circuit_registry.bump)]
+    pub circuit_registry: Account<'info, CircuitRegistry>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.is_admin(&payer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+#[init_computation_definition_accounts("aggregate_market_votes_v2", payer)]
+#[derive(Accounts)]
+pub struct InitAggregateV2CompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"circuit_registry"], bump = circuit_registry.bump)]
+    pub circuit_registry: Account<'info, CircuitRegistry>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.is_admin(&payer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+#[init_computation_definition_accounts("calculate_payout_v1", payer)]
+#[derive(Accounts)]
+pub struct InitCalculatePayoutCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"circuit_registry"], bump = circuit_registry.bump)]
+    pub circuit_registry: Account<'info, CircuitRegistry>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.is_admin(&payer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+#[init_computation_definition_accounts("calculate_payout_v2", payer)]
+#[derive(Accounts)]
+pub struct InitCalculatePayoutV2CompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"circuit_registry"], bump = circuit_registry.bump)]
+    pub circuit_registry: Account<'info, CircuitRegistry>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.is_admin(&payer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+#[init_computation_definition_accounts("calculate_market_odds_v1", payer)]
+#[derive(Accounts)]
+pub struct InitCalculateOddsCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"circuit_registry"], bump = circuit_registry.bump)]
+    pub circuit_registry: Account<'info, CircuitRegistry>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.is_admin(&payer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+#[init_computation_definition_accounts("update_reputation_v1", payer)]
+#[derive(Accounts)]
+pub struct InitUpdateReputationCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"circuit_registry"], bump = circuit_registry.bump)]
+    pub circuit_registry: Account<'info, CircuitRegistry>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.is_admin(&payer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+#[init_computation_definition_accounts("insert_leaderboard_entry", payer)]
+#[derive(Accounts)]
+pub struct InitInsertLeaderboardCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"circuit_registry"], bump = circuit_registry.bump)]
+    pub circuit_registry: Account<'info, CircuitRegistry>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.is_admin(&payer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+#[init_computation_definition_accounts("reveal_leaderboard", payer)]
+#[derive(Accounts)]
+pub struct InitRevealLeaderboardCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"circuit_registry"], bump = circuit_registry.bump)]
+    pub circuit_registry: Account<'info, CircuitRegistry>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.is_admin(&payer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+#[init_computation_definition_accounts("reencrypt_market_state_v1", payer)]
+#[derive(Accounts)]
+pub struct InitReencryptMarketStateCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
     #[account(mut)]
-    pub user_position: Account<'info, UserPosition>,
+    /// CHECK: comp_def_account, checked by arcium program
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"circuit_registry"], bump = circuit_registry.bump)]
+    pub circuit_registry: Account<'info, CircuitRegistry>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.is_admin(&payer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
 }
 
+#[init_computation_definition_accounts("reencrypt_positions_v1", payer)]
 #[derive(Accounts)]
-pub struct ClaimPayout<'info> {
+pub struct InitReencryptPositionsCompDef<'info> {
     #[account(mut)]
-    pub user: Signer<'info>,
-    pub prediction_market: Account<'info, PredictionMarket>,
-    #[account(
-        mut,
-        seeds = [b"position", user.key().as_ref(), prediction_market.key().as_ref()],
-        bump = user_position.bump
-    )]
-    pub user_position: Account<'info, UserPosition>,
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"circuit_registry"], bump = circuit_registry.bump)]
+    pub circuit_registry: Account<'info, CircuitRegistry>,
     #[account(
-        mut,
-        seeds = [b"profile", user.key().as_ref()],
-        bump = user_profile.bump
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.is_admin(&payer.key()) @ ErrorCode::Unauthorized
     )]
-    pub user_profile: Account<'info, UserProfile>,
+    pub program_config: Account<'info, ProgramConfig>,
 }
 
-#[queue_computation_accounts("calculate_market_odds", payer)]
+#[init_computation_definition_accounts("disclose_vote_v1", payer)]
 #[derive(Accounts)]
-#[instruction(computation_offset: u64)]
-pub struct CalculateMarketOdds<'info> {
+pub struct InitDiscloseVoteCompDef<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program
+    pub comp_def_account: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"circuit_registry"], bump = circuit_registry.bump)]
+    pub circuit_registry: Account<'info, CircuitRegistry>,
     #[account(
-        init_if_needed,
-        space = 9,
-        payer = payer,
-        seeds = [&SIGN_PDA_SEED],
-        bump,
-        address = derive_sign_pda!(),
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.is_admin(&payer.key()) @ ErrorCode::Unauthorized
     )]
-    pub sign_pda_account: Account<'info, SignerAccount>,
-    #[account(address = derive_mxe_pda!())]
-    pub mxe_account: Account<'info, MXEAccount>,
-    #[account(mut, address = derive_mempool_pda!())]
-    /// CHECK: mempool_account, checked by the arcium program
-    pub mempool_account: UncheckedAccount<'info>,
-    #[account(mut, address = derive_execpool_pda!())]
-    /// CHECK: executing_pool, checked by the arcium program
-    pub executing_pool: UncheckedAccount<'info>,
-    #[account(mut, address = derive_comp_pda!(computation_offset))]
-    /// CHECK: computation_account, checked by the arcium program
-    pub computation_account: UncheckedAccount<'info>,
-    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_CALCULATE_ODDS))]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    #[account(mut, address = derive_cluster_pda!(mxe_account))]
-    pub cluster_account: Account<'info, Cluster>,
-    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
-    pub pool_account: Account<'info, FeePool>,
-    #[account(address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
-    pub clock_account: Account<'info, ClockAccount>,
-    pub system_program: Program<'info, System>,
-    pub arcium_program: Program<'info, Arcium>,
-    pub prediction_market: Account<'info, PredictionMarket>,
+    pub program_config: Account<'info, ProgramConfig>,
 }
 
-#[callback_accounts("calculate_market_odds")]
+#[init_computation_definition_accounts("reveal_votes_v1", payer)]
 #[derive(Accounts)]
-pub struct CalculateMarketOddsCallback<'info> {
+pub struct InitRevealVotesCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account, checked by arcium program
+    pub comp_def_account: UncheckedAccount<'info>,
     pub arcium_program: Program<'info, Arcium>,
-    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_CALCULATE_ODDS))]
-    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
-    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
-    /// CHECK: instructions_sysvar, checked by the account constraint
-    pub instructions_sysvar: AccountInfo<'info>,
-    pub prediction_market: Account<'info, PredictionMarket>,
+    pub system_program: Program<'info, System>,
+    #[account(seeds = [b"circuit_registry"], bump = circuit_registry.bump)]
+    pub circuit_registry: Account<'info, CircuitRegistry>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.is_admin(&payer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
 }
 
-// Init comp def contexts
-#[init_computation_definition_accounts("submit_private_vote", payer)]
+#[init_computation_definition_accounts("calculate_payout_hybrid_v1", payer)]
 #[derive(Accounts)]
-pub struct InitSubmitVoteCompDef<'info> {
+pub struct InitCalculatePayoutHybridCompDef<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     #[account(mut, address = derive_mxe_pda!())]
@@ -986,11 +6394,19 @@ pub struct InitSubmitVoteCompDef<'info> {
     pub comp_def_account: UncheckedAccount<'info>,
     pub arcium_program: Program<'info, Arcium>,
     pub system_program: Program<'info, System>,
+    #[account(seeds = [b"circuit_registry"], bump = circuit_registry.bump)]
+    pub circuit_registry: Account<'info, CircuitRegistry>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.is_admin(&payer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
 }
 
-#[init_computation_definition_accounts("aggregate_market_votes", payer)]
+#[init_computation_definition_accounts("calculate_public_payout_v1", payer)]
 #[derive(Accounts)]
-pub struct InitAggregateCompDef<'info> {
+pub struct InitCalculatePublicPayoutCompDef<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     #[account(mut, address = derive_mxe_pda!())]
@@ -1000,11 +6416,19 @@ pub struct InitAggregateCompDef<'info> {
     pub comp_def_account: UncheckedAccount<'info>,
     pub arcium_program: Program<'info, Arcium>,
     pub system_program: Program<'info, System>,
+    #[account(seeds = [b"circuit_registry"], bump = circuit_registry.bump)]
+    pub circuit_registry: Account<'info, CircuitRegistry>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.is_admin(&payer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
 }
 
-#[init_computation_definition_accounts("calculate_payout", payer)]
+#[init_computation_definition_accounts("calculate_market_odds_hybrid_v1", payer)]
 #[derive(Accounts)]
-pub struct InitCalculatePayoutCompDef<'info> {
+pub struct InitCalculateOddsHybridCompDef<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     #[account(mut, address = derive_mxe_pda!())]
@@ -1014,11 +6438,19 @@ pub struct InitCalculatePayoutCompDef<'info> {
     pub comp_def_account: UncheckedAccount<'info>,
     pub arcium_program: Program<'info, Arcium>,
     pub system_program: Program<'info, System>,
+    #[account(seeds = [b"circuit_registry"], bump = circuit_registry.bump)]
+    pub circuit_registry: Account<'info, CircuitRegistry>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.is_admin(&payer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
 }
 
-#[init_computation_definition_accounts("calculate_market_odds", payer)]
+#[init_computation_definition_accounts("calculate_parlay_payout_v1", payer)]
 #[derive(Accounts)]
-pub struct InitCalculateOddsCompDef<'info> {
+pub struct InitCalculateParlayPayoutCompDef<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     #[account(mut, address = derive_mxe_pda!())]
@@ -1028,6 +6460,24 @@ pub struct InitCalculateOddsCompDef<'info> {
     pub comp_def_account: UncheckedAccount<'info>,
     pub arcium_program: Program<'info, Arcium>,
     pub system_program: Program<'info, System>,
+    #[account(seeds = [b"circuit_registry"], bump = circuit_registry.bump)]
+    pub circuit_registry: Account<'info, CircuitRegistry>,
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.is_admin(&payer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: discriminator and layout are checked by the migrate handler
+    #[account(mut, owner = crate::ID)]
+    pub account: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 // =====================================================================
@@ -1038,13 +6488,24 @@ pub struct InitCalculateOddsCompDef<'info> {
 pub struct MarketCreatedEvent {
     pub market_id: u64,
     pub creator: Pubkey,
-    pub title: String,
+    pub metadata_uri: String,
+    pub metadata_hash: [u8; 32],
     pub category: u8,
     pub oracle_type: u8,
     pub voting_ends_at: i64,
+    pub creation_bond: u64,
+    pub privacy_mode: u8,
+    pub parent_market: Option<Pubkey>,
     pub timestamp: i64,
 }
 
+#[event]
+pub struct MarketMetadataUpdatedEvent {
+    pub market_id: u64,
+    pub metadata_uri: String,
+    pub metadata_hash: [u8; 32],
+}
+
 #[event]
 pub struct VoteSubmittedEvent {
     pub market_id: u64,
@@ -1053,6 +6514,23 @@ pub struct VoteSubmittedEvent {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct VoteCommittedEvent {
+    pub market_id: u64,
+    pub position: Pubkey,
+    pub leaf_index: u32,
+    pub leaf: [u8; 32],
+    pub root: [u8; 32],
+}
+
+#[event]
+pub struct VoteAggregatedEvent {
+    pub market_id: u64,
+    pub leaf_index: u32,
+    pub aggregated_vote_root: [u8; 32],
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct MarketResolvedEvent {
     pub market_id: u64,
@@ -1060,6 +6538,98 @@ pub struct MarketResolvedEvent {
     pub resolution_timestamp: i64,
 }
 
+#[event]
+pub struct AdminTransferProposedEvent {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+#[event]
+pub struct AdminTransferredEvent {
+    pub previous_admin: Pubkey,
+    pub admin: Pubkey,
+}
+
+#[event]
+pub struct GuardianUpdatedEvent {
+    pub guardian: Pubkey,
+}
+
+#[event]
+pub struct PauseFlagsUpdatedEvent {
+    pub authority: Pubkey,
+    pub markets_paused: bool,
+    pub votes_paused: bool,
+    pub claims_paused: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MarketCancelledEvent {
+    pub market_id: u64,
+    pub malformed: bool,        // Flagged by the admin rather than cancelled by the oracle
+    pub bond_slashed: u64,
+    pub bond_recipient: Pubkey, // Dispute winner or the treasury
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConditionalMarketCancelledEvent {
+    pub market_id: u64,
+    pub parent_market_id: u64,
+    pub parent_outcome: Option<bool>, // None if the parent was cancelled
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RefundClaimedEvent {
+    pub market_id: u64,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CreationBondsUpdatedEvent {
+    pub creation_bonds: [u64; MARKET_CATEGORY_COUNT],
+}
+
+#[event]
+pub struct CreationBondReturnedEvent {
+    pub market_id: u64,
+    pub creator: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct FeesChargedEvent {
+    pub market_id: u64,
+    pub protocol_fee: u64,
+    pub creator_fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeeConfigUpdatedEvent {
+    pub protocol_fee_bps: u16,
+    pub max_creator_fee_bps: u16,
+}
+
+#[event]
+pub struct SlashedBondsWithdrawnEvent {
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeesWithdrawnEvent {
+    pub market_id: Option<u64>, // None for protocol fees
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct PayoutClaimedEvent {
     pub market_id: u64,
@@ -1067,9 +6637,178 @@ pub struct PayoutClaimedEvent {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct PublicVoteSubmittedEvent {
+    pub market_id: u64,
+    pub user: Pubkey,
+    pub vote_choice: u8,
+    pub stake_amount: u64,
+    pub predicted_probability: u8,
+    pub conviction_score: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PublicPayoutClaimedEvent {
+    pub market_id: u64,
+    pub user: Pubkey,
+    pub payout_amount: u64,
+    pub was_correct: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ParlayCreatedEvent {
+    pub parlay: Pubkey,
+    pub user: Pubkey,
+    pub markets: [Pubkey; MAX_PARLAY_LEGS],
+    pub leg_count: u8,
+    pub stake_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ParlaySettledEvent {
+    pub parlay: Pubkey,
+    pub user: Pubkey,
+    pub payout_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ParlayClaimedEvent {
+    pub parlay: Pubkey,
+    pub user: Pubkey,
+    pub payout_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ParlayPoolFundedEvent {
+    pub funder: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ParlayPoolWithdrawnEvent {
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct MarketOddsUpdatedEvent {
     pub market_id: u64,
+    pub yes_probability: u8,
+    pub no_probability: u8,
+    pub participants: u32,
+    pub high_confidence: bool,
+    pub herding_index: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ComputationFailedEvent {
+    pub computation_offset: u64,
+    pub kind: ComputationKind,
+    pub target: Pubkey,
+    pub attempts: u8,
+}
+
+#[event]
+pub struct ComputationRetriedEvent {
+    pub failed_offset: u64,
+    pub computation_offset: u64,
+    pub target: Pubkey,
+    pub attempts: u8,
+}
+
+#[event]
+pub struct VoteRolledBackEvent {
+    pub market_id: u64,
+    pub user: Pubkey,
+    pub leaf_index: u32,
+    pub stake_amount: u64,
+    pub attempts: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MxeKeyRotatedEvent {
+    pub epoch: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MarketStateReencryptedEvent {
+    pub market_id: u64,
+    pub key_epoch: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PositionsReencryptedEvent {
+    pub market: Pubkey,
+    pub key_epoch: u32,
+    pub positions: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PositionDisclosedEvent {
+    pub position: Pubkey,
+    pub user: Pubkey,
+    pub recipient_pubkey: [u8; 32],
+    pub disclosure_index: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VoteRevealedEvent {
+    pub market_id: u64,
+    pub user: Pubkey,
+    pub vote_choice: u8,
+    pub predicted_probability: u8,
+    pub conviction_score: u16,
+    pub stake_amount: u64,
+}
+
+#[event]
+pub struct CircuitSourceUpdatedEvent {
+    pub comp_def_offset: u32,
+    pub source: String,
+    pub hash: [u8; 32],
+}
+
+#[event]
+pub struct LeaderboardSeasonStartedEvent {
+    pub season_id: u32,
+    pub authority: Pubkey,
+    pub starts_at: i64,
+    pub ends_at: i64,
+}
+
+#[event]
+pub struct LeaderboardEntrySubmittedEvent {
+    pub season_id: u32,
+    pub user: Pubkey,
+    pub rank: Option<u8>, // 1-based, None if outside the top-N
+}
+
+#[event]
+pub struct LeaderboardFinalizedEvent {
+    pub season_id: u32,
+    pub ranked_users: [Pubkey; 10],
+    pub scores: [u16; 10],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AccountMigratedEvent {
+    pub account: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
     pub timestamp: i64,
 }
 
@@ -1109,4 +6848,132 @@ pub enum ErrorCode {
     InvalidEncryptedState,
     #[msg("Cluster not set")]
     ClusterNotSet,
+    #[msg("Payout has not been calculated yet")]
+    PayoutNotCalculated,
+    #[msg("Reputation already recorded for this position")]
+    ReputationAlreadyRecorded,
+    #[msg("Reputation must be recorded before claiming")]
+    ReputationNotRecorded,
+    #[msg("Invalid leaderboard season")]
+    InvalidSeason,
+    #[msg("Leaderboard season is not active")]
+    SeasonNotActive,
+    #[msg("Only the season authority can do this")]
+    InvalidSeasonAuthority,
+    #[msg("A leaderboard update is already in progress")]
+    LeaderboardBusy,
+    #[msg("Profile has no private reputation yet")]
+    NoPrivateReputation,
+    #[msg("Profile already entered this season")]
+    AlreadyOnLeaderboard,
+    #[msg("Leaderboard has no entries")]
+    EmptyLeaderboard,
+    #[msg("Unauthorized")]
+    Unauthorized,
+    #[msg("Circuit source is too long (max 200 chars)")]
+    CircuitSourceTooLong,
+    #[msg("Circuit hash must be set")]
+    InvalidCircuitHash,
+    #[msg("Circuit registry is full")]
+    CircuitRegistryFull,
+    #[msg("Circuit is not registered")]
+    CircuitNotRegistered,
+    #[msg("Market was created with a different circuit version")]
+    CircuitVersionMismatch,
+    #[msg("Computation has not failed")]
+    ComputationNotFailed,
+    #[msg("Computation has been retried the maximum number of times")]
+    RetryLimitReached,
+    #[msg("Vote can't be rolled back yet")]
+    RollbackNotAllowed,
+    #[msg("Computation is still pending or can be retried")]
+    ComputationNotSettled,
+    #[msg("Computation record doesn't match this account")]
+    InvalidComputationRecord,
+    #[msg("Computation offset was already allocated; use the allocator's next_offset")]
+    StaleComputationOffset,
+    #[msg("Encrypted state predates the current MXE key; re-encrypt it first")]
+    StaleKeyEpoch,
+    #[msg("Already encrypted under the current MXE key")]
+    KeyEpochCurrent,
+    #[msg("Re-encryption batch must hold 1-4 positions of this market")]
+    InvalidReencryptBatch,
+    #[msg("Position's vote hasn't been validated")]
+    PositionNotValidated,
+    #[msg("Market wasn't created with reveal_after_resolution")]
+    RevealNotEnabled,
+    #[msg("Reveal batch must hold 1-4 positions of this market")]
+    InvalidRevealBatch,
+    #[msg("Position's vote has already been revealed")]
+    PositionAlreadyRevealed,
+    #[msg("Market's vote accumulator is full")]
+    VoteAccumulatorFull,
+    #[msg("Votes must be aggregated in accumulator order")]
+    VoteOutOfOrder,
+    #[msg("Fee exceeds the maximum (10%)")]
+    FeeTooHigh,
+    #[msg("Creator fee exceeds the configured maximum")]
+    CreatorFeeTooHigh,
+    #[msg("Not enough accrued fees")]
+    InsufficientAccruedFees,
+    #[msg("Not enough slashed bonds in the treasury")]
+    InsufficientSlashedBonds,
+    #[msg("Treasury doesn't hold enough lamports for this withdrawal")]
+    TreasuryUnderfunded,
+    #[msg("Market creation is paused")]
+    MarketCreationPaused,
+    #[msg("Voting is paused")]
+    VotingPaused,
+    #[msg("Payouts and withdrawals are paused")]
+    ClaimsPaused,
+    #[msg("Only active or locked markets can be cancelled")]
+    CannotCancelMarket,
+    #[msg("Market has no creation bond to return")]
+    NoCreationBond,
+    #[msg("Metadata is missing a title or resolution criteria, or has a bad image URL")]
+    InvalidMetadata,
+    #[msg("Resolution criteria are too long (max 1000 chars)")]
+    ResolutionCriteriaTooLong,
+    #[msg("Metadata URI is empty or too long (max 200 chars)")]
+    InvalidMetadataUri,
+    #[msg("Metadata hash must not be zero")]
+    InvalidMetadataHash,
+    #[msg("Metadata can't change after the first vote")]
+    MetadataLocked,
+    #[msg("Market index page is full")]
+    MarketIndexPageFull,
+    #[msg("Account uses an older layout; migrate it first")]
+    AccountNotMigrated,
+    #[msg("Account is already on the current layout")]
+    AccountAlreadyMigrated,
+    #[msg("Instruction doesn't apply to this market's or position's privacy mode")]
+    PrivacyModeMismatch,
+    #[msg("Parent market is missing, doesn't match, or has already settled")]
+    InvalidParentMarket,
+    #[msg("Parent market hasn't resolved yet")]
+    ParentMarketUnresolved,
+    #[msg("Parent market went against this market's condition; cancel it instead")]
+    ParentConditionFailed,
+    #[msg("Parent market resolved to this market's condition")]
+    ParentConditionMet,
+    #[msg("Market isn't cancelled")]
+    MarketNotCancelled,
+    #[msg("Parlay legs must be 1-4 distinct markets, passed in the parlay's order")]
+    InvalidParlayLegs,
+    #[msg("Every leg's market must resolve or be cancelled before the parlay settles")]
+    ParlayLegsUnresolved,
+    #[msg("Parlay is already settled")]
+    ParlayAlreadySettled,
+    #[msg("Stake would take the market past its stake limit")]
+    StakeLimitExceeded,
+    #[msg("A resolved leg's odds must be revealed after it stopped taking votes")]
+    ParlayOddsStale,
+    #[msg("Parlay pool can't cover this parlay's largest payout")]
+    ParlayPoolUnderfunded,
+    #[msg("A settlement for this parlay is already queued")]
+    ParlaySettlementPending,
+    #[msg("Parlay hasn't settled yet")]
+    ParlayNotSettled,
+    #[msg("Market's final odds are locked")]
+    FinalOddsLocked,
 }
@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+
+use crate::{ErrorCode, MARKET_CATEGORY_COUNT};
+
+// On-chain market discovery. The MarketRegistry PDA assigns market ids in order
+// and counts markets per category; CreatorMarkets counts each creator's markets.
+// Every new market's pubkey is appended to the current page of its category's
+// index and of its creator's index, so clients and other programs can list
+// markets by walking pages 0..=count / MARKET_INDEX_PAGE_SIZE without an
+// off-chain indexer.
+//
+//   category pages: ["category_index", [category slot], page (u32 le)]
+//   creator pages:  ["creator_index", creator, page (u32 le)]
+
+pub const MARKET_INDEX_PAGE_SIZE: usize = 32;
+
+#[account]
+#[derive(InitSpace)]
+pub struct MarketRegistry {
+    pub version: u8,
+    pub bump: u8,
+    pub next_market_id: u64,
+    pub category_counts: [u64; MARKET_CATEGORY_COUNT], // By MarketCategory::slot
+}
+
+impl MarketRegistry {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+    pub const VERSION: u8 = 1;
+
+    /// Hand out the next market id
+    pub fn assign_market_id(&mut self) -> u64 {
+        let market_id = self.next_market_id;
+        self.next_market_id += 1;
+        market_id
+    }
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct CreatorMarkets {
+    pub version: u8,
+    pub bump: u8,
+    pub creator: Pubkey,
+    pub market_count: u64,
+}
+
+impl CreatorMarkets {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+    pub const VERSION: u8 = 1;
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct MarketIndexPage {
+    pub version: u8,
+    pub bump: u8,
+    pub page: u32,
+    #[max_len(MARKET_INDEX_PAGE_SIZE)]
+    pub markets: Vec<Pubkey>, // In creation order
+}
+
+impl MarketIndexPage {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+    pub const VERSION: u8 = 1;
+
+    /// Page holding the entry after `count` existing ones
+    pub fn page_for(count: u64) -> u32 {
+        (count / MARKET_INDEX_PAGE_SIZE as u64) as u32
+    }
+
+    /// Append a market to this page, initializing it if it was just created
+    pub fn push(&mut self, bump: u8, page: u32, market: Pubkey) -> Result<()> {
+        if self.markets.is_empty() {
+            self.version = Self::VERSION;
+            self.bump = bump;
+            self.page = page;
+        }
+        require!(
+            self.markets.len() < MARKET_INDEX_PAGE_SIZE,
+            ErrorCode::MarketIndexPageFull
+        );
+        self.markets.push(market);
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+
+use crate::ErrorCode;
+
+// Market text lives off-chain. A market stores the URI of its metadata document
+// and the SHA-256 of the document's canonical JSON encoding, so the account stays
+// small and fixed-offset while clients can still prove the text they display is
+// what the creator committed to. The creator may swap the document until the
+// first vote arrives; after that the commitment is final.
+//
+// Schema version 1, keys in this order:
+//   {"version":1,"title":"..","description":"..","resolution_criteria":"..","image":".."}
+// The canonical encoding has no whitespace between tokens and escapes `"`, `\`
+// and control characters; hosts may serve the document in any formatting.
+
+pub const METADATA_SCHEMA_VERSION: u8 = 1;
+pub const MAX_METADATA_URI_LEN: usize = 200;
+pub const MAX_TITLE_LEN: usize = 200;
+pub const MAX_DESCRIPTION_LEN: usize = 1000;
+pub const MAX_RESOLUTION_CRITERIA_LEN: usize = 1000;
+pub const MAX_IMAGE_URL_LEN: usize = 200;
+
+/// Off-chain metadata document a market's `metadata_hash` commits to
+#[derive(Clone, Debug, PartialEq)]
+pub struct MarketMetadata {
+    pub title: String,
+    pub description: String,
+    pub resolution_criteria: String, // How the oracle decides the outcome
+    pub image: String,               // https:// or ipfs:// URL, or empty
+}
+
+impl MarketMetadata {
+    /// Check the document against the schema's limits
+    pub fn validate(&self) -> Result<()> {
+        require!(!self.title.trim().is_empty(), ErrorCode::InvalidMetadata);
+        require!(
+            !self.resolution_criteria.trim().is_empty(),
+            ErrorCode::InvalidMetadata
+        );
+        require!(self.title.len() <= MAX_TITLE_LEN, ErrorCode::TitleTooLong);
+        require!(
+            self.description.len() <= MAX_DESCRIPTION_LEN,
+            ErrorCode::DescriptionTooLong
+        );
+        require!(
+            self.resolution_criteria.len() <= MAX_RESOLUTION_CRITERIA_LEN,
+            ErrorCode::ResolutionCriteriaTooLong
+        );
+        require!(
+            self.image.len() <= MAX_IMAGE_URL_LEN,
+            ErrorCode::ImageUrlTooLong
+        );
+        require!(
+            self.image.is_empty()
+                || self.image.starts_with("https://")
+                || self.image.starts_with("ipfs://"),
+            ErrorCode::InvalidMetadata
+        );
+
+        Ok(())
+    }
+
+    /// Canonical JSON encoding the content hash is taken over
+    pub fn canonical_json(&self) -> String {
+        let mut json = format!("{{\"version\":{}", METADATA_SCHEMA_VERSION);
+        for (key, value) in [
+            ("title", &self.title),
+            ("description", &self.description),
+            ("resolution_criteria", &self.resolution_criteria),
+            ("image", &self.image),
+        ] {
+            json.push_str(",\"");
+            json.push_str(key);
+            json.push_str("\":");
+            push_json_string(&mut json, value);
+        }
+        json.push('}');
+        json
+    }
+
+    /// SHA-256 of the canonical JSON encoding
+    pub fn content_hash(&self) -> [u8; 32] {
+        hash(self.canonical_json().as_bytes()).to_bytes()
+    }
+}
+
+fn push_json_string(json: &mut String, value: &str) {
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+/// Check the on-chain half of the commitment: a bounded URI and a non-zero hash
+pub fn validate_metadata_commitment(metadata_uri: &str, metadata_hash: &[u8; 32]) -> Result<()> {
+    require!(
+        !metadata_uri.is_empty() && metadata_uri.len() <= MAX_METADATA_URI_LEN,
+        ErrorCode::InvalidMetadataUri
+    );
+    require!(*metadata_hash != [0; 32], ErrorCode::InvalidMetadataHash);
+
+    Ok(())
+}
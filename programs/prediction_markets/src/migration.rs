@@ -0,0 +1,599 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use crate::{
+    ErrorCode, MarketCategory, MarketStatus, OracleType, PredictionMarket, PrivacyMode,
+    Treasury, UserPosition, UserProfile, MAX_METADATA_URI_LEN,
+};
+
+// In-place layout upgrades. Every program account carries a version byte right
+// after its discriminator. Accounts written before versioning have no such byte
+// and are recognised by their length instead (version 0). migrate_* reads the old
+// layout, reallocs the account to the current size (the caller tops up rent) and
+// rewrites it at the current version, so markets and positions keep their
+// addresses, sealed state and history across upgrades.
+//
+// Each older layout is kept below as a frozen struct, and an account is brought
+// forward one version at a time (V0 -> V1 -> current).
+//
+// A version byte alone can't tell a legacy account apart, since arbitrary data
+// sits at that offset; handlers check `is_current`, which also checks the size.
+
+/// Sizes of the unversioned layouts (the hand-written `space` before InitSpace)
+pub const LEGACY_MARKET_LEN: usize = 1016;
+pub const LEGACY_POSITION_LEN: usize = 8 + 600;
+pub const LEGACY_PROFILE_LEN: usize = 8 + 280;
+
+const _: () = assert!(PredictionMarket::LEN != LEGACY_MARKET_LEN);
+const _: () = assert!(UserPosition::LEN != LEGACY_POSITION_LEN);
+const _: () = assert!(UserProfile::LEN != LEGACY_PROFILE_LEN);
+// Version 1 accounts must not be mistaken for legacy ones either
+const _: () = assert!(8 + PredictionMarketV1::INIT_SPACE != LEGACY_MARKET_LEN);
+const _: () = assert!(8 + UserPositionV1::INIT_SPACE != LEGACY_POSITION_LEN);
+const _: () = assert!(8 + UserProfileV1::INIT_SPACE != LEGACY_PROFILE_LEN);
+
+/// Accounts whose layout is versioned and migrated in place
+pub trait Versioned {
+    const CURRENT_VERSION: u8;
+    const CURRENT_LEN: usize;
+
+    fn version(&self) -> u8;
+}
+
+impl Versioned for PredictionMarket {
+    const CURRENT_VERSION: u8 = PredictionMarket::VERSION;
+    const CURRENT_LEN: usize = PredictionMarket::LEN;
+
+    fn version(&self) -> u8 {
+        self.version
+    }
+}
+
+impl Versioned for UserPosition {
+    const CURRENT_VERSION: u8 = UserPosition::VERSION;
+    const CURRENT_LEN: usize = UserPosition::LEN;
+
+    fn version(&self) -> u8 {
+        self.version
+    }
+}
+
+impl Versioned for UserProfile {
+    const CURRENT_VERSION: u8 = UserProfile::VERSION;
+    const CURRENT_LEN: usize = UserProfile::LEN;
+
+    fn version(&self) -> u8 {
+        self.version
+    }
+}
+
+/// Whether an account is on the current layout: right version byte and size
+pub fn is_current<'info, T>(account: &Account<'info, T>) -> bool
+where
+    T: Versioned + AccountSerialize + AccountDeserialize + Owner + Clone,
+{
+    account.version() == T::CURRENT_VERSION
+        && account.to_account_info().data_len() == T::CURRENT_LEN
+}
+
+/// Layout version of raw account data, 0 for a pre-versioning account
+fn stored_version(data: &[u8], discriminator: &[u8], legacy_len: usize) -> Result<u8> {
+    require!(
+        data.len() > discriminator.len() && data.starts_with(discriminator),
+        anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+    );
+    if data.len() == legacy_len {
+        return Ok(0);
+    }
+    Ok(data[discriminator.len()])
+}
+
+/// Read a market in any older layout and bring it to the current one
+pub fn upgrade_market(data: &[u8]) -> Result<(u8, PredictionMarket)> {
+    let discriminator = PredictionMarket::DISCRIMINATOR;
+    match stored_version(data, discriminator, LEGACY_MARKET_LEN)? {
+        0 => {
+            let legacy = PredictionMarketV0::deserialize(&mut &data[discriminator.len()..])?;
+            Ok((0, PredictionMarketV1::from(legacy).into()))
+        }
+        1 => {
+            let legacy = PredictionMarketV1::deserialize(&mut &data[discriminator.len()..])?;
+            Ok((1, legacy.into()))
+        }
+        _ => err!(ErrorCode::AccountAlreadyMigrated),
+    }
+}
+
+/// Read a position in any older layout and bring it to the current one
+pub fn upgrade_position(data: &[u8]) -> Result<(u8, UserPosition)> {
+    let discriminator = UserPosition::DISCRIMINATOR;
+    match stored_version(data, discriminator, LEGACY_POSITION_LEN)? {
+        0 => {
+            let legacy = UserPositionV0::deserialize(&mut &data[discriminator.len()..])?;
+            Ok((0, UserPositionV1::from(legacy).into()))
+        }
+        1 => {
+            let legacy = UserPositionV1::deserialize(&mut &data[discriminator.len()..])?;
+            Ok((1, legacy.into()))
+        }
+        _ => err!(ErrorCode::AccountAlreadyMigrated),
+    }
+}
+
+/// Read a profile in any older layout and bring it to the current one
+pub fn upgrade_profile(data: &[u8]) -> Result<(u8, UserProfile)> {
+    let discriminator = UserProfile::DISCRIMINATOR;
+    match stored_version(data, discriminator, LEGACY_PROFILE_LEN)? {
+        0 => {
+            let legacy = UserProfileV0::deserialize(&mut &data[discriminator.len()..])?;
+            Ok((0, UserProfileV1::from(legacy).into()))
+        }
+        1 => {
+            let legacy = UserProfileV1::deserialize(&mut &data[discriminator.len()..])?;
+            Ok((1, legacy.into()))
+        }
+        _ => err!(ErrorCode::AccountAlreadyMigrated),
+    }
+}
+
+/// Read the treasury in an older layout and bring it to the current one. The
+/// treasury was versioned from the start. `held` is what it holds above rent:
+/// it only ever received slashed bonds, which version 1 added to its protocol fees
+pub fn upgrade_treasury(data: &[u8], held: u64) -> Result<(u8, Treasury)> {
+    let discriminator = Treasury::DISCRIMINATOR;
+    require!(
+        data.len() > discriminator.len() && data.starts_with(discriminator),
+        anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+    );
+    match data[discriminator.len()] {
+        1 => {
+            let legacy = TreasuryV1::deserialize(&mut &data[discriminator.len()..])?;
+            let slashed_bonds_accrued = held.min(legacy.total_slashed_bonds);
+            Ok((
+                1,
+                Treasury {
+                    version: Treasury::VERSION,
+                    bump: legacy.bump,
+                    protocol_fees_accrued: legacy
+                        .protocol_fees_accrued
+                        .saturating_sub(slashed_bonds_accrued),
+                    creator_fees_accrued: legacy.creator_fees_accrued,
+                    total_protocol_fees: legacy.total_protocol_fees,
+                    total_slashed_bonds: legacy.total_slashed_bonds,
+                    slashed_bonds_accrued,
+                },
+            ))
+        }
+        _ => err!(ErrorCode::AccountAlreadyMigrated),
+    }
+}
+
+/// Resize `account` to `len` and write `value` over it. The payer tops up rent
+/// so the account stays exempt while still holding `reserved_lamports`
+pub fn rewrite_account<'info, T: AccountSerialize>(
+    account: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    value: &T,
+    len: usize,
+    reserved_lamports: u64,
+) -> Result<()> {
+    let required = Rent::get()?.minimum_balance(len) + reserved_lamports;
+    let lamports = account.lamports();
+    if lamports < required {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            required - lamports,
+        )?;
+    }
+
+    account.realloc(len, false)?;
+    let mut data = account.try_borrow_mut_data()?;
+    value.try_serialize(&mut &mut data[..])?;
+
+    Ok(())
+}
+
+// ============================================================================
+// LEGACY LAYOUTS
+// ============================================================================
+
+/// PredictionMarket before the version byte
+#[derive(AnchorDeserialize)]
+pub struct PredictionMarketV0 {
+    pub bump: u8,
+    pub encrypted_vote_state: [[u8; 32]; 17],
+    pub nonce: u128,
+    pub market_id: u64,
+    pub creator: Pubkey,
+    pub category: MarketCategory,
+    pub status: MarketStatus,
+    pub created_at: i64,
+    pub voting_ends_at: i64,
+    pub resolution_timestamp: i64,
+    pub oracle_type: OracleType,
+    pub oracle_pubkey: Pubkey,
+    pub resolved_outcome: Option<bool>,
+    pub time_decay_enabled: bool,
+    pub reveal_after_resolution: bool,
+    pub circuit_version: u8,
+    pub key_epoch: u32,
+    pub aggregated_vote_root: [u8; 32],
+    pub aggregated_vote_count: u32,
+    pub protocol_fee_bps: u16,
+    pub creator_fee_bps: u16,
+    pub fee_amount: u64,
+    pub creator_fees_owed: u64,
+    pub creation_bond: u64,
+    pub total_stake: u64,
+    pub yes_stake: u64,
+    pub no_stake: u64,
+    pub participant_count: u32,
+    pub odds_yes: u8,
+    pub odds_no: u8,
+    pub herding_index: u8,
+    pub odds_updated_at: i64,
+    pub metadata_hash: [u8; 32],
+    pub metadata_uri: String,
+}
+
+impl From<PredictionMarketV0> for PredictionMarketV1 {
+    fn from(legacy: PredictionMarketV0) -> Self {
+        Self {
+            version: 1,
+            bump: legacy.bump,
+            encrypted_vote_state: legacy.encrypted_vote_state,
+            nonce: legacy.nonce,
+            market_id: legacy.market_id,
+            creator: legacy.creator,
+            category: legacy.category,
+            status: legacy.status,
+            created_at: legacy.created_at,
+            voting_ends_at: legacy.voting_ends_at,
+            resolution_timestamp: legacy.resolution_timestamp,
+            oracle_type: legacy.oracle_type,
+            oracle_pubkey: legacy.oracle_pubkey,
+            resolved_outcome: legacy.resolved_outcome,
+            time_decay_enabled: legacy.time_decay_enabled,
+            reveal_after_resolution: legacy.reveal_after_resolution,
+            circuit_version: legacy.circuit_version,
+            key_epoch: legacy.key_epoch,
+            aggregated_vote_root: legacy.aggregated_vote_root,
+            aggregated_vote_count: legacy.aggregated_vote_count,
+            protocol_fee_bps: legacy.protocol_fee_bps,
+            creator_fee_bps: legacy.creator_fee_bps,
+            fee_amount: legacy.fee_amount,
+            creator_fees_owed: legacy.creator_fees_owed,
+            creation_bond: legacy.creation_bond,
+            total_stake: legacy.total_stake,
+            yes_stake: legacy.yes_stake,
+            no_stake: legacy.no_stake,
+            participant_count: legacy.participant_count,
+            odds_yes: legacy.odds_yes,
+            odds_no: legacy.odds_no,
+            herding_index: legacy.herding_index,
+            odds_updated_at: legacy.odds_updated_at,
+            metadata_hash: legacy.metadata_hash,
+            metadata_uri: legacy.metadata_uri,
+        }
+    }
+}
+
+/// PredictionMarket before privacy modes, conditional markets and locked final odds
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct PredictionMarketV1 {
+    pub version: u8,
+    pub bump: u8,
+    pub encrypted_vote_state: [[u8; 32]; 17],
+    pub nonce: u128,
+    pub market_id: u64,
+    pub creator: Pubkey,
+    pub category: MarketCategory,
+    pub status: MarketStatus,
+    pub created_at: i64,
+    pub voting_ends_at: i64,
+    pub resolution_timestamp: i64,
+    pub oracle_type: OracleType,
+    pub oracle_pubkey: Pubkey,
+    pub resolved_outcome: Option<bool>,
+    pub time_decay_enabled: bool,
+    pub reveal_after_resolution: bool,
+    pub circuit_version: u8,
+    pub key_epoch: u32,
+    pub aggregated_vote_root: [u8; 32],
+    pub aggregated_vote_count: u32,
+    pub protocol_fee_bps: u16,
+    pub creator_fee_bps: u16,
+    pub fee_amount: u64,
+    pub creator_fees_owed: u64,
+    pub creation_bond: u64,
+    pub total_stake: u64,
+    pub yes_stake: u64,
+    pub no_stake: u64,
+    pub participant_count: u32,
+    pub odds_yes: u8,
+    pub odds_no: u8,
+    pub herding_index: u8,
+    pub odds_updated_at: i64,
+    pub metadata_hash: [u8; 32],
+    #[max_len(MAX_METADATA_URI_LEN)]
+    pub metadata_uri: String,
+}
+
+impl From<PredictionMarketV1> for PredictionMarket {
+    fn from(legacy: PredictionMarketV1) -> Self {
+        Self {
+            version: PredictionMarket::VERSION,
+            bump: legacy.bump,
+            encrypted_vote_state: legacy.encrypted_vote_state,
+            nonce: legacy.nonce,
+            market_id: legacy.market_id,
+            creator: legacy.creator,
+            category: legacy.category,
+            status: legacy.status,
+            created_at: legacy.created_at,
+            voting_ends_at: legacy.voting_ends_at,
+            resolution_timestamp: legacy.resolution_timestamp,
+            oracle_type: legacy.oracle_type,
+            oracle_pubkey: legacy.oracle_pubkey,
+            resolved_outcome: legacy.resolved_outcome,
+            time_decay_enabled: legacy.time_decay_enabled,
+            reveal_after_resolution: legacy.reveal_after_resolution,
+            // Every market before version 2 was encrypted and standalone
+            privacy_mode: PrivacyMode::Private,
+            parent_market: None,
+            parent_outcome: false,
+            circuit_version: legacy.circuit_version,
+            key_epoch: legacy.key_epoch,
+            aggregated_vote_root: legacy.aggregated_vote_root,
+            aggregated_vote_count: legacy.aggregated_vote_count,
+            protocol_fee_bps: legacy.protocol_fee_bps,
+            creator_fee_bps: legacy.creator_fee_bps,
+            fee_amount: legacy.fee_amount,
+            creator_fees_owed: legacy.creator_fees_owed,
+            creation_bond: legacy.creation_bond,
+            total_stake: legacy.total_stake,
+            yes_stake: legacy.yes_stake,
+            no_stake: legacy.no_stake,
+            participant_count: legacy.participant_count,
+            odds_yes: legacy.odds_yes,
+            odds_no: legacy.odds_no,
+            herding_index: legacy.herding_index,
+            odds_updated_at: legacy.odds_updated_at,
+            final_odds_locked: false,
+            metadata_hash: legacy.metadata_hash,
+            metadata_uri: legacy.metadata_uri,
+        }
+    }
+}
+
+/// UserPosition before the version byte
+#[derive(AnchorDeserialize)]
+pub struct UserPositionV0 {
+    pub user: Pubkey,
+    pub market: Pubkey,
+    pub stake_amount: u64,
+    pub timestamp: i64,
+    pub is_claimed: bool,
+    pub is_validated: bool,
+    pub encrypted_vote_data: [[u8; 32]; 7],
+    pub vote_pubkey: [u8; 32],
+    pub vote_nonce: u128,
+    pub encrypted_payout: [u8; 32],
+    pub payout_nonce: u128,
+    pub payout_amount: u64,
+    pub bump: u8,
+    pub is_payout_calculated: bool,
+    pub reputation_recorded: bool,
+    pub key_epoch: u32,
+    pub disclosure_count: u32,
+    pub is_revealed: bool,
+    pub revealed_vote_choice: u8,
+    pub revealed_probability: u8,
+    pub revealed_conviction: u16,
+    pub leaf_index: u32,
+    pub vote_root: [u8; 32],
+    pub is_aggregated: bool,
+}
+
+impl From<UserPositionV0> for UserPositionV1 {
+    fn from(legacy: UserPositionV0) -> Self {
+        Self {
+            version: 1,
+            user: legacy.user,
+            market: legacy.market,
+            stake_amount: legacy.stake_amount,
+            timestamp: legacy.timestamp,
+            is_claimed: legacy.is_claimed,
+            is_validated: legacy.is_validated,
+            encrypted_vote_data: legacy.encrypted_vote_data,
+            vote_pubkey: legacy.vote_pubkey,
+            vote_nonce: legacy.vote_nonce,
+            encrypted_payout: legacy.encrypted_payout,
+            payout_nonce: legacy.payout_nonce,
+            payout_amount: legacy.payout_amount,
+            bump: legacy.bump,
+            is_payout_calculated: legacy.is_payout_calculated,
+            reputation_recorded: legacy.reputation_recorded,
+            key_epoch: legacy.key_epoch,
+            disclosure_count: legacy.disclosure_count,
+            is_revealed: legacy.is_revealed,
+            revealed_vote_choice: legacy.revealed_vote_choice,
+            revealed_probability: legacy.revealed_probability,
+            revealed_conviction: legacy.revealed_conviction,
+            leaf_index: legacy.leaf_index,
+            vote_root: legacy.vote_root,
+            is_aggregated: legacy.is_aggregated,
+        }
+    }
+}
+
+/// UserPosition before public votes
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct UserPositionV1 {
+    pub version: u8,
+    pub user: Pubkey,
+    pub market: Pubkey,
+    pub stake_amount: u64,
+    pub timestamp: i64,
+    pub is_claimed: bool,
+    pub is_validated: bool,
+    pub encrypted_vote_data: [[u8; 32]; 7],
+    pub vote_pubkey: [u8; 32],
+    pub vote_nonce: u128,
+    pub encrypted_payout: [u8; 32],
+    pub payout_nonce: u128,
+    pub payout_amount: u64,
+    pub bump: u8,
+    pub is_payout_calculated: bool,
+    pub reputation_recorded: bool,
+    pub key_epoch: u32,
+    pub disclosure_count: u32,
+    pub is_revealed: bool,
+    pub revealed_vote_choice: u8,
+    pub revealed_probability: u8,
+    pub revealed_conviction: u16,
+    pub leaf_index: u32,
+    pub vote_root: [u8; 32],
+    pub is_aggregated: bool,
+}
+
+impl From<UserPositionV1> for UserPosition {
+    fn from(legacy: UserPositionV1) -> Self {
+        Self {
+            version: UserPosition::VERSION,
+            user: legacy.user,
+            market: legacy.market,
+            stake_amount: legacy.stake_amount,
+            timestamp: legacy.timestamp,
+            is_claimed: legacy.is_claimed,
+            is_validated: legacy.is_validated,
+            encrypted_vote_data: legacy.encrypted_vote_data,
+            vote_pubkey: legacy.vote_pubkey,
+            vote_nonce: legacy.vote_nonce,
+            encrypted_payout: legacy.encrypted_payout,
+            payout_nonce: legacy.payout_nonce,
+            payout_amount: legacy.payout_amount,
+            bump: legacy.bump,
+            is_payout_calculated: legacy.is_payout_calculated,
+            reputation_recorded: legacy.reputation_recorded,
+            key_epoch: legacy.key_epoch,
+            disclosure_count: legacy.disclosure_count,
+            is_revealed: legacy.is_revealed,
+            revealed_vote_choice: legacy.revealed_vote_choice,
+            revealed_probability: legacy.revealed_probability,
+            revealed_conviction: legacy.revealed_conviction,
+            leaf_index: legacy.leaf_index,
+            vote_root: legacy.vote_root,
+            is_aggregated: legacy.is_aggregated,
+            // Positions before version 2 all hold encrypted votes
+            is_public: false,
+        }
+    }
+}
+
+/// UserProfile before the version byte
+#[derive(AnchorDeserialize)]
+pub struct UserProfileV0 {
+    pub user: Pubkey,
+    pub total_markets_participated: u32,
+    pub correct_predictions: u32,
+    pub total_winnings: u64,
+    pub reputation_score: u16,
+    pub streak_current: u16,
+    pub streak_best: u16,
+    pub last_activity: i64,
+    pub achievements: [u8; 32],
+    pub preferred_categories: u16,
+    pub bump: u8,
+    pub encrypted_reputation: [[u8; 32]; 5],
+    pub reputation_nonce: u128,
+    pub has_private_reputation: bool,
+    pub leaderboard_season: u32,
+}
+
+impl From<UserProfileV0> for UserProfileV1 {
+    fn from(legacy: UserProfileV0) -> Self {
+        Self {
+            version: 1,
+            user: legacy.user,
+            total_markets_participated: legacy.total_markets_participated,
+            correct_predictions: legacy.correct_predictions,
+            total_winnings: legacy.total_winnings,
+            reputation_score: legacy.reputation_score,
+            streak_current: legacy.streak_current,
+            streak_best: legacy.streak_best,
+            last_activity: legacy.last_activity,
+            achievements: legacy.achievements,
+            preferred_categories: legacy.preferred_categories,
+            bump: legacy.bump,
+            encrypted_reputation: legacy.encrypted_reputation,
+            reputation_nonce: legacy.reputation_nonce,
+            has_private_reputation: legacy.has_private_reputation,
+            leaderboard_season: legacy.leaderboard_season,
+        }
+    }
+}
+
+/// UserProfile before parlays
+#[derive(AnchorDeserialize, InitSpace)]
+pub struct UserProfileV1 {
+    pub version: u8,
+    pub user: Pubkey,
+    pub total_markets_participated: u32,
+    pub correct_predictions: u32,
+    pub total_winnings: u64,
+    pub reputation_score: u16,
+    pub streak_current: u16,
+    pub streak_best: u16,
+    pub last_activity: i64,
+    pub achievements: [u8; 32],
+    pub preferred_categories: u16,
+    pub bump: u8,
+    pub encrypted_reputation: [[u8; 32]; 5],
+    pub reputation_nonce: u128,
+    pub has_private_reputation: bool,
+    pub leaderboard_season: u32,
+}
+
+impl From<UserProfileV1> for UserProfile {
+    fn from(legacy: UserProfileV1) -> Self {
+        Self {
+            version: UserProfile::VERSION,
+            user: legacy.user,
+            total_markets_participated: legacy.total_markets_participated,
+            correct_predictions: legacy.correct_predictions,
+            total_winnings: legacy.total_winnings,
+            reputation_score: legacy.reputation_score,
+            streak_current: legacy.streak_current,
+            streak_best: legacy.streak_best,
+            last_activity: legacy.last_activity,
+            achievements: legacy.achievements,
+            preferred_categories: legacy.preferred_categories,
+            bump: legacy.bump,
+            encrypted_reputation: legacy.encrypted_reputation,
+            reputation_nonce: legacy.reputation_nonce,
+            has_private_reputation: legacy.has_private_reputation,
+            leaderboard_season: legacy.leaderboard_season,
+            // No parlays could be opened before version 2
+            parlay_count: 0,
+        }
+    }
+}
+
+/// Treasury before slashed bonds were counted apart from protocol fees
+#[derive(AnchorDeserialize)]
+pub struct TreasuryV1 {
+    pub version: u8,
+    pub bump: u8,
+    pub protocol_fees_accrued: u64,
+    pub creator_fees_accrued: u64,
+    pub total_protocol_fees: u64,
+    pub total_slashed_bonds: u64,
+}
@@ -4,7 +4,7 @@ use std::collections::HashMap;
 // Oracle resolution system for prediction markets
 // Inspired by UMA DVM and Chainlink for different data types
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, InitSpace)]
 pub enum OracleType {
     UmaOptimistic,    // UMA's optimistic oracle for subjective outcomes
     ChainlinkPrice,   // Chainlink for objective price data
@@ -0,0 +1,173 @@
+use anchor_lang::prelude::*;
+use arcium_anchor::prelude::Argument;
+
+use crate::{is_current, ErrorCode, MarketStatus, PredictionMarket};
+
+// A parlay bundles picks on up to MAX_PARLAY_LEGS markets into a single bet. The
+// picks are encrypted to the owner like a vote; the markets and the stake are
+// public. Parlays don't join the markets' pools and don't move their stakes or
+// odds. Once every leg's market has resolved or been cancelled, settle_parlay
+// prices the parlay in calculate_parlay_payout_v1 from each market's final odds,
+// locked the first time they're revealed after it resolved. It pays the stake
+// times every leg's decimal odds if all the picks were right and nothing
+// otherwise, up to MAX_PARLAY_MULTIPLIER times the stake. A cancelled market is a void leg and
+// drops out.
+//
+// Unlike market stakes, parlay stakes are real lamports. They go into the
+// ParlayPool, which anyone can fund, and which pays the parlays out. A new
+// parlay reserves its largest possible payout in the pool, so it can only be
+// placed while the pool can cover it; once it settles, the reservation shrinks
+// to the actual payout, which is revealed so claim_parlay can pay it.
+
+/// Most markets a parlay can combine (matches the circuit)
+pub const MAX_PARLAY_LEGS: usize = 4;
+
+/// Leg outcome of a cancelled market or an unused slot (matches the circuit)
+pub const PARLAY_LEG_VOID: u8 = 2;
+
+/// Most a parlay pays, in multiples of its stake (matches the circuit)
+pub const MAX_PARLAY_MULTIPLIER: u64 = 100;
+
+/// Lamports that pay out parlays; seeds ["parlay_pool"]
+#[account]
+#[derive(InitSpace)]
+pub struct ParlayPool {
+    pub version: u8,
+    pub bump: u8,
+    pub reserved: u64, // Owed to, or set aside for, parlays not yet claimed
+}
+
+impl ParlayPool {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+    pub const VERSION: u8 = 1;
+
+    /// Lamports above rent that no parlay has a claim on
+    pub fn available(pool: &Account<ParlayPool>) -> Result<u64> {
+        let rent_floor = Rent::get()?.minimum_balance(Self::LEN);
+        Ok(pool
+            .to_account_info()
+            .lamports()
+            .saturating_sub(rent_floor)
+            .saturating_sub(pool.reserved))
+    }
+
+    /// Move `amount` lamports out of the pool to `to`
+    pub fn pay<'info>(
+        pool: &Account<'info, ParlayPool>,
+        to: &AccountInfo<'info>,
+        amount: u64,
+    ) -> Result<()> {
+        **pool.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **to.try_borrow_mut_lamports()? += amount;
+        Ok(())
+    }
+}
+
+/// Combined bet on several markets; seeds ["parlay", user, parlay_id]
+#[account]
+#[derive(InitSpace)]
+pub struct Parlay {
+    pub version: u8,
+    pub bump: u8,
+    pub user: Pubkey,
+    pub parlay_id: u32, // The owner's UserProfile.parlay_count when it was placed
+    pub markets: [Pubkey; MAX_PARLAY_LEGS], // Legs in order; unused slots are default
+    pub leg_count: u8,
+    pub stake_amount: u64,
+    pub created_at: i64,
+    // Encrypted ParlayChoices: one pick per slot (0 = No, 1 = Yes)
+    pub encrypted_choices: [[u8; 32]; MAX_PARLAY_LEGS],
+    pub choices_pubkey: [u8; 32],
+    pub choices_nonce: u128,
+    pub key_epoch: u32, // MXE key epoch encrypted_choices were encrypted under
+    pub is_settling: bool, // A settlement is queued and its callback hasn't landed yet
+    pub is_settled: bool,
+    pub payout_amount: u64, // Revealed when the parlay settles
+    pub is_claimed: bool,
+}
+
+impl Parlay {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+    pub const VERSION: u8 = 1;
+
+    /// What the pool sets aside for an unsettled parlay: its largest possible payout
+    pub fn max_payout(&self) -> u64 {
+        self.stake_amount * MAX_PARLAY_MULTIPLIER
+    }
+
+    /// Check the leg markets for a new parlay: 1 to MAX_PARLAY_LEGS distinct
+    /// markets still taking votes. Returns them in slot order
+    pub fn open_legs<'info>(
+        legs: &'info [AccountInfo<'info>],
+        now: i64,
+    ) -> Result<[Pubkey; MAX_PARLAY_LEGS]> {
+        require!(
+            !legs.is_empty() && legs.len() <= MAX_PARLAY_LEGS,
+            ErrorCode::InvalidParlayLegs
+        );
+
+        let mut markets = [Pubkey::default(); MAX_PARLAY_LEGS];
+        for (slot, info) in legs.iter().enumerate() {
+            let market = Account::<PredictionMarket>::try_from(info)?;
+            require!(is_current(&market), ErrorCode::AccountNotMigrated);
+            require!(
+                market.status == MarketStatus::Active && now < market.voting_ends_at,
+                ErrorCode::MarketNotActive
+            );
+            require!(
+                !markets[..slot].contains(&info.key()),
+                ErrorCode::InvalidParlayLegs
+            );
+            markets[slot] = info.key();
+        }
+        Ok(markets)
+    }
+
+    /// calculate_parlay_payout_v1 arguments: the encrypted picks, the stake, then
+    /// each slot's outcome and revealed odds. `legs` are the leg markets in order
+    pub fn payout_arguments<'info>(
+        &self,
+        legs: &'info [AccountInfo<'info>],
+    ) -> Result<Vec<Argument>> {
+        require!(
+            legs.len() == self.leg_count as usize,
+            ErrorCode::InvalidParlayLegs
+        );
+
+        let mut args = vec![
+            Argument::ArcisPubkey(self.choices_pubkey),
+            Argument::PlaintextU128(self.choices_nonce),
+        ];
+        args.extend(
+            self.encrypted_choices
+                .iter()
+                .map(|choice| Argument::EncryptedU8(*choice)),
+        );
+        args.push(Argument::PlaintextU64(self.stake_amount));
+
+        for slot in 0..MAX_PARLAY_LEGS {
+            let (outcome, odds_yes, odds_no) = match legs.get(slot) {
+                Some(info) => {
+                    require_keys_eq!(info.key(), self.markets[slot], ErrorCode::InvalidParlayLegs);
+                    let market = Account::<PredictionMarket>::try_from(info)?;
+                    require!(is_current(&market), ErrorCode::AccountNotMigrated);
+                    let outcome = match (&market.status, market.resolved_outcome) {
+                        (MarketStatus::Resolved | MarketStatus::Settled, Some(outcome)) => {
+                            require!(market.final_odds_locked, ErrorCode::ParlayOddsStale);
+                            outcome as u8
+                        }
+                        (MarketStatus::Cancelled, _) => PARLAY_LEG_VOID,
+                        _ => return err!(ErrorCode::ParlayLegsUnresolved),
+                    };
+                    (outcome, market.odds_yes, market.odds_no)
+                }
+                None => (PARLAY_LEG_VOID, 0, 0),
+            };
+            args.push(Argument::PlaintextU8(outcome));
+            args.push(Argument::PlaintextU8(odds_yes));
+            args.push(Argument::PlaintextU8(odds_no));
+        }
+
+        Ok(args)
+    }
+}
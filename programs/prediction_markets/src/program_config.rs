@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+
+use crate::{MarketCategory, MARKET_CATEGORY_COUNT};
+
+// Program-wide settings and roles. The admin owns fee rates, comp-def
+// initialization and the guardian appointment, and hands the role over in two
+// steps (propose_admin / accept_admin) so a typo can't brick the program. The
+// guardian is an incident-response key: it can pause market creation, voting
+// and claims independently, but only the admin can lift a pause.
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProgramConfig {
+    pub version: u8,
+    pub bump: u8,
+    pub admin: Pubkey,
+    pub protocol_fee_bps: u16,
+    pub max_creator_fee_bps: u16,
+    pub pending_admin: Option<Pubkey>, // Proposed by the admin, set once it accepts
+    pub guardian: Pubkey,
+    pub markets_paused: bool,          // Blocks create_market
+    pub votes_paused: bool,            // Blocks submit_encrypted_vote
+    pub claims_paused: bool,           // Blocks payouts, claims and fee withdrawals
+    pub creation_bonds: [u64; MARKET_CATEGORY_COUNT], // Lamports, by MarketCategory::slot
+}
+
+impl ProgramConfig {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+    pub const VERSION: u8 = 1;
+
+    pub fn is_admin(&self, key: &Pubkey) -> bool {
+        self.admin == *key
+    }
+
+    /// Bond a creator posts to open a market in `category`
+    pub fn creation_bond(&self, category: MarketCategory) -> u64 {
+        self.creation_bonds[category.slot()]
+    }
+
+    /// Admin or guardian
+    pub fn can_pause(&self, key: &Pubkey) -> bool {
+        self.admin == *key || self.guardian == *key
+    }
+}
@@ -0,0 +1,247 @@
+use anchor_lang::prelude::*;
+use arcium_anchor::prelude::Argument;
+
+use crate::tally::{
+    aggregate_market_votes, calculate_market_odds, calculate_payout_after_fees, update_reputation,
+    MarketOdds, MarketVotingState, PayoutData, ReputationState, VoteData,
+};
+use crate::{ErrorCode, PredictionMarket, UserPosition, UserProfile};
+
+// Public markets run without the MXE. A vote is stored in plaintext on its
+// position (the `revealed_*` fields, with `is_revealed` set from the start) and
+// folded straight into the market's PublicTally. Tallying, odds, payouts and
+// reputation go through tally.rs, the plaintext copy of the circuits' math, so a
+// public market pays out exactly what an encrypted one would for the same votes. The tally is created by the market's first vote:
+//
+//   ["public_tally", market]
+//
+// Hybrid markets take both kinds of vote. Public ones land here and in the
+// market's plaintext yes_stake/no_stake, encrypted ones in encrypted_vote_state.
+// Odds and payouts need both pools, so they go through the MXE: the *_hybrid
+// circuits take the tally as plaintext scalars (`pool_arguments`) and add it to
+// the sealed state before doing the usual math.
+
+pub const MAX_CONVICTION_SCORE: u16 = 1000;
+
+/// Most a market can take in stakes, in total. The tally multiplies stake by a
+/// time weight of up to 200 and then by 100 for the odds percentage, and by a
+/// conviction of up to MAX_CONVICTION_SCORE, so keeping every market's total
+/// under u64::MAX / 20_000 keeps each of those products and sums inside a u64.
+pub const MAX_STAKE: u64 = u64::MAX / 20_000;
+
+/// Plaintext MarketVotingState of a public market, or of a hybrid market's public votes
+#[account]
+#[derive(InitSpace)]
+pub struct PublicTally {
+    pub version: u8,
+    pub bump: u8,
+    pub market: Pubkey,
+    pub total_yes_votes: u32,
+    pub total_no_votes: u32,
+    pub total_skip_votes: u32,
+    pub total_yes_stake: u64,
+    pub total_no_stake: u64,
+    pub total_participants: u32,
+    pub weighted_probability_sum: u64,
+    pub conviction_weighted_yes: u64,
+    pub conviction_weighted_no: u64,
+    pub last_updated: u64,
+    pub brier_score_sum_yes: u64,
+    pub brier_score_sum_no: u64,
+    pub time_weighted_yes_stake: u64,
+    pub time_weighted_no_stake: u64,
+    pub herding_aligned_votes: u32,
+    pub herding_counted_votes: u32,
+}
+
+impl PublicTally {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+    pub const VERSION: u8 = 1;
+
+    /// Stamp a freshly created tally; no-op once it belongs to the market
+    pub fn open(&mut self, market: Pubkey, bump: u8) {
+        if self.market == Pubkey::default() {
+            self.version = Self::VERSION;
+            self.bump = bump;
+            self.market = market;
+        }
+    }
+
+    /// The tally as the hybrid circuits take it, one scalar per PublicPool field
+    pub fn pool_arguments(&self) -> Vec<Argument> {
+        vec![
+            Argument::PlaintextU64(self.total_yes_stake),
+            Argument::PlaintextU64(self.total_no_stake),
+            Argument::PlaintextU32(self.total_participants),
+            Argument::PlaintextU64(self.conviction_weighted_yes),
+            Argument::PlaintextU64(self.conviction_weighted_no),
+            Argument::PlaintextU64(self.brier_score_sum_yes),
+            Argument::PlaintextU64(self.brier_score_sum_no),
+            Argument::PlaintextU64(self.time_weighted_yes_stake),
+            Argument::PlaintextU64(self.time_weighted_no_stake),
+            Argument::PlaintextU32(self.herding_aligned_votes),
+            Argument::PlaintextU32(self.herding_counted_votes),
+        ]
+    }
+
+    fn state(&self, market_id: u64) -> MarketVotingState {
+        MarketVotingState {
+            market_id,
+            total_yes_votes: self.total_yes_votes,
+            total_no_votes: self.total_no_votes,
+            total_skip_votes: self.total_skip_votes,
+            total_yes_stake: self.total_yes_stake,
+            total_no_stake: self.total_no_stake,
+            total_participants: self.total_participants,
+            weighted_probability_sum: self.weighted_probability_sum,
+            conviction_weighted_yes: self.conviction_weighted_yes,
+            conviction_weighted_no: self.conviction_weighted_no,
+            last_updated: self.last_updated,
+            brier_score_sum_yes: self.brier_score_sum_yes,
+            brier_score_sum_no: self.brier_score_sum_no,
+            time_weighted_yes_stake: self.time_weighted_yes_stake,
+            time_weighted_no_stake: self.time_weighted_no_stake,
+            herding_aligned_votes: self.herding_aligned_votes,
+            herding_counted_votes: self.herding_counted_votes,
+        }
+    }
+
+    fn set_state(&mut self, state: &MarketVotingState) {
+        self.total_yes_votes = state.total_yes_votes;
+        self.total_no_votes = state.total_no_votes;
+        self.total_skip_votes = state.total_skip_votes;
+        self.total_yes_stake = state.total_yes_stake;
+        self.total_no_stake = state.total_no_stake;
+        self.total_participants = state.total_participants;
+        self.weighted_probability_sum = state.weighted_probability_sum;
+        self.conviction_weighted_yes = state.conviction_weighted_yes;
+        self.conviction_weighted_no = state.conviction_weighted_no;
+        self.last_updated = state.last_updated;
+        self.brier_score_sum_yes = state.brier_score_sum_yes;
+        self.brier_score_sum_no = state.brier_score_sum_no;
+        self.time_weighted_yes_stake = state.time_weighted_yes_stake;
+        self.time_weighted_no_stake = state.time_weighted_no_stake;
+        self.herding_aligned_votes = state.herding_aligned_votes;
+        self.herding_counted_votes = state.herding_counted_votes;
+    }
+
+    /// Fold a public position's vote in, as aggregate_market_votes does
+    pub fn record(&mut self, market: &PredictionMarket, position: &UserPosition, now: i64) {
+        let state = aggregate_market_votes(
+            &public_vote(market, position),
+            now as u64,
+            market.created_at as u64,
+            market.voting_ends_at as u64,
+            market.time_decay_enabled,
+            &self.state(market.market_id),
+        );
+        self.set_state(&state);
+    }
+
+    /// Current odds, as calculate_market_odds reveals them
+    pub fn odds(&self, market_id: u64) -> MarketOdds {
+        calculate_market_odds(&self.state(market_id))
+    }
+
+    /// A public position's payout after the fees charged at resolution, with the
+    /// same bonus pools calculate_user_payout_v2 passes to the circuit
+    pub fn payout(&self, market: &PredictionMarket, position: &UserPosition) -> u64 {
+        let payout_data = PayoutData {
+            user: position.user.to_bytes(),
+            market_id: market.market_id,
+            user_stake: position.stake_amount,
+            user_vote: position.revealed_vote_choice,
+            user_probability: position.revealed_probability,
+            user_conviction: position.revealed_conviction,
+            user_timestamp: position.timestamp as u64,
+            market_outcome: market_outcome(market),
+            accuracy_bonus: market.total_stake / 100,
+            conviction_bonus: market.total_stake / 200,
+        };
+        calculate_payout_after_fees(
+            &payout_data,
+            market.fee_amount,
+            market.created_at as u64,
+            market.voting_ends_at as u64,
+            market.time_decay_enabled,
+            &self.state(market.market_id),
+        )
+    }
+}
+
+impl UserProfile {
+    /// Fold a settled public position into the plaintext reputation stats, as
+    /// update_reputation does for the sealed ones; returns whether the call was right
+    pub fn record_public_outcome(
+        &mut self,
+        market: &PredictionMarket,
+        position: &UserPosition,
+        payout: u64,
+    ) -> bool {
+        let outcome = market_outcome(market);
+        let reputation = update_reputation(
+            &public_vote(market, position),
+            payout,
+            outcome,
+            Some(&ReputationState {
+                correct_predictions: self.correct_predictions,
+                total_winnings: self.total_winnings,
+                streak_current: self.streak_current,
+                streak_best: self.streak_best,
+                reputation_score: self.reputation_score,
+            }),
+        );
+        self.correct_predictions = reputation.correct_predictions;
+        self.total_winnings = reputation.total_winnings;
+        self.streak_current = reputation.streak_current;
+        self.streak_best = reputation.streak_best;
+        self.reputation_score = reputation.reputation_score;
+
+        position.revealed_vote_choice == outcome
+    }
+}
+
+/// Check a public vote the way submit_private_vote_v1 checks an encrypted one
+pub fn validate_public_vote(
+    vote_choice: u8,
+    stake_amount: u64,
+    predicted_probability: u8,
+    conviction_score: u16,
+) -> Result<()> {
+    require!(stake_amount > 0 && stake_amount <= MAX_STAKE, ErrorCode::InvalidStakeAmount);
+    require!(
+        vote_choice <= 2
+            && predicted_probability <= 100
+            && conviction_score > 0
+            && conviction_score <= MAX_CONVICTION_SCORE,
+        ErrorCode::InvalidVoteData
+    );
+
+    Ok(())
+}
+
+/// `total` plus `stake_amount`, as long as the market stays within MAX_STAKE
+pub fn add_stake(total: u64, stake_amount: u64) -> Result<u64> {
+    total
+        .checked_add(stake_amount)
+        .filter(|sum| *sum <= MAX_STAKE)
+        .ok_or_else(|| error!(ErrorCode::StakeLimitExceeded))
+}
+
+fn public_vote(market: &PredictionMarket, position: &UserPosition) -> VoteData {
+    VoteData {
+        voter: position.user.to_bytes(),
+        market_id: market.market_id,
+        vote_choice: position.revealed_vote_choice,
+        stake_amount: position.stake_amount,
+        predicted_probability: position.revealed_probability,
+        conviction_score: position.revealed_conviction,
+        timestamp: position.timestamp as u64,
+        nonce: 0,
+    }
+}
+
+fn market_outcome(market: &PredictionMarket) -> u8 {
+    market.resolved_outcome.map_or(0, u8::from)
+}
+
@@ -0,0 +1,343 @@
+// Plaintext twin of the circuits' tally, odds, payout and reputation math, for
+// the markets that settle without the MXE (see public_markets.rs). Each function
+// mirrors its `encrypted-ixs` circuit line for line with the `Enc<..>` wrappers
+// stripped, the same way circuits-reference does; the market_flow tests check
+// the two agree. Keep all three in sync.
+
+// Arcis has no abs_diff/saturating_sub; keep the circuits' exact shape so the
+// two can be diffed side by side
+#![allow(clippy::implicit_saturating_sub, clippy::manual_abs_diff)]
+
+/// Starting reputation for a profile without sealed state yet
+pub const BASE_REPUTATION: u16 = 1000;
+pub const MAX_REPUTATION: u16 = 10000;
+
+/// Minimum number of counted votes before a herding index is reported
+pub const MIN_HERDING_SAMPLE: u32 = 10;
+
+/// Maximum Brier score: a 100% forecast on the realised outcome
+pub const MAX_BRIER_SCORE: u64 = 10000;
+
+/// A vote as the circuits see it once decrypted
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VoteData {
+    pub voter: [u8; 32],
+    pub market_id: u64,
+    pub vote_choice: u8, // 0 = No, 1 = Yes, 2 = Skip
+    pub stake_amount: u64,
+    pub predicted_probability: u8, // 0-100
+    pub conviction_score: u16,
+    pub timestamp: u64,
+    pub nonce: u128,
+}
+
+/// Market voting state, as sealed in `encrypted_vote_state` or kept in a PublicTally
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MarketVotingState {
+    pub market_id: u64,
+    pub total_yes_votes: u32,
+    pub total_no_votes: u32,
+    pub total_skip_votes: u32,
+    pub total_yes_stake: u64,
+    pub total_no_stake: u64,
+    pub total_participants: u32,
+    pub weighted_probability_sum: u64, // Sum of stake * probability
+    pub conviction_weighted_yes: u64,
+    pub conviction_weighted_no: u64,
+    pub last_updated: u64,
+    pub brier_score_sum_yes: u64, // Sum of Brier scores if the market resolves Yes
+    pub brier_score_sum_no: u64,  // Sum of Brier scores if the market resolves No
+    pub time_weighted_yes_stake: u64, // Sum of stake * time weight (100-200)
+    pub time_weighted_no_stake: u64,
+    pub herding_aligned_votes: u32, // Votes that sided with the prevailing odds
+    pub herding_counted_votes: u32, // Votes cast while the odds favoured a side
+}
+
+/// Payout calculation data
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PayoutData {
+    pub user: [u8; 32],
+    pub market_id: u64,
+    pub user_stake: u64,
+    pub user_vote: u8,
+    pub user_probability: u8,
+    pub user_conviction: u16,
+    pub user_timestamp: u64,
+    pub market_outcome: u8,    // 0 = No, 1 = Yes
+    pub accuracy_bonus: u64,   // Accuracy pool, shared by all Yes/No voters
+    pub conviction_bonus: u64, // Conviction pool, shared by winners
+}
+
+/// Reputation stats, as sealed on UserProfile or kept there in plaintext
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReputationState {
+    pub correct_predictions: u32,
+    pub total_winnings: u64,
+    pub streak_current: u16,
+    pub streak_best: u16,
+    pub reputation_score: u16,
+}
+
+/// Revealed odds: (yes %, no %, participants, high confidence, herding index)
+pub type MarketOdds = (u8, u8, u32, bool, u8);
+
+/// Time weight in percent: 100 without decay, otherwise from 200 for a vote cast
+/// at market creation down to 100 for one cast at the close
+pub fn time_weight(timestamp: u64, created_at: u64, voting_ends_at: u64, time_decay: bool) -> u64 {
+    let duration = if voting_ends_at > created_at { voting_ends_at - created_at } else { 1 };
+    let remaining = if voting_ends_at > timestamp { voting_ends_at - timestamp } else { 0 };
+    let remaining = remaining.min(duration);
+    if time_decay {
+        100 + (remaining * 100) / duration
+    } else {
+        100
+    }
+}
+
+/// Coarse herding index (0-4) from the share of votes that followed the crowd
+pub fn herding_index(aligned: u32, counted: u32) -> u8 {
+    let alignment = if counted > 0 { (aligned as u64 * 100) / counted as u64 } else { 0 };
+    if counted < MIN_HERDING_SAMPLE || alignment <= 55 {
+        0
+    } else if alignment <= 65 {
+        1
+    } else if alignment <= 75 {
+        2
+    } else if alignment <= 85 {
+        3
+    } else {
+        4
+    }
+}
+
+/// Inverted Brier score on a 0-10000 scale (higher is better calibrated)
+pub fn brier_score(probability: u8, outcome: u8) -> u64 {
+    let target = if outcome == 1 { 100u64 } else { 0u64 };
+    let p = probability as u64;
+    let error = if target > p { target - p } else { p - target };
+    MAX_BRIER_SCORE - error * error
+}
+
+/// `aggregate_market_votes_v1` / `_v2`: fold a single vote into the market state
+pub fn aggregate_market_votes(
+    vote: &VoteData,
+    current_time: u64,
+    created_at: u64,
+    voting_ends_at: u64,
+    time_decay: bool,
+    current_state: &MarketVotingState,
+) -> MarketVotingState {
+    let mut state = current_state.clone();
+
+    let in_window = vote.timestamp >= created_at && vote.timestamp <= voting_ends_at;
+    let weight = time_weight(vote.timestamp, created_at, voting_ends_at, time_decay);
+
+    let total_weighted = state.time_weighted_yes_stake + state.time_weighted_no_stake;
+    let prevailing_yes = if total_weighted > 0 {
+        (state.time_weighted_yes_stake * 100) / total_weighted
+    } else {
+        50
+    };
+
+    if vote.vote_choice <= 2
+        && vote.stake_amount > 0
+        && vote.predicted_probability <= 100
+        && in_window
+    {
+        if vote.vote_choice != 2 && prevailing_yes != 50 {
+            state.herding_counted_votes += 1;
+            let crowd_says_yes = prevailing_yes > 50;
+            if (vote.vote_choice == 1) == crowd_says_yes {
+                state.herding_aligned_votes += 1;
+            }
+        }
+
+        if vote.vote_choice == 1 {
+            state.total_yes_votes += 1;
+            state.total_yes_stake += vote.stake_amount;
+            state.time_weighted_yes_stake += vote.stake_amount * weight;
+            state.conviction_weighted_yes += vote.conviction_score as u64 * vote.stake_amount;
+        } else if vote.vote_choice == 0 {
+            state.total_no_votes += 1;
+            state.total_no_stake += vote.stake_amount;
+            state.time_weighted_no_stake += vote.stake_amount * weight;
+            state.conviction_weighted_no += vote.conviction_score as u64 * vote.stake_amount;
+        } else if vote.vote_choice == 2 {
+            state.total_skip_votes += 1;
+        }
+
+        if vote.vote_choice != 2 {
+            state.weighted_probability_sum +=
+                vote.stake_amount * vote.predicted_probability as u64;
+            state.brier_score_sum_yes += brier_score(vote.predicted_probability, 1);
+            state.brier_score_sum_no += brier_score(vote.predicted_probability, 0);
+        }
+
+        state.total_participants += 1;
+    }
+
+    state.last_updated = current_time;
+
+    state
+}
+
+/// `calculate_payout_v2`: a single user's payout once `fee_amount` is taken off the total stake
+pub fn calculate_payout_after_fees(
+    payout_data: &PayoutData,
+    fee_amount: u64,
+    created_at: u64,
+    voting_ends_at: u64,
+    time_decay: bool,
+    state: &MarketVotingState,
+) -> u64 {
+    let mut final_payout = 0u64;
+
+    let outcome_is_yes = payout_data.market_outcome == 1;
+    let (total_winning_stake, total_losing_stake, winning_conviction, score_sum) =
+        if outcome_is_yes {
+            (
+                state.total_yes_stake,
+                state.total_no_stake,
+                state.conviction_weighted_yes,
+                state.brier_score_sum_yes,
+            )
+        } else {
+            (
+                state.total_no_stake,
+                state.total_yes_stake,
+                state.conviction_weighted_no,
+                state.brier_score_sum_no,
+            )
+        };
+    let total_winning_weight = if outcome_is_yes {
+        state.time_weighted_yes_stake
+    } else {
+        state.time_weighted_no_stake
+    };
+
+    let total_stake = total_winning_stake + total_losing_stake;
+    let distributable = total_stake - fee_amount.min(total_stake);
+    // With no calibration score to share it by (every forecast maximally wrong),
+    // the accuracy pool stays in the prize pool instead of going unpaid. With no
+    // Yes/No voter on the winning side, the prize and conviction pools have nobody
+    // to go to, so everything is shared out by calibration score instead
+    let has_winners = total_winning_weight > 0;
+    let accuracy_pool = if score_sum == 0 {
+        0
+    } else if has_winners {
+        payout_data.accuracy_bonus.min(distributable)
+    } else {
+        distributable
+    };
+    let conviction_pool = if has_winners {
+        payout_data.conviction_bonus.min(distributable - accuracy_pool)
+    } else {
+        0
+    };
+    let prize_pool = distributable - accuracy_pool - conviction_pool;
+
+    let counted = payout_data.user_vote <= 2
+        && payout_data.user_stake > 0
+        && payout_data.user_probability <= 100
+        && payout_data.user_timestamp >= created_at
+        && payout_data.user_timestamp <= voting_ends_at;
+
+    let user_won = counted && payout_data.user_vote == payout_data.market_outcome;
+
+    if user_won {
+        if total_winning_weight > 0 {
+            let weight =
+                time_weight(payout_data.user_timestamp, created_at, voting_ends_at, time_decay);
+            let user_weight = payout_data.user_stake as u128 * weight as u128;
+            final_payout +=
+                ((user_weight * prize_pool as u128) / total_winning_weight as u128) as u64;
+        }
+
+        if winning_conviction > 0 {
+            let user_conviction_weight =
+                payout_data.user_conviction as u128 * payout_data.user_stake as u128;
+            final_payout += ((conviction_pool as u128 * user_conviction_weight)
+                / winning_conviction as u128) as u64;
+        }
+    }
+
+    if counted && payout_data.user_vote != 2 && score_sum > 0 {
+        let score = brier_score(payout_data.user_probability, payout_data.market_outcome);
+        final_payout += ((accuracy_pool as u128 * score as u128) / score_sum as u128) as u64;
+    }
+
+    final_payout
+}
+
+/// `calculate_market_odds_v1`: revealed odds, spread widened by the herding index
+pub fn calculate_market_odds(state: &MarketVotingState) -> MarketOdds {
+    let herding = herding_index(state.herding_aligned_votes, state.herding_counted_votes);
+
+    let total_stake = state.total_yes_stake + state.total_no_stake;
+    let total_weighted = state.time_weighted_yes_stake + state.time_weighted_no_stake;
+
+    let (yes_probability, no_probability, high_confidence) = if total_weighted > 0 {
+        let yes_prob = (state.time_weighted_yes_stake * 100) / total_weighted;
+        let no_prob = (state.time_weighted_no_stake * 100) / total_weighted;
+
+        let liquidity_factor: u64 = if total_stake > 10000 {
+            95
+        } else if total_stake > 1000 {
+            90
+        } else {
+            85
+        };
+        let liquidity_factor = liquidity_factor - herding as u64 * 2;
+
+        let adjusted_yes = (yes_prob * liquidity_factor) / 100;
+        let adjusted_no = (no_prob * liquidity_factor) / 100;
+
+        (adjusted_yes as u8, adjusted_no as u8, total_stake > 1000)
+    } else {
+        (50u8, 50u8, false)
+    };
+
+    (yes_probability, no_probability, state.total_participants, high_confidence, herding)
+}
+
+/// `update_reputation_v1`: fold a resolved position into the reputation stats
+pub fn update_reputation(
+    vote: &VoteData,
+    payout: u64,
+    market_outcome: u8,
+    sealed: Option<&ReputationState>,
+) -> ReputationState {
+    let mut reputation = match sealed {
+        Some(state) => state.clone(),
+        None => ReputationState {
+            correct_predictions: 0,
+            total_winnings: 0,
+            streak_current: 0,
+            streak_best: 0,
+            reputation_score: BASE_REPUTATION,
+        },
+    };
+
+    if vote.vote_choice != 2 {
+        if vote.vote_choice == market_outcome {
+            reputation.correct_predictions += 1;
+            reputation.total_winnings += payout;
+            reputation.streak_current += 1;
+            if reputation.streak_current > reputation.streak_best {
+                reputation.streak_best = reputation.streak_current;
+            }
+            let gain = (10 + reputation.streak_current * 2).min(50);
+            reputation.reputation_score =
+                (reputation.reputation_score + gain).min(MAX_REPUTATION);
+        } else {
+            reputation.streak_current = 0;
+            reputation.reputation_score = if reputation.reputation_score > 20 {
+                reputation.reputation_score - 20
+            } else {
+                0
+            };
+        }
+    }
+
+    reputation
+}
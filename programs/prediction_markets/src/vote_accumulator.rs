@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::ErrorCode;
+
+// Append-only Merkle commitment to every vote submitted to a market. Each
+// submit_encrypted_vote appends a leaf hashing the vote's ciphertexts, x25519
+// pubkey and nonce; aggregation then runs in leaf order and echoes the root it
+// covered, so anyone replaying the VoteCommittedEvent log can check that the
+// sealed tally includes every committed vote.
+//
+// Leaves and nodes are domain-separated sha256 hashes (0x00 / 0x01 prefix). The
+// tree has a fixed depth; empty leaves are all-zero, so the root over N leaves
+// can be recomputed from the leaves alone.
+
+/// Depth of the vote tree; a market holds at most 2^VOTE_TREE_DEPTH votes
+pub const VOTE_TREE_DEPTH: usize = 16;
+
+#[account]
+#[derive(InitSpace)]
+pub struct VoteAccumulator {
+    pub version: u8,
+    pub bump: u8,
+    pub market: Pubkey,
+    pub leaf_count: u32,
+    pub root: [u8; 32],
+    // Rightmost filled node at each level, enough to append without the leaves
+    pub frontier: [[u8; 32]; VOTE_TREE_DEPTH],
+}
+
+impl VoteAccumulator {
+    pub const LEN: usize = 8 + Self::INIT_SPACE;
+    pub const VERSION: u8 = 1;
+
+    /// Leaf committing to one encrypted vote
+    pub fn vote_leaf(ciphertexts: &[[u8; 32]; 7], pubkey: &[u8; 32], nonce: u128) -> [u8; 32] {
+        let nonce = nonce.to_le_bytes();
+        let mut parts: Vec<&[u8]> = Vec::with_capacity(10);
+        parts.push(&[0x00]);
+        parts.extend(ciphertexts.iter().map(|ciphertext| ciphertext.as_slice()));
+        parts.push(pubkey);
+        parts.push(&nonce);
+        hashv(&parts).to_bytes()
+    }
+
+    pub fn node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        hashv(&[&[0x01], left, right]).to_bytes()
+    }
+
+    /// Append a leaf and update the root; returns the leaf's index
+    pub fn append(&mut self, leaf: [u8; 32]) -> Result<u32> {
+        require!(
+            (self.leaf_count as usize) < (1 << VOTE_TREE_DEPTH),
+            ErrorCode::VoteAccumulatorFull
+        );
+
+        let index = self.leaf_count;
+        let mut node = leaf;
+        let mut size = index + 1;
+        for level in 0..VOTE_TREE_DEPTH {
+            if size & 1 == 1 {
+                self.frontier[level] = node;
+                break;
+            }
+            node = Self::node(&self.frontier[level], &node);
+            size >>= 1;
+        }
+
+        self.leaf_count += 1;
+        self.root = self.compute_root();
+        Ok(index)
+    }
+
+    fn compute_root(&self) -> [u8; 32] {
+        let mut node = [0u8; 32];
+        let mut zero = [0u8; 32];
+        let mut size = self.leaf_count;
+        for level in 0..VOTE_TREE_DEPTH {
+            node = if size & 1 == 1 {
+                Self::node(&self.frontier[level], &node)
+            } else {
+                Self::node(&node, &zero)
+            };
+            zero = Self::node(&zero, &zero);
+            size >>= 1;
+        }
+        node
+    }
+}
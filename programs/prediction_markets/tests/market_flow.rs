@@ -0,0 +1,3036 @@
+//! End-to-end market lifecycle against the mock MXE:
+//! create -> vote -> aggregate -> resolve -> payout -> reputation -> claim,
+//! plus recovery of aborted computations.
+
+mod mock_mxe;
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use circuits_reference::{
+    MarketVotingState, ParlayChoices, PayoutData, PublicPool, VoteData, PARLAY_LEG_VOID,
+};
+use mock_mxe::{
+    computation_record_address, market_registry_address, market_state_from,
+    mxe_key_epoch_address, parlay_pool_address, program_config_address, reputation_from,
+    treasury_address, vote_fields, Circuit, MockMxe,
+};
+use prediction_markets::{
+    ComputationRecord, ComputationStatus, CreatorMarkets, DisclosureRecord, ErrorCode,
+    MarketCategory, MarketIndexPage, MarketMetadata, MarketRegistry, MarketStatus, OracleType,
+    Parlay, ParlayPool, PredictionMarket, PrivacyMode, ProgramConfig, PublicTally, Treasury,
+    UserPosition, UserProfile, VoteAccumulator,
+    LEGACY_MARKET_LEN, LEGACY_POSITION_LEN, LEGACY_PROFILE_LEN, MARKET_CATEGORY_COUNT,
+    MAX_COMPUTATION_ATTEMPTS, MAX_STAKE, VOTE_TREE_DEPTH,
+};
+use solana_program_test::BanksClientError;
+use solana_sdk::{
+    account::AccountSharedData,
+    hash::hashv,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_program,
+    transaction::TransactionError,
+};
+
+const MARKET_ID: u64 = 1;
+const VOTING_PERIOD: i64 = 3_600;
+
+struct Voter {
+    keypair: Keypair,
+    client_pubkey: [u8; 32],
+    encryption_nonce: u128,
+    vote: VoteData,
+    profile: Pubkey,
+    position: Pubkey,
+}
+
+fn market_address() -> Pubkey {
+    market_address_for(MARKET_ID)
+}
+
+fn market_address_for(market_id: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"market", market_id.to_le_bytes().as_ref()],
+        &prediction_markets::ID,
+    )
+    .0
+}
+
+fn category_index_address(category: MarketCategory, page: u32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"category_index", &[category.slot() as u8], page.to_le_bytes().as_ref()],
+        &prediction_markets::ID,
+    )
+    .0
+}
+
+fn creator_markets_address(creator: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"creator_markets", creator.as_ref()], &prediction_markets::ID).0
+}
+
+fn creator_index_address(creator: &Pubkey, page: u32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"creator_index", creator.as_ref(), page.to_le_bytes().as_ref()],
+        &prediction_markets::ID,
+    )
+    .0
+}
+
+fn vote_accumulator_address() -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"vote_accumulator", market_address().as_ref()],
+        &prediction_markets::ID,
+    )
+    .0
+}
+
+const METADATA_URI: &str = "https://example.com/markets/1.json";
+
+fn market_metadata() -> MarketMetadata {
+    MarketMetadata {
+        title: "Will the mock MXE settle this market?".to_string(),
+        description: "Runs the full lifecycle against circuits-reference".to_string(),
+        resolution_criteria: "Resolves YES if every payout matches the reference".to_string(),
+        image: String::new(),
+    }
+}
+
+fn program_ix(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: prediction_markets::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+fn custom_error(error: BanksClientError) -> u32 {
+    match error {
+        BanksClientError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(code),
+        )) => code,
+        other => panic!("expected a program error, got {other:?}"),
+    }
+}
+
+async fn create_market(mxe: &mut MockMxe, oracle: &Keypair) -> PredictionMarket {
+    create_market_with(mxe, oracle, false, 0).await
+}
+
+async fn create_market_with(
+    mxe: &mut MockMxe,
+    oracle: &Keypair,
+    reveal_after_resolution: bool,
+    creator_fee_bps: u16,
+) -> PredictionMarket {
+    let create = create_market_ix(
+        mxe,
+        oracle,
+        reveal_after_resolution,
+        creator_fee_bps,
+        PrivacyMode::Private,
+        None,
+    )
+    .await;
+    mxe.process(&[create], &[]).await.expect("create market");
+
+    mxe.account(market_address()).await
+}
+
+async fn create_market_ix(
+    mxe: &mut MockMxe,
+    oracle: &Keypair,
+    reveal_after_resolution: bool,
+    creator_fee_bps: u16,
+    privacy_mode: PrivacyMode,
+    parent: Option<(Pubkey, bool)>,
+) -> Instruction {
+    let voting_ends_at = mxe.unix_timestamp().await + VOTING_PERIOD;
+    let creator = mxe.payer().pubkey();
+    // Index pages the next market lands in, as a client would find them
+    let registry: MarketRegistry = mxe.account(market_registry_address()).await;
+    let category_page =
+        MarketIndexPage::page_for(registry.category_counts[MarketCategory::Technology.slot()]);
+    let creator_page = mxe
+        .try_account::<CreatorMarkets>(creator_markets_address(&creator))
+        .await
+        .map_or(0, |creator_markets| MarketIndexPage::page_for(creator_markets.market_count));
+    program_ix(
+        prediction_markets::accounts::CreateMarket {
+            creator,
+            market_registry: market_registry_address(),
+            prediction_market: market_address_for(registry.next_market_id),
+            oracle: oracle.pubkey(),
+            mxe_key_epoch: mxe_key_epoch_address(),
+            program_config: program_config_address(),
+            category_index_page: category_index_address(MarketCategory::Technology, category_page),
+            creator_markets: creator_markets_address(&creator),
+            creator_index_page: creator_index_address(&creator, creator_page),
+            parent_market: parent.map(|(parent_market, _)| parent_market),
+            system_program: system_program::ID,
+        },
+        prediction_markets::instruction::CreateMarket {
+            metadata_uri: METADATA_URI.to_string(),
+            metadata_hash: market_metadata().content_hash(),
+            category: MarketCategory::Technology,
+            voting_ends_at,
+            oracle_type: OracleType::Community,
+            time_decay_enabled: false,
+            reveal_after_resolution,
+            creator_fee_bps,
+            privacy_mode,
+            parent_outcome: parent.map(|(_, parent_outcome)| parent_outcome),
+        },
+    )
+}
+
+/// Fund a voter, create their profile and submit their encrypted vote
+async fn join_market(
+    mxe: &mut MockMxe,
+    index: u8,
+    vote_choice: u8,
+    stake_amount: u64,
+    predicted_probability: u8,
+    conviction_score: u16,
+) -> Voter {
+    let voter = new_voter(
+        mxe,
+        index,
+        vote_choice,
+        stake_amount,
+        predicted_probability,
+        conviction_score,
+    )
+    .await;
+    let submit = submit_vote_ix(mxe, &voter).await;
+    mxe.process_and_compute(&[submit], &[&voter.keypair])
+        .await
+        .expect("submit vote");
+
+    voter
+}
+
+/// Fund a voter and create their profile
+async fn new_voter(
+    mxe: &mut MockMxe,
+    index: u8,
+    vote_choice: u8,
+    stake_amount: u64,
+    predicted_probability: u8,
+    conviction_score: u16,
+) -> Voter {
+    let keypair = Keypair::new();
+    let user = keypair.pubkey();
+    let profile = Pubkey::find_program_address(&[b"profile", user.as_ref()], &prediction_markets::ID).0;
+    let position = Pubkey::find_program_address(
+        &[b"position", user.as_ref(), market_address().as_ref()],
+        &prediction_markets::ID,
+    )
+    .0;
+    let voter = Voter {
+        keypair,
+        client_pubkey: [10 + index; 32],
+        encryption_nonce: 1_000 + index as u128,
+        vote: VoteData {
+            voter: [0; 32],
+            market_id: MARKET_ID,
+            vote_choice,
+            stake_amount,
+            predicted_probability,
+            conviction_score,
+            timestamp: mxe.unix_timestamp().await as u64,
+            nonce: 42 + index as u128,
+        },
+        profile,
+        position,
+    };
+
+    mxe.fund(&user, 1_000_000_000).await;
+
+    let init_profile = program_ix(
+        prediction_markets::accounts::InitializeUserProfile {
+            user,
+            user_profile: profile,
+            system_program: system_program::ID,
+        },
+        prediction_markets::instruction::InitializeUserProfile {},
+    );
+    mxe.process(&[init_profile], &[&voter.keypair])
+        .await
+        .expect("initialize profile");
+
+    voter
+}
+
+async fn submit_vote_ix(mxe: &mut MockMxe, voter: &Voter) -> Instruction {
+    let ciphertexts = mxe
+        .shared_cipher(&voter.client_pubkey)
+        .encrypt(&vote_fields(&voter.vote), voter.encryption_nonce);
+    let (computation_offset, arcium) = mxe.queue_accounts(Circuit::SubmitPrivateVote).await;
+    program_ix(
+        prediction_markets::accounts::SubmitEncryptedVote {
+            user: voter.keypair.pubkey(),
+            sign_pda_account: arcium.sign_pda_account,
+            mxe_account: arcium.mxe_account,
+            mempool_account: arcium.mempool_account,
+            executing_pool: arcium.executing_pool,
+            computation_account: arcium.computation_account,
+            comp_def_account: arcium.comp_def_account,
+            cluster_account: arcium.cluster_account,
+            pool_account: arcium.pool_account,
+            clock_account: arcium.clock_account,
+            system_program: system_program::ID,
+            arcium_program: arcium.arcium_program,
+            computation_allocator: arcium.computation_allocator,
+            computation_record: arcium.computation_record,
+            prediction_market: market_address(),
+            user_position: voter.position,
+            user_profile: voter.profile,
+            mxe_key_epoch: mxe_key_epoch_address(),
+            vote_accumulator: vote_accumulator_address(),
+            program_config: program_config_address(),
+        },
+        prediction_markets::instruction::SubmitEncryptedVote {
+            computation_offset,
+            vote_data_encrypted: ciphertexts.try_into().unwrap(),
+            vote_encryption_pubkey: voter.client_pubkey,
+            vote_nonce: voter.encryption_nonce,
+            stake_amount: voter.vote.stake_amount,
+        },
+    )
+}
+
+async fn aggregate(mxe: &mut MockMxe, voter: &Voter) {
+    let aggregate = aggregate_ix(mxe, voter).await;
+    mxe.process_and_compute(&[aggregate], &[])
+        .await
+        .expect("aggregate vote");
+}
+
+async fn aggregate_ix(mxe: &mut MockMxe, voter: &Voter) -> Instruction {
+    let (computation_offset, arcium) = mxe.queue_accounts(Circuit::AggregateMarketVotesV2).await;
+    program_ix(
+        prediction_markets::accounts::AggregateVotesV2 {
+            payer: mxe.payer().pubkey(),
+            sign_pda_account: arcium.sign_pda_account,
+            mxe_account: arcium.mxe_account,
+            mempool_account: arcium.mempool_account,
+            executing_pool: arcium.executing_pool,
+            computation_account: arcium.computation_account,
+            comp_def_account: arcium.comp_def_account,
+            cluster_account: arcium.cluster_account,
+            pool_account: arcium.pool_account,
+            clock_account: arcium.clock_account,
+            system_program: system_program::ID,
+            arcium_program: arcium.arcium_program,
+            computation_allocator: arcium.computation_allocator,
+            computation_record: arcium.computation_record,
+            prediction_market: market_address(),
+            user_position: voter.position,
+            mxe_key_epoch: mxe_key_epoch_address(),
+        },
+        prediction_markets::instruction::AggregateVotesV2 { computation_offset },
+    )
+}
+
+async fn aggregate_v1_ix(mxe: &mut MockMxe, voter: &Voter) -> Instruction {
+    let (computation_offset, arcium) = mxe.queue_accounts(Circuit::AggregateMarketVotes).await;
+    program_ix(
+        prediction_markets::accounts::AggregateVotes {
+            payer: mxe.payer().pubkey(),
+            sign_pda_account: arcium.sign_pda_account,
+            mxe_account: arcium.mxe_account,
+            mempool_account: arcium.mempool_account,
+            executing_pool: arcium.executing_pool,
+            computation_account: arcium.computation_account,
+            comp_def_account: arcium.comp_def_account,
+            cluster_account: arcium.cluster_account,
+            pool_account: arcium.pool_account,
+            clock_account: arcium.clock_account,
+            system_program: system_program::ID,
+            arcium_program: arcium.arcium_program,
+            computation_allocator: arcium.computation_allocator,
+            computation_record: arcium.computation_record,
+            prediction_market: market_address(),
+            user_position: voter.position,
+            mxe_key_epoch: mxe_key_epoch_address(),
+        },
+        prediction_markets::instruction::AggregateVotes { computation_offset },
+    )
+}
+
+async fn resolve(mxe: &mut MockMxe, oracle: &Keypair, outcome: bool) {
+    let resolve = resolve_ix(oracle, market_address(), None, outcome);
+    mxe.process(&[resolve], &[oracle]).await.expect("resolve market");
+}
+
+fn resolve_ix(
+    oracle: &Keypair,
+    market: Pubkey,
+    parent_market: Option<Pubkey>,
+    outcome: bool,
+) -> Instruction {
+    program_ix(
+        prediction_markets::accounts::ResolveMarket {
+            oracle: oracle.pubkey(),
+            prediction_market: market,
+            treasury: treasury_address(),
+            parent_market,
+        },
+        prediction_markets::instruction::ResolveMarket { outcome },
+    )
+}
+
+async fn calculate_payout(mxe: &mut MockMxe, voter: &Voter) {
+    let (computation_offset, arcium) = mxe.queue_accounts(Circuit::CalculatePayoutV2).await;
+    let payout = program_ix(
+        prediction_markets::accounts::CalculateUserPayoutV2 {
+            user: voter.keypair.pubkey(),
+            sign_pda_account: arcium.sign_pda_account,
+            mxe_account: arcium.mxe_account,
+            mempool_account: arcium.mempool_account,
+            executing_pool: arcium.executing_pool,
+            computation_account: arcium.computation_account,
+            comp_def_account: arcium.comp_def_account,
+            cluster_account: arcium.cluster_account,
+            pool_account: arcium.pool_account,
+            clock_account: arcium.clock_account,
+            system_program: system_program::ID,
+            arcium_program: arcium.arcium_program,
+            computation_allocator: arcium.computation_allocator,
+            computation_record: arcium.computation_record,
+            prediction_market: market_address(),
+            user_position: voter.position,
+            program_config: program_config_address(),
+        },
+        prediction_markets::instruction::CalculateUserPayoutV2 { computation_offset },
+    );
+    mxe.process_and_compute(&[payout], &[&voter.keypair])
+        .await
+        .expect("calculate payout");
+}
+
+async fn record_reputation(mxe: &mut MockMxe, voter: &Voter) {
+    let (computation_offset, arcium) = mxe.queue_accounts(Circuit::UpdateReputation).await;
+    let record = program_ix(
+        prediction_markets::accounts::RecordPrivateReputation {
+            user: voter.keypair.pubkey(),
+            sign_pda_account: arcium.sign_pda_account,
+            mxe_account: arcium.mxe_account,
+            mempool_account: arcium.mempool_account,
+            executing_pool: arcium.executing_pool,
+            computation_account: arcium.computation_account,
+            comp_def_account: arcium.comp_def_account,
+            cluster_account: arcium.cluster_account,
+            pool_account: arcium.pool_account,
+            clock_account: arcium.clock_account,
+            system_program: system_program::ID,
+            arcium_program: arcium.arcium_program,
+            program_config: program_config_address(),
+            computation_allocator: arcium.computation_allocator,
+            computation_record: arcium.computation_record,
+            prediction_market: market_address(),
+            user_position: voter.position,
+            user_profile: voter.profile,
+        },
+        prediction_markets::instruction::RecordPrivateReputation { computation_offset },
+    );
+    mxe.process_and_compute(&[record], &[&voter.keypair])
+        .await
+        .expect("record reputation");
+}
+
+fn claim_ix(voter: &Voter) -> Instruction {
+    program_ix(
+        prediction_markets::accounts::ClaimPayout {
+            user: voter.keypair.pubkey(),
+            prediction_market: market_address(),
+            user_position: voter.position,
+            user_profile: voter.profile,
+            program_config: program_config_address(),
+        },
+        prediction_markets::instruction::ClaimPayout {},
+    )
+}
+
+#[tokio::test]
+async fn market_settles_end_to_end() {
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    let market = create_market(&mut mxe, &oracle).await;
+
+    let voters = vec![
+        join_market(&mut mxe, 0, 1, 3_000, 80, 700).await,
+        join_market(&mut mxe, 1, 1, 1_000, 60, 300).await,
+        join_market(&mut mxe, 2, 0, 2_000, 30, 500).await,
+    ];
+    for voter in &voters {
+        let position: UserPosition = mxe.account(voter.position).await;
+        assert!(position.is_validated);
+    }
+
+    // Aggregate every vote and check the sealed totals against the reference fold
+    let mut expected = MarketVotingState::default();
+    for voter in &voters {
+        aggregate(&mut mxe, voter).await;
+        expected = circuits_reference::aggregate_market_votes(
+            &voter.vote,
+            voter.vote.timestamp,
+            market.created_at as u64,
+            market.voting_ends_at as u64,
+            market.time_decay_enabled,
+            &expected,
+        );
+    }
+    let market: PredictionMarket = mxe.account(market_address()).await;
+    let state = market_state_from(
+        &mxe.mxe_cipher()
+            .decrypt(&market.encrypted_vote_state, market.nonce),
+    );
+    assert_eq!(market.total_stake, 6_000);
+    assert_eq!(state.total_yes_votes, expected.total_yes_votes);
+    assert_eq!(state.total_no_votes, expected.total_no_votes);
+    assert_eq!(state.total_yes_stake, 4_000);
+    assert_eq!(state.total_no_stake, 2_000);
+    assert_eq!(state.total_participants, 3);
+    assert_eq!(state.brier_score_sum_yes, expected.brier_score_sum_yes);
+    assert_eq!(state.brier_score_sum_no, expected.brier_score_sum_no);
+    assert_eq!(state.time_weighted_yes_stake, expected.time_weighted_yes_stake);
+    assert_eq!(state.time_weighted_no_stake, expected.time_weighted_no_stake);
+
+    resolve(&mut mxe, &oracle, true).await;
+
+    let mut total_paid = 0;
+    for voter in &voters {
+        calculate_payout(&mut mxe, voter).await;
+        record_reputation(&mut mxe, voter).await;
+        mxe.process(&[claim_ix(voter)], &[&voter.keypair])
+            .await
+            .expect("claim payout");
+
+        let position: UserPosition = mxe.account(voter.position).await;
+        assert!(position.is_claimed);
+        let payout = mxe
+            .shared_cipher(&voter.client_pubkey)
+            .decrypt(&[position.encrypted_payout], position.payout_nonce)[0] as u64;
+
+        let expected_payout = circuits_reference::calculate_payout(
+            &PayoutData {
+                user: [0; 32],
+                market_id: MARKET_ID,
+                user_stake: voter.vote.stake_amount,
+                user_vote: voter.vote.vote_choice,
+                user_probability: voter.vote.predicted_probability,
+                user_conviction: voter.vote.conviction_score,
+                user_timestamp: voter.vote.timestamp,
+                market_outcome: 1,
+                accuracy_bonus: market.total_stake / 100,
+                conviction_bonus: market.total_stake / 200,
+            },
+            market.created_at as u64,
+            market.voting_ends_at as u64,
+            market.time_decay_enabled,
+            &state,
+        );
+        assert_eq!(payout, expected_payout);
+        if voter.vote.vote_choice == 1 {
+            assert!(payout > voter.vote.stake_amount);
+        } else {
+            assert!(payout < voter.vote.stake_amount);
+        }
+        total_paid += payout;
+
+        let profile: UserProfile = mxe.account(voter.profile).await;
+        assert!(profile.has_private_reputation);
+        let reputation = reputation_from(
+            &mxe.mxe_cipher()
+                .decrypt(&profile.encrypted_reputation, profile.reputation_nonce),
+        );
+        assert_eq!(
+            reputation,
+            circuits_reference::update_reputation(&voter.vote, payout, 1, None)
+        );
+    }
+    assert!(total_paid <= market.total_stake);
+}
+
+#[tokio::test]
+async fn claim_requires_recorded_reputation() {
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    create_market(&mut mxe, &oracle).await;
+
+    let voter = join_market(&mut mxe, 0, 1, 1_000, 70, 400).await;
+    aggregate(&mut mxe, &voter).await;
+    resolve(&mut mxe, &oracle, true).await;
+    calculate_payout(&mut mxe, &voter).await;
+
+    let error = mxe
+        .process(&[claim_ix(&voter)], &[&voter.keypair])
+        .await
+        .err()
+        .expect("claim before reputation should fail");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::ReputationNotRecorded));
+}
+
+#[tokio::test]
+async fn failed_vote_is_retried_then_rolled_back() {
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    create_market(&mut mxe, &oracle).await;
+
+    let voter = new_voter(&mut mxe, 0, 1, 1_000, 70, 400).await;
+    let submit = submit_vote_ix(&mut mxe, &voter).await;
+    let mut queued = mxe.process(&[submit], &[&voter.keypair]).await.unwrap();
+    let mut failed_offset = queued[0].computation_offset;
+    mxe.abort(queued.remove(0)).await.expect("aborted callback still lands");
+
+    let record: ComputationRecord = mxe.account(computation_record_address(failed_offset)).await;
+    assert!(record.status == ComputationStatus::Failed);
+    let position: UserPosition = mxe.account(voter.position).await;
+    assert!(!position.is_validated);
+
+    // Retry until the attempt limit, aborting every time
+    for attempt in 2..=MAX_COMPUTATION_ATTEMPTS {
+        let (computation_offset, arcium) = mxe.queue_accounts(Circuit::SubmitPrivateVote).await;
+        let retry = program_ix(
+            prediction_markets::accounts::RetryComputation {
+                payer: mxe.payer().pubkey(),
+                sign_pda_account: arcium.sign_pda_account,
+                mxe_account: arcium.mxe_account,
+                mempool_account: arcium.mempool_account,
+                executing_pool: arcium.executing_pool,
+                computation_account: arcium.computation_account,
+                comp_def_account: arcium.comp_def_account,
+                cluster_account: arcium.cluster_account,
+                pool_account: arcium.pool_account,
+                clock_account: arcium.clock_account,
+                system_program: system_program::ID,
+                arcium_program: arcium.arcium_program,
+                prediction_market: market_address(),
+                user_position: voter.position,
+                failed_record: computation_record_address(failed_offset),
+                program_config: program_config_address(),
+                computation_allocator: arcium.computation_allocator,
+                computation_record: arcium.computation_record,
+            },
+            prediction_markets::instruction::RetryComputation { computation_offset },
+        );
+        let mut queued = mxe.process(&[retry], &[]).await.expect("retry");
+        mxe.abort(queued.remove(0)).await.unwrap();
+
+        let previous: ComputationRecord =
+            mxe.account(computation_record_address(failed_offset)).await;
+        assert!(previous.status == ComputationStatus::Retried);
+        let record: ComputationRecord =
+            mxe.account(computation_record_address(computation_offset)).await;
+        assert_eq!(record.attempts, attempt);
+        failed_offset = computation_offset;
+    }
+
+    let rollback = program_ix(
+        prediction_markets::accounts::RollbackFailedVote {
+            caller: mxe.payer().pubkey(),
+            prediction_market: market_address(),
+            user_position: voter.position,
+            user: voter.keypair.pubkey(),
+            user_profile: voter.profile,
+            computation_record: computation_record_address(failed_offset),
+        },
+        prediction_markets::instruction::RollbackFailedVote {},
+    );
+    mxe.process(&[rollback], &[]).await.expect("rollback");
+
+    let market: PredictionMarket = mxe.account(market_address()).await;
+    assert_eq!(market.total_stake, 0);
+    assert_eq!(market.participant_count, 0);
+    let profile: UserProfile = mxe.account(voter.profile).await;
+    assert_eq!(profile.total_markets_participated, 0);
+    assert!(mxe
+        .context
+        .banks_client
+        .get_account(voter.position)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn invalid_vote_fails_validation() {
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    create_market(&mut mxe, &oracle).await;
+
+    // A conviction of 0 is out of range; only the circuit can see it
+    for (index, conviction_score, valid) in [(0, 0, false), (1, 400, true)] {
+        let voter = new_voter(&mut mxe, index, 1, 1_000, 70, conviction_score).await;
+        let submit = submit_vote_ix(&mut mxe, &voter).await;
+        let mut queued = mxe.process(&[submit], &[&voter.keypair]).await.unwrap();
+        let offset = queued[0].computation_offset;
+        mxe.compute(queued.remove(0)).await.expect("validation callback");
+
+        let record: ComputationRecord = mxe.account(computation_record_address(offset)).await;
+        let position: UserPosition = mxe.account(voter.position).await;
+        if valid {
+            assert!(record.status == ComputationStatus::Succeeded);
+            assert!(position.is_validated);
+        } else {
+            assert!(record.status == ComputationStatus::Failed);
+            assert!(!position.is_validated);
+        }
+    }
+}
+
+#[tokio::test]
+async fn vote_timestamp_far_from_the_clock_is_never_aggregated() {
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    create_market(&mut mxe, &oracle).await;
+
+    // Inside the voting window, so only the drift check can catch it; left
+    // unchecked it would be aggregated with a later time-decay weight
+    let mut voter = new_voter(&mut mxe, 0, 1, 1_000, 70, 400).await;
+    voter.vote.timestamp += 30 * 60;
+    let submit = submit_vote_ix(&mut mxe, &voter).await;
+    mxe.process_and_compute(&[submit], &[&voter.keypair])
+        .await
+        .expect("validation callback");
+    let position: UserPosition = mxe.account(voter.position).await;
+    assert!(!position.is_validated);
+
+    let aggregate = aggregate_ix(&mut mxe, &voter).await;
+    let error = mxe
+        .process(&[aggregate], &[])
+        .await
+        .err()
+        .expect("aggregate a drifted vote");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::PositionNotValidated));
+}
+
+#[tokio::test]
+async fn sealed_stake_must_match_the_stake_paid() {
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    create_market(&mut mxe, &oracle).await;
+
+    // Pay 1 lamport but seal 1_000 in the vote
+    let voter = new_voter(&mut mxe, 0, 1, 1_000, 70, 400).await;
+    let mut submit = submit_vote_ix(&mut mxe, &voter).await;
+    let stake_at = submit.data.len() - 8;
+    submit.data[stake_at..].copy_from_slice(&1u64.to_le_bytes());
+    mxe.process_and_compute(&[submit], &[&voter.keypair])
+        .await
+        .expect("validation callback");
+
+    let position: UserPosition = mxe.account(voter.position).await;
+    assert_eq!(position.stake_amount, 1);
+    assert!(!position.is_validated);
+
+    let aggregate = aggregate_ix(&mut mxe, &voter).await;
+    let error = mxe
+        .process(&[aggregate], &[])
+        .await
+        .err()
+        .expect("aggregate a mismatched stake");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::PositionNotValidated));
+}
+
+#[tokio::test]
+async fn v1_aggregation_takes_each_validated_vote_once() {
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    create_market(&mut mxe, &oracle).await;
+    pin_market_to_circuit_v1(&mut mxe).await;
+
+    let invalid = new_voter(&mut mxe, 0, 1, 1_000, 70, 0).await;
+    let submit = submit_vote_ix(&mut mxe, &invalid).await;
+    mxe.process_and_compute(&[submit], &[&invalid.keypair])
+        .await
+        .expect("validation callback");
+    let aggregate = aggregate_v1_ix(&mut mxe, &invalid).await;
+    let error = mxe
+        .process(&[aggregate], &[])
+        .await
+        .err()
+        .expect("aggregate an invalid vote");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::PositionNotValidated));
+
+    let voter = join_market(&mut mxe, 1, 1, 1_000, 70, 400).await;
+    let aggregate = aggregate_v1_ix(&mut mxe, &voter).await;
+    mxe.process_and_compute(&[aggregate], &[])
+        .await
+        .expect("aggregate vote");
+    let position: UserPosition = mxe.account(voter.position).await;
+    assert!(position.is_aggregated);
+    let market: PredictionMarket = mxe.account(market_address()).await;
+    let state = market_state_from(
+        &mxe.mxe_cipher()
+            .decrypt(&market.encrypted_vote_state, market.nonce),
+    );
+    assert_eq!(state.total_participants, 1);
+
+    let again = aggregate_v1_ix(&mut mxe, &voter).await;
+    let error = mxe
+        .process(&[again], &[])
+        .await
+        .err()
+        .expect("aggregate the same vote twice");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::VoteOutOfOrder));
+}
+
+#[tokio::test]
+async fn stale_computation_offset_is_rejected() {
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    create_market(&mut mxe, &oracle).await;
+
+    // Both clients read the same next_offset before either lands
+    let first = new_voter(&mut mxe, 0, 1, 1_000, 70, 400).await;
+    let second = new_voter(&mut mxe, 1, 1, 1_000, 60, 300).await;
+    let first_submit = submit_vote_ix(&mut mxe, &first).await;
+    let second_submit = submit_vote_ix(&mut mxe, &second).await;
+
+    let queued = mxe.process(&[first_submit], &[&first.keypair]).await.unwrap();
+    let record: ComputationRecord =
+        mxe.account(computation_record_address(queued[0].computation_offset)).await;
+    assert!(record.status == ComputationStatus::Queued);
+
+    let error = mxe
+        .process(&[second_submit], &[&second.keypair])
+        .await
+        .err()
+        .expect("second client reused the offset");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::StaleComputationOffset));
+
+    // Re-reading the allocator gives the second client a fresh offset
+    let second_submit = submit_vote_ix(&mut mxe, &second).await;
+    mxe.process_and_compute(&[second_submit], &[&second.keypair])
+        .await
+        .expect("resubmit with allocated offset");
+}
+
+#[tokio::test]
+async fn key_rotation_reencrypts_market_and_positions() {
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    create_market(&mut mxe, &oracle).await;
+
+    let early = join_market(&mut mxe, 0, 1, 3_000, 80, 700).await;
+    aggregate(&mut mxe, &early).await;
+    let market: PredictionMarket = mxe.account(market_address()).await;
+    let sealed_before = mxe
+        .mxe_cipher()
+        .decrypt(&market.encrypted_vote_state, market.nonce);
+
+    mxe.rotate_key([9; 32]);
+    let rotate = program_ix(
+        prediction_markets::accounts::RecordMxeKeyRotation {
+            admin: mxe.payer().pubkey(),
+            mxe_key_epoch: mxe_key_epoch_address(),
+            program_config: program_config_address(),
+        },
+        prediction_markets::instruction::RecordMxeKeyRotation {},
+    );
+    mxe.process(&[rotate], &[]).await.expect("record rotation");
+
+    // A vote cast after the rotation is encrypted under the new key, but the
+    // market's sealed state isn't until it has been re-encrypted
+    let late = join_market(&mut mxe, 1, 0, 1_000, 40, 300).await;
+    let late_aggregate = aggregate_ix(&mut mxe, &late).await;
+    let error = mxe
+        .process(&[late_aggregate], &[])
+        .await
+        .err()
+        .expect("aggregate onto stale state");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::StaleKeyEpoch));
+
+    let (computation_offset, arcium) = mxe.queue_accounts(Circuit::ReencryptMarketState).await;
+    let reencrypt_market = program_ix(
+        prediction_markets::accounts::ReencryptMarketState {
+            payer: mxe.payer().pubkey(),
+            sign_pda_account: arcium.sign_pda_account,
+            mxe_account: arcium.mxe_account,
+            mempool_account: arcium.mempool_account,
+            executing_pool: arcium.executing_pool,
+            computation_account: arcium.computation_account,
+            comp_def_account: arcium.comp_def_account,
+            cluster_account: arcium.cluster_account,
+            pool_account: arcium.pool_account,
+            clock_account: arcium.clock_account,
+            system_program: system_program::ID,
+            arcium_program: arcium.arcium_program,
+            prediction_market: market_address(),
+            mxe_key_epoch: mxe_key_epoch_address(),
+            computation_allocator: arcium.computation_allocator,
+            computation_record: arcium.computation_record,
+        },
+        prediction_markets::instruction::ReencryptMarketState { computation_offset },
+    );
+    mxe.process_and_compute(&[reencrypt_market], &[])
+        .await
+        .expect("reencrypt market state");
+
+    let market: PredictionMarket = mxe.account(market_address()).await;
+    assert_eq!(market.key_epoch, 1);
+    assert_eq!(
+        mxe.mxe_cipher()
+            .decrypt(&market.encrypted_vote_state, market.nonce),
+        sealed_before
+    );
+
+    let (computation_offset, arcium) = mxe.queue_accounts(Circuit::ReencryptPositions).await;
+    let mut reencrypt_positions = program_ix(
+        prediction_markets::accounts::ReencryptPositions {
+            payer: mxe.payer().pubkey(),
+            sign_pda_account: arcium.sign_pda_account,
+            mxe_account: arcium.mxe_account,
+            mempool_account: arcium.mempool_account,
+            executing_pool: arcium.executing_pool,
+            computation_account: arcium.computation_account,
+            comp_def_account: arcium.comp_def_account,
+            cluster_account: arcium.cluster_account,
+            pool_account: arcium.pool_account,
+            clock_account: arcium.clock_account,
+            system_program: system_program::ID,
+            arcium_program: arcium.arcium_program,
+            prediction_market: market_address(),
+            mxe_key_epoch: mxe_key_epoch_address(),
+            computation_allocator: arcium.computation_allocator,
+            computation_record: arcium.computation_record,
+        },
+        prediction_markets::instruction::ReencryptPositions { computation_offset },
+    );
+    reencrypt_positions
+        .accounts
+        .push(AccountMeta::new(early.position, false));
+    mxe.process_and_compute(&[reencrypt_positions], &[])
+        .await
+        .expect("reencrypt positions");
+
+    let position: UserPosition = mxe.account(early.position).await;
+    assert_eq!(position.key_epoch, 1);
+    assert_eq!(
+        mxe.shared_cipher(&early.client_pubkey)
+            .decrypt(&position.encrypted_vote_data, position.vote_nonce),
+        vote_fields(&early.vote)
+    );
+
+    // Both votes now aggregate under the new key
+    aggregate(&mut mxe, &late).await;
+    let market: PredictionMarket = mxe.account(market_address()).await;
+    let state = market_state_from(
+        &mxe.mxe_cipher()
+            .decrypt(&market.encrypted_vote_state, market.nonce),
+    );
+    assert_eq!(state.total_participants, 2);
+    assert_eq!(state.total_yes_stake, 3_000);
+    assert_eq!(state.total_no_stake, 1_000);
+}
+
+#[tokio::test]
+async fn vote_is_disclosed_only_to_the_recipient() {
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    create_market(&mut mxe, &oracle).await;
+    let voter = join_market(&mut mxe, 0, 1, 2_500, 65, 450).await;
+
+    let recipient_pubkey = [99; 32];
+    let position_before: UserPosition = mxe.account(voter.position).await;
+    let disclosure = Pubkey::find_program_address(
+        &[b"disclosure", voter.position.as_ref(), 0u32.to_le_bytes().as_ref()],
+        &prediction_markets::ID,
+    )
+    .0;
+
+    let (computation_offset, arcium) = mxe.queue_accounts(Circuit::DiscloseVote).await;
+    let disclose = program_ix(
+        prediction_markets::accounts::DisclosePosition {
+            user: voter.keypair.pubkey(),
+            sign_pda_account: arcium.sign_pda_account,
+            mxe_account: arcium.mxe_account,
+            mempool_account: arcium.mempool_account,
+            executing_pool: arcium.executing_pool,
+            computation_account: arcium.computation_account,
+            comp_def_account: arcium.comp_def_account,
+            cluster_account: arcium.cluster_account,
+            pool_account: arcium.pool_account,
+            clock_account: arcium.clock_account,
+            system_program: system_program::ID,
+            arcium_program: arcium.arcium_program,
+            prediction_market: market_address(),
+            user_position: voter.position,
+            disclosure_record: disclosure,
+            mxe_key_epoch: mxe_key_epoch_address(),
+            program_config: program_config_address(),
+            computation_allocator: arcium.computation_allocator,
+            computation_record: arcium.computation_record,
+        },
+        prediction_markets::instruction::DisclosePosition {
+            computation_offset,
+            recipient_pubkey,
+            recipient_nonce: 5_000,
+        },
+    );
+    mxe.process_and_compute(&[disclose], &[&voter.keypair])
+        .await
+        .expect("disclose position");
+
+    let record: DisclosureRecord = mxe.account(disclosure).await;
+    assert!(record.is_complete);
+    assert_eq!(record.user, voter.keypair.pubkey());
+    assert_eq!(record.recipient_pubkey, recipient_pubkey);
+    assert_eq!(
+        mxe.shared_cipher(&recipient_pubkey)
+            .decrypt(&record.encrypted_vote, record.vote_nonce),
+        vote_fields(&voter.vote)
+    );
+    // The voter's own ciphertext is untouched and the next disclosure gets a new record
+    let position: UserPosition = mxe.account(voter.position).await;
+    assert_eq!(position.encrypted_vote_data, position_before.encrypted_vote_data);
+    assert_eq!(position.disclosure_count, 1);
+}
+
+async fn reveal_positions_ix(mxe: &mut MockMxe, voters: &[Voter]) -> Instruction {
+    let (computation_offset, arcium) = mxe.queue_accounts(Circuit::RevealVotes).await;
+    let mut reveal = program_ix(
+        prediction_markets::accounts::RevealPositions {
+            payer: mxe.payer().pubkey(),
+            sign_pda_account: arcium.sign_pda_account,
+            mxe_account: arcium.mxe_account,
+            mempool_account: arcium.mempool_account,
+            executing_pool: arcium.executing_pool,
+            computation_account: arcium.computation_account,
+            comp_def_account: arcium.comp_def_account,
+            cluster_account: arcium.cluster_account,
+            pool_account: arcium.pool_account,
+            clock_account: arcium.clock_account,
+            system_program: system_program::ID,
+            arcium_program: arcium.arcium_program,
+            prediction_market: market_address(),
+            mxe_key_epoch: mxe_key_epoch_address(),
+            program_config: program_config_address(),
+            computation_allocator: arcium.computation_allocator,
+            computation_record: arcium.computation_record,
+        },
+        prediction_markets::instruction::RevealPositions { computation_offset },
+    );
+    reveal
+        .accounts
+        .extend(voters.iter().map(|voter| AccountMeta::new(voter.position, false)));
+    reveal
+}
+
+#[tokio::test]
+async fn votes_are_revealed_after_resolution() {
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    create_market_with(&mut mxe, &oracle, true, 0).await;
+
+    let voters = vec![
+        join_market(&mut mxe, 0, 1, 3_000, 80, 700).await,
+        join_market(&mut mxe, 1, 0, 1_500, 35, 250).await,
+    ];
+
+    // Votes stay private while the market is open
+    let early = reveal_positions_ix(&mut mxe, &voters).await;
+    let error = mxe
+        .process(&[early], &[])
+        .await
+        .err()
+        .expect("reveal before resolution");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::MarketNotResolved));
+
+    resolve(&mut mxe, &oracle, true).await;
+    let reveal = reveal_positions_ix(&mut mxe, &voters).await;
+    mxe.process_and_compute(&[reveal], &[])
+        .await
+        .expect("reveal positions");
+
+    for voter in &voters {
+        let position: UserPosition = mxe.account(voter.position).await;
+        assert!(position.is_revealed);
+        assert_eq!(position.revealed_vote_choice, voter.vote.vote_choice);
+        assert_eq!(position.revealed_probability, voter.vote.predicted_probability);
+        assert_eq!(position.revealed_conviction, voter.vote.conviction_score);
+    }
+}
+
+/// Root over `leaves` built level by level, as an auditor would from the event log
+fn vote_tree_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    let node = |left: &[u8; 32], right: &[u8; 32]| hashv(&[&[0x01], left, right]).to_bytes();
+    let mut level = leaves.to_vec();
+    let mut zero = [0u8; 32];
+    for _ in 0..VOTE_TREE_DEPTH {
+        if level.len() % 2 == 1 {
+            level.push(zero);
+        }
+        level = level.chunks(2).map(|pair| node(&pair[0], &pair[1])).collect();
+        zero = node(&zero, &zero);
+    }
+    level[0]
+}
+
+#[tokio::test]
+async fn aggregated_root_covers_every_committed_vote() {
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    create_market(&mut mxe, &oracle).await;
+
+    let voters = vec![
+        join_market(&mut mxe, 0, 1, 3_000, 80, 700).await,
+        join_market(&mut mxe, 1, 0, 1_000, 35, 300).await,
+        join_market(&mut mxe, 2, 1, 2_000, 60, 500).await,
+    ];
+
+    // Recompute every leaf from the public ciphertexts
+    let mut leaves = Vec::new();
+    for (index, voter) in voters.iter().enumerate() {
+        let position: UserPosition = mxe.account(voter.position).await;
+        assert_eq!(position.leaf_index, index as u32);
+        let nonce = position.vote_nonce.to_le_bytes();
+        let mut parts: Vec<&[u8]> = vec![&[0x00]];
+        parts.extend(position.encrypted_vote_data.iter().map(|c| c.as_slice()));
+        parts.push(&position.vote_pubkey);
+        parts.push(&nonce);
+        leaves.push(hashv(&parts).to_bytes());
+        assert_eq!(position.vote_root, vote_tree_root(&leaves));
+    }
+    let accumulator: VoteAccumulator = mxe.account(vote_accumulator_address()).await;
+    assert_eq!(accumulator.leaf_count, 3);
+    assert_eq!(accumulator.root, vote_tree_root(&leaves));
+
+    // Votes are tallied in commitment order
+    let skip_ahead = aggregate_ix(&mut mxe, &voters[1]).await;
+    let error = mxe
+        .process(&[skip_ahead], &[])
+        .await
+        .err()
+        .expect("aggregate out of order");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::VoteOutOfOrder));
+
+    for (count, voter) in voters.iter().enumerate() {
+        aggregate(&mut mxe, voter).await;
+        let market: PredictionMarket = mxe.account(market_address()).await;
+        assert_eq!(market.aggregated_vote_count, count as u32 + 1);
+        assert_eq!(market.aggregated_vote_root, vote_tree_root(&leaves[..=count]));
+    }
+
+    let again = aggregate_ix(&mut mxe, &voters[2]).await;
+    let error = mxe
+        .process(&[again], &[])
+        .await
+        .err()
+        .expect("aggregate the same vote twice");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::VoteOutOfOrder));
+
+    let market: PredictionMarket = mxe.account(market_address()).await;
+    assert_eq!(market.aggregated_vote_root, accumulator.root);
+}
+
+#[tokio::test]
+async fn fees_are_charged_at_resolution_and_withdrawn() {
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    let admin = mxe.payer().pubkey();
+
+    let update = program_ix(
+        prediction_markets::accounts::UpdateFeeConfig {
+            admin,
+            program_config: program_config_address(),
+        },
+        prediction_markets::instruction::UpdateFeeConfig {
+            protocol_fee_bps: 200,
+            max_creator_fee_bps: 300,
+        },
+    );
+    mxe.process(&[update], &[]).await.expect("set fees");
+
+    let too_high =
+        create_market_ix(&mut mxe, &oracle, false, 301, PrivacyMode::Private, None).await;
+    let error = mxe
+        .process(&[too_high], &[])
+        .await
+        .err()
+        .expect("creator fee above the cap");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::CreatorFeeTooHigh));
+    create_market_with(&mut mxe, &oracle, false, 100).await;
+
+    let winner = join_market(&mut mxe, 0, 1, 3_000, 80, 700).await;
+    let loser = join_market(&mut mxe, 1, 0, 1_000, 40, 300).await;
+    aggregate(&mut mxe, &winner).await;
+    aggregate(&mut mxe, &loser).await;
+    resolve(&mut mxe, &oracle, true).await;
+
+    // 2% protocol + 1% creator on 4,000 staked
+    let market: PredictionMarket = mxe.account(market_address()).await;
+    assert_eq!(market.fee_amount, 120);
+    assert_eq!(market.creator_fees_owed, 40);
+    let treasury: Treasury = mxe.account(treasury_address()).await;
+    assert_eq!(treasury.protocol_fees_accrued, 80);
+    assert_eq!(treasury.creator_fees_accrued, 40);
+
+    // Payouts split what's left after fees
+    calculate_payout(&mut mxe, &winner).await;
+    let position: UserPosition = mxe.account(winner.position).await;
+    let payout = mxe
+        .shared_cipher(&winner.client_pubkey)
+        .decrypt(&[position.encrypted_payout], position.payout_nonce)[0] as u64;
+    let state = market_state_from(
+        &mxe.mxe_cipher()
+            .decrypt(&market.encrypted_vote_state, market.nonce),
+    );
+    let expected = circuits_reference::calculate_payout_after_fees(
+        &PayoutData {
+            user: [0; 32],
+            market_id: MARKET_ID,
+            user_stake: winner.vote.stake_amount,
+            user_vote: winner.vote.vote_choice,
+            user_probability: winner.vote.predicted_probability,
+            user_conviction: winner.vote.conviction_score,
+            user_timestamp: winner.vote.timestamp,
+            market_outcome: 1,
+            accuracy_bonus: market.total_stake / 100,
+            conviction_bonus: market.total_stake / 200,
+        },
+        market.fee_amount,
+        market.created_at as u64,
+        market.voting_ends_at as u64,
+        market.time_decay_enabled,
+        &state,
+    );
+    assert_eq!(payout, expected);
+    assert!(payout <= market.total_stake - market.fee_amount);
+
+    // Fees are counters on unescrowed stake: withdrawing settles them, and the
+    // treasury's lamports (slashed bonds) stay put
+    let recipient = Pubkey::new_unique();
+    mxe.fund(&treasury_address(), 1_000_000).await;
+    mxe.fund(&recipient, 1_000_000).await;
+    let treasury_before = balance(&mut mxe, treasury_address()).await;
+    let withdraw = program_ix(
+        prediction_markets::accounts::WithdrawProtocolFees {
+            admin,
+            program_config: program_config_address(),
+            treasury: treasury_address(),
+            recipient,
+        },
+        prediction_markets::instruction::WithdrawProtocolFees { amount: 80 },
+    );
+    mxe.process(&[withdraw], &[])
+        .await
+        .expect("withdraw protocol fees");
+    assert_eq!(balance(&mut mxe, recipient).await, 1_000_000);
+
+    let withdraw_creator = program_ix(
+        prediction_markets::accounts::WithdrawCreatorFees {
+            creator: admin,
+            prediction_market: market_address(),
+            treasury: treasury_address(),
+            program_config: program_config_address(),
+        },
+        prediction_markets::instruction::WithdrawCreatorFees {},
+    );
+    mxe.process(&[withdraw_creator.clone()], &[])
+        .await
+        .expect("withdraw creator fees");
+    let treasury: Treasury = mxe.account(treasury_address()).await;
+    assert_eq!(treasury.protocol_fees_accrued, 0);
+    assert_eq!(treasury.creator_fees_accrued, 0);
+    assert_eq!(balance(&mut mxe, treasury_address()).await, treasury_before);
+
+    mxe.context.get_new_latest_blockhash().await.unwrap();
+    let error = mxe
+        .process(&[withdraw_creator], &[])
+        .await
+        .err()
+        .expect("creator fees already withdrawn");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::InsufficientAccruedFees));
+}
+
+fn set_pause_flags_ix(authority: Pubkey, votes_paused: bool) -> Instruction {
+    program_ix(
+        prediction_markets::accounts::SetPauseFlags {
+            authority,
+            program_config: program_config_address(),
+        },
+        prediction_markets::instruction::SetPauseFlags {
+            markets_paused: false,
+            votes_paused,
+            claims_paused: false,
+        },
+    )
+}
+
+#[tokio::test]
+async fn guardian_pauses_and_only_the_admin_unpauses() {
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    let guardian = Keypair::new();
+    let new_admin = Keypair::new();
+    let old_admin = mxe.payer().pubkey();
+    create_market(&mut mxe, &oracle).await;
+
+    let appoint = program_ix(
+        prediction_markets::accounts::SetGuardian {
+            admin: old_admin,
+            program_config: program_config_address(),
+        },
+        prediction_markets::instruction::SetGuardian {
+            guardian: guardian.pubkey(),
+        },
+    );
+    mxe.process(&[appoint], &[]).await.expect("set guardian");
+    mxe.process(&[set_pause_flags_ix(guardian.pubkey(), true)], &[&guardian])
+        .await
+        .expect("guardian pauses voting");
+
+    let voter = new_voter(&mut mxe, 0, 1, 1_000, 70, 500).await;
+    let submit = submit_vote_ix(&mut mxe, &voter).await;
+    let error = mxe
+        .process(&[submit], &[&voter.keypair])
+        .await
+        .err()
+        .expect("vote while paused");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::VotingPaused));
+
+    let error = mxe
+        .process(&[set_pause_flags_ix(guardian.pubkey(), false)], &[&guardian])
+        .await
+        .err()
+        .expect("guardian unpauses");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::Unauthorized));
+
+    // The admin role only moves once the proposed key accepts it
+    let propose = program_ix(
+        prediction_markets::accounts::ProposeAdmin {
+            admin: old_admin,
+            program_config: program_config_address(),
+        },
+        prediction_markets::instruction::ProposeAdmin {
+            new_admin: new_admin.pubkey(),
+        },
+    );
+    mxe.process(&[propose], &[]).await.expect("propose admin");
+    let accept = |pending_admin: Pubkey| {
+        program_ix(
+            prediction_markets::accounts::AcceptAdmin {
+                pending_admin,
+                program_config: program_config_address(),
+            },
+            prediction_markets::instruction::AcceptAdmin {},
+        )
+    };
+    let error = mxe
+        .process(&[accept(guardian.pubkey())], &[&guardian])
+        .await
+        .err()
+        .expect("accept by someone else");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::Unauthorized));
+    mxe.process(&[accept(new_admin.pubkey())], &[&new_admin])
+        .await
+        .expect("accept admin");
+
+    let config: ProgramConfig = mxe.account(program_config_address()).await;
+    assert_eq!(config.admin, new_admin.pubkey());
+    assert_eq!(config.pending_admin, None);
+    assert!(config.votes_paused);
+
+    let error = mxe
+        .process(&[set_pause_flags_ix(old_admin, false)], &[])
+        .await
+        .err()
+        .expect("previous admin unpauses");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::Unauthorized));
+    mxe.process(&[set_pause_flags_ix(new_admin.pubkey(), false)], &[&new_admin])
+        .await
+        .expect("admin unpauses voting");
+
+    mxe.context.get_new_latest_blockhash().await.unwrap();
+    let submit = submit_vote_ix(&mut mxe, &voter).await;
+    mxe.process_and_compute(&[submit], &[&voter.keypair])
+        .await
+        .expect("vote after unpausing");
+}
+
+async fn set_technology_bond(mxe: &mut MockMxe, bond: u64) {
+    let mut creation_bonds = [0; MARKET_CATEGORY_COUNT];
+    creation_bonds[MarketCategory::Technology.slot()] = bond;
+    let set_bonds = program_ix(
+        prediction_markets::accounts::SetCreationBonds {
+            admin: mxe.payer().pubkey(),
+            program_config: program_config_address(),
+        },
+        prediction_markets::instruction::SetCreationBonds { creation_bonds },
+    );
+    mxe.process(&[set_bonds], &[]).await.expect("set creation bonds");
+}
+
+async fn balance(mxe: &mut MockMxe, address: Pubkey) -> u64 {
+    mxe.context.banks_client.get_balance(address).await.unwrap()
+}
+
+#[tokio::test]
+async fn creation_bond_is_returned_after_resolution() {
+    const BOND: u64 = 50_000_000;
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    let creator = mxe.payer().pubkey();
+    set_technology_bond(&mut mxe, BOND).await;
+
+    let market = create_market(&mut mxe, &oracle).await;
+    assert_eq!(market.creation_bond, BOND);
+    let market_account = mxe
+        .context
+        .banks_client
+        .get_account(market_address())
+        .await
+        .unwrap()
+        .unwrap();
+    let rent = mxe
+        .context
+        .banks_client
+        .get_rent()
+        .await
+        .unwrap()
+        .minimum_balance(market_account.data.len());
+    assert_eq!(balance(&mut mxe, market_address()).await, rent + BOND);
+
+    let reclaim = program_ix(
+        prediction_markets::accounts::ReclaimCreationBond {
+            creator,
+            prediction_market: market_address(),
+            program_config: program_config_address(),
+        },
+        prediction_markets::instruction::ReclaimCreationBond {},
+    );
+    let error = mxe
+        .process(&[reclaim.clone()], &[])
+        .await
+        .err()
+        .expect("reclaim before resolution");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::MarketNotResolved));
+
+    resolve(&mut mxe, &oracle, true).await;
+    let before = balance(&mut mxe, creator).await;
+    mxe.context.get_new_latest_blockhash().await.unwrap();
+    mxe.process(&[reclaim], &[]).await.expect("reclaim bond");
+
+    let market: PredictionMarket = mxe.account(market_address()).await;
+    assert_eq!(market.creation_bond, 0);
+    assert_eq!(balance(&mut mxe, market_address()).await, rent);
+    // Less the transaction fee
+    assert!(balance(&mut mxe, creator).await > before + BOND - 100_000);
+}
+
+#[tokio::test]
+async fn cancelled_market_slashes_the_bond() {
+    const BOND: u64 = 50_000_000;
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    let dispute_winner = Pubkey::new_unique();
+    set_technology_bond(&mut mxe, BOND).await;
+    create_market(&mut mxe, &oracle).await;
+
+    let cancel = |oracle: Pubkey| {
+        program_ix(
+            prediction_markets::accounts::CancelMarket {
+                oracle,
+                prediction_market: market_address(),
+                treasury: treasury_address(),
+                dispute_winner: Some(dispute_winner),
+            },
+            prediction_markets::instruction::CancelMarket {},
+        )
+    };
+    let stranger = Keypair::new();
+    let error = mxe
+        .process(&[cancel(stranger.pubkey())], &[&stranger])
+        .await
+        .err()
+        .expect("cancel by someone other than the oracle");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::InvalidOracle));
+    mxe.process(&[cancel(oracle.pubkey())], &[&oracle])
+        .await
+        .expect("cancel market");
+
+    let market: PredictionMarket = mxe.account(market_address()).await;
+    assert!(market.status == MarketStatus::Cancelled);
+    assert_eq!(market.creation_bond, 0);
+    assert_eq!(balance(&mut mxe, dispute_winner).await, BOND);
+
+    // Cancelled markets take no more votes
+    let voter = new_voter(&mut mxe, 0, 1, 1_000, 70, 500).await;
+    let submit = submit_vote_ix(&mut mxe, &voter).await;
+    let error = mxe
+        .process(&[submit], &[&voter.keypair])
+        .await
+        .err()
+        .expect("vote on a cancelled market");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::MarketNotActive));
+}
+
+#[tokio::test]
+async fn slashed_bonds_are_kept_apart_from_fees() {
+    const BOND: u64 = 50_000_000;
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    let admin = mxe.payer().pubkey();
+    set_technology_bond(&mut mxe, BOND).await;
+    create_market(&mut mxe, &oracle).await;
+
+    // With no dispute winner the bond goes to the treasury, not its fees
+    let cancel = program_ix(
+        prediction_markets::accounts::CancelMarket {
+            oracle: oracle.pubkey(),
+            prediction_market: market_address(),
+            treasury: treasury_address(),
+            dispute_winner: None,
+        },
+        prediction_markets::instruction::CancelMarket {},
+    );
+    let before = balance(&mut mxe, treasury_address()).await;
+    mxe.process(&[cancel], &[&oracle]).await.expect("cancel market");
+    assert_eq!(balance(&mut mxe, treasury_address()).await, before + BOND);
+    let treasury: Treasury = mxe.account(treasury_address()).await;
+    assert_eq!(treasury.slashed_bonds_accrued, BOND);
+    assert_eq!(treasury.protocol_fees_accrued, 0);
+
+    // A version 1 treasury counted the bond as protocol fees; migrating splits it out
+    let mut account = mxe
+        .context
+        .banks_client
+        .get_account(treasury_address())
+        .await
+        .unwrap()
+        .unwrap();
+    account.data.truncate(Treasury::LEN - 8);
+    account.data[8] = 1;
+    account.data[10..18].copy_from_slice(&BOND.to_le_bytes());
+    mxe.context
+        .set_account(&treasury_address(), &AccountSharedData::from(account));
+    let migrate = migrate_ix(
+        admin,
+        treasury_address(),
+        prediction_markets::instruction::MigrateTreasury {},
+    );
+    mxe.process(&[migrate], &[]).await.expect("migrate treasury");
+    let treasury: Treasury = mxe.account(treasury_address()).await;
+    assert_eq!(treasury.version, Treasury::VERSION);
+    assert_eq!(treasury.slashed_bonds_accrued, BOND);
+    assert_eq!(treasury.protocol_fees_accrued, 0);
+    assert_eq!(treasury.total_slashed_bonds, BOND);
+
+    let recipient = Pubkey::new_unique();
+    let withdraw = |admin: Pubkey, amount: u64| {
+        program_ix(
+            prediction_markets::accounts::WithdrawSlashedBonds {
+                admin,
+                program_config: program_config_address(),
+                treasury: treasury_address(),
+                recipient,
+            },
+            prediction_markets::instruction::WithdrawSlashedBonds { amount },
+        )
+    };
+    let stranger = Keypair::new();
+    let error = mxe
+        .process(&[withdraw(stranger.pubkey(), BOND)], &[&stranger])
+        .await
+        .err()
+        .expect("withdraw by someone other than the admin");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::Unauthorized));
+    let error = mxe
+        .process(&[withdraw(admin, BOND + 1)], &[])
+        .await
+        .err()
+        .expect("withdraw more than was slashed");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::InsufficientSlashedBonds));
+    mxe.process(&[withdraw(admin, BOND)], &[])
+        .await
+        .expect("withdraw slashed bonds");
+    assert_eq!(balance(&mut mxe, recipient).await, BOND);
+    let treasury: Treasury = mxe.account(treasury_address()).await;
+    assert_eq!(treasury.slashed_bonds_accrued, 0);
+}
+
+#[tokio::test]
+async fn metadata_can_change_until_the_first_vote() {
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    let market = create_market(&mut mxe, &oracle).await;
+    assert_eq!(market.metadata_uri, METADATA_URI);
+    assert_eq!(market.metadata_hash, market_metadata().content_hash());
+
+    let mut revised = market_metadata();
+    revised.resolution_criteria.push_str(", checked by the test harness");
+    revised.validate().expect("revised metadata is valid");
+    assert_ne!(revised.content_hash(), market.metadata_hash);
+
+    let update = |metadata_uri: &str, metadata_hash: [u8; 32]| {
+        program_ix(
+            prediction_markets::accounts::UpdateMarketMetadata {
+                creator: mxe.payer().pubkey(),
+                prediction_market: market_address(),
+                vote_accumulator: vote_accumulator_address(),
+            },
+            prediction_markets::instruction::UpdateMarketMetadata {
+                metadata_uri: metadata_uri.to_string(),
+                metadata_hash,
+            },
+        )
+    };
+    let revised_uri = "ipfs://bafy-revised-market-1";
+    let first_update = update(revised_uri, revised.content_hash());
+    let second_update = update(METADATA_URI, market_metadata().content_hash());
+    mxe.process(&[first_update], &[]).await.expect("update metadata");
+
+    let market: PredictionMarket = mxe.account(market_address()).await;
+    assert_eq!(market.metadata_uri, revised_uri);
+    assert_eq!(market.metadata_hash, revised.content_hash());
+
+    join_market(&mut mxe, 0, 1, 1_000, 70, 500).await;
+    let error = mxe
+        .process(&[second_update], &[])
+        .await
+        .err()
+        .expect("update after the first vote");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::MetadataLocked));
+}
+
+#[test]
+fn metadata_hash_covers_the_canonical_encoding() {
+    let metadata = MarketMetadata {
+        title: "Say \"yes\"".to_string(),
+        description: "Line one\nline two".to_string(),
+        resolution_criteria: "Oracle reads C:\\results".to_string(),
+        image: "https://example.com/market.png".to_string(),
+    };
+    metadata.validate().expect("valid metadata");
+    let json = concat!(
+        r#"{"version":1,"title":"Say \"yes\"","description":"Line one\nline two","#,
+        r#""resolution_criteria":"Oracle reads C:\\results","#,
+        r#""image":"https://example.com/market.png"}"#,
+    );
+    assert_eq!(metadata.canonical_json(), json);
+    assert_eq!(metadata.content_hash(), hashv(&[json.as_bytes()]).to_bytes());
+
+    let untitled = MarketMetadata {
+        title: " ".to_string(),
+        ..metadata.clone()
+    };
+    assert!(untitled.validate().is_err());
+    let insecure_image = MarketMetadata {
+        image: "http://example.com/market.png".to_string(),
+        ..metadata
+    };
+    assert!(insecure_image.validate().is_err());
+}
+
+#[tokio::test]
+async fn markets_get_sequential_ids_and_are_indexed() {
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    let creator = mxe.payer().pubkey();
+
+    let first = create_market(&mut mxe, &oracle).await;
+    assert_eq!(first.market_id, MARKET_ID);
+    let second = create_market_ix(&mut mxe, &oracle, true, 0, PrivacyMode::Private, None).await;
+    mxe.process(&[second], &[]).await.expect("create second market");
+    let second: PredictionMarket = mxe.account(market_address_for(MARKET_ID + 1)).await;
+    assert_eq!(second.market_id, MARKET_ID + 1);
+
+    let registry: MarketRegistry = mxe.account(market_registry_address()).await;
+    assert_eq!(registry.next_market_id, MARKET_ID + 2);
+    assert_eq!(registry.category_counts[MarketCategory::Technology.slot()], 2);
+    assert_eq!(registry.category_counts[MarketCategory::Sports.slot()], 0);
+
+    let listed = vec![market_address(), market_address_for(MARKET_ID + 1)];
+    let category_page: MarketIndexPage = mxe
+        .account(category_index_address(MarketCategory::Technology, 0))
+        .await;
+    assert_eq!(category_page.page, 0);
+    assert_eq!(category_page.markets, listed);
+
+    let creator_markets: CreatorMarkets = mxe.account(creator_markets_address(&creator)).await;
+    assert_eq!(creator_markets.creator, creator);
+    assert_eq!(creator_markets.market_count, 2);
+    let creator_page: MarketIndexPage = mxe.account(creator_index_address(&creator, 0)).await;
+    assert_eq!(creator_page.markets, listed);
+}
+
+/// Rewrite an account in its pre-versioning layout: no version byte, old size
+async fn downgrade_to_legacy(mxe: &mut MockMxe, address: Pubkey, legacy_len: usize) {
+    let mut account = mxe
+        .context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .unwrap();
+    account.data.remove(8);
+    account.data.resize(legacy_len, 0);
+    mxe.context.set_account(&address, &AccountSharedData::from(account));
+}
+
+/// Where version 2 put privacy_mode and the parent fields in an unresolved market;
+/// every field before them is fixed-size
+const MARKET_V2_FIELDS_AT: usize = 8 + 2 + 17 * 32 + 16 + 8 + 32 + 2 + 3 * 8 + 1 + 32 + 1 + 2;
+
+/// Settle an unresolved market on the version 1 circuits, as markets created before
+/// the vote accumulator do
+async fn pin_market_to_circuit_v1(mxe: &mut MockMxe) {
+    let mut account = mxe
+        .context
+        .banks_client
+        .get_account(market_address())
+        .await
+        .unwrap()
+        .unwrap();
+    // circuit_version follows privacy_mode, parent_market (None) and parent_outcome
+    account.data[MARKET_V2_FIELDS_AT + 3] = 1;
+    mxe.context.set_account(&market_address(), &AccountSharedData::from(account));
+}
+
+/// Rewrite an account in its version 1 layout: drop the `written` bytes version 2
+/// added at `offset`, and the `space` reserved for them
+async fn downgrade_to_v1(
+    mxe: &mut MockMxe,
+    address: Pubkey,
+    offset: usize,
+    written: usize,
+    space: usize,
+) {
+    let mut account = mxe
+        .context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .unwrap();
+    let v1_len = account.data.len() - space;
+    account.data[8] = 1;
+    account.data.drain(offset..offset + written);
+    account.data.resize(v1_len, 0);
+    mxe.context.set_account(&address, &AccountSharedData::from(account));
+}
+
+/// Version 2 market fields: privacy_mode, parent_market (None) and parent_outcome,
+/// then final_odds_locked after odds_updated_at
+async fn downgrade_market_to_v1(mxe: &mut MockMxe) {
+    let final_odds_locked_at = MARKET_V2_FIELDS_AT + 3 + 1 + 4 + 32 + 4 + 2 * 2 + 6 * 8 + 4 + 3 + 8;
+    downgrade_to_v1(mxe, market_address(), final_odds_locked_at, 1, 1).await;
+    downgrade_to_v1(mxe, market_address(), MARKET_V2_FIELDS_AT, 1 + 1 + 1, 1 + 33 + 1).await;
+}
+
+/// Version 2 position field: is_public, kept last
+async fn downgrade_position_to_v1(mxe: &mut MockMxe, position: Pubkey) {
+    downgrade_to_v1(mxe, position, UserPosition::LEN - 1, 1, 1).await;
+}
+
+/// Version 2 profile field: parlay_count, kept last
+async fn downgrade_profile_to_v1(mxe: &mut MockMxe, profile: Pubkey) {
+    downgrade_to_v1(mxe, profile, UserProfile::LEN - 4, 4, 4).await;
+}
+
+fn migrate_ix(payer: Pubkey, account: Pubkey, data: impl InstructionData) -> Instruction {
+    program_ix(
+        prediction_markets::accounts::MigrateAccount {
+            payer,
+            account,
+            system_program: system_program::ID,
+        },
+        data,
+    )
+}
+
+async fn data_len(mxe: &mut MockMxe, address: Pubkey) -> usize {
+    mxe.context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .unwrap()
+        .data
+        .len()
+}
+
+#[tokio::test]
+async fn legacy_accounts_migrate_in_place() {
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    let payer = mxe.payer().pubkey();
+    let market = create_market_with(&mut mxe, &oracle, true, 0).await;
+    let voter = join_market(&mut mxe, 0, 1, 3_000, 80, 700).await;
+    let position: UserPosition = mxe.account(voter.position).await;
+    assert_eq!(market.version, PredictionMarket::VERSION);
+    assert_eq!(position.version, UserPosition::VERSION);
+
+    downgrade_market_to_v1(&mut mxe).await;
+    downgrade_to_legacy(&mut mxe, market_address(), LEGACY_MARKET_LEN).await;
+    downgrade_position_to_v1(&mut mxe, voter.position).await;
+    downgrade_to_legacy(&mut mxe, voter.position, LEGACY_POSITION_LEN).await;
+    downgrade_profile_to_v1(&mut mxe, voter.profile).await;
+    downgrade_to_legacy(&mut mxe, voter.profile, LEGACY_PROFILE_LEN).await;
+
+    let migrate_market = migrate_ix(
+        payer,
+        market_address(),
+        prediction_markets::instruction::MigrateMarket {},
+    );
+    mxe.process(&[migrate_market.clone()], &[])
+        .await
+        .expect("migrate market");
+    assert_eq!(data_len(&mut mxe, market_address()).await, PredictionMarket::LEN);
+    let migrated: PredictionMarket = mxe.account(market_address()).await;
+    assert_eq!(migrated.version, PredictionMarket::VERSION);
+    assert_eq!(migrated.market_id, market.market_id);
+    assert_eq!(migrated.creator, market.creator);
+    assert_eq!(migrated.encrypted_vote_state, market.encrypted_vote_state);
+    assert_eq!(migrated.metadata_uri, market.metadata_uri);
+
+    mxe.context.get_new_latest_blockhash().await.unwrap();
+    let error = mxe
+        .process(&[migrate_market], &[])
+        .await
+        .err()
+        .expect("migrate twice");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::AccountAlreadyMigrated));
+
+    // Handlers refuse the old position layout until it is migrated
+    resolve(&mut mxe, &oracle, true).await;
+    let reveal = reveal_positions_ix(&mut mxe, std::slice::from_ref(&voter)).await;
+    let error = mxe
+        .process(&[reveal], &[])
+        .await
+        .err()
+        .expect("reveal a legacy position");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::AccountNotMigrated));
+
+    let migrate_position = migrate_ix(
+        payer,
+        voter.position,
+        prediction_markets::instruction::MigratePosition {},
+    );
+    let migrate_profile = migrate_ix(
+        payer,
+        voter.profile,
+        prediction_markets::instruction::MigrateProfile {},
+    );
+    mxe.process(&[migrate_position, migrate_profile], &[])
+        .await
+        .expect("migrate position and profile");
+    assert_eq!(data_len(&mut mxe, voter.position).await, UserPosition::LEN);
+    assert_eq!(data_len(&mut mxe, voter.profile).await, UserProfile::LEN);
+    let profile: UserProfile = mxe.account(voter.profile).await;
+    assert_eq!(profile.version, UserProfile::VERSION);
+    assert_eq!(profile.user, voter.keypair.pubkey());
+
+    // The sealed vote survived the move and still decrypts
+    let reveal = reveal_positions_ix(&mut mxe, std::slice::from_ref(&voter)).await;
+    mxe.process_and_compute(&[reveal], &[])
+        .await
+        .expect("reveal migrated position");
+    let revealed: UserPosition = mxe.account(voter.position).await;
+    assert_eq!(revealed.version, UserPosition::VERSION);
+    assert_eq!(revealed.stake_amount, position.stake_amount);
+    assert!(revealed.is_revealed);
+    assert_eq!(revealed.revealed_vote_choice, voter.vote.vote_choice);
+    assert_eq!(revealed.revealed_conviction, voter.vote.conviction_score);
+}
+
+#[tokio::test]
+async fn v1_accounts_upgrade_to_v2() {
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    let payer = mxe.payer().pubkey();
+    let market = create_market_with(&mut mxe, &oracle, false, 0).await;
+    let voter = join_market(&mut mxe, 0, 1, 3_000, 80, 700).await;
+    let position: UserPosition = mxe.account(voter.position).await;
+    let profile: UserProfile = mxe.account(voter.profile).await;
+
+    downgrade_market_to_v1(&mut mxe).await;
+    downgrade_position_to_v1(&mut mxe, voter.position).await;
+    downgrade_profile_to_v1(&mut mxe, voter.profile).await;
+    assert_eq!(
+        data_len(&mut mxe, market_address()).await,
+        PredictionMarket::LEN - (1 + 33 + 1)
+    );
+
+    let migrate_market = migrate_ix(
+        payer,
+        market_address(),
+        prediction_markets::instruction::MigrateMarket {},
+    );
+    mxe.process(&[migrate_market.clone()], &[])
+        .await
+        .expect("migrate v1 market");
+    assert_eq!(data_len(&mut mxe, market_address()).await, PredictionMarket::LEN);
+    let migrated: PredictionMarket = mxe.account(market_address()).await;
+    assert_eq!(migrated.version, PredictionMarket::VERSION);
+    assert!(migrated.privacy_mode == PrivacyMode::Private);
+    assert_eq!(migrated.parent_market, None);
+    assert!(!migrated.parent_outcome);
+    assert_eq!(migrated.market_id, market.market_id);
+    assert_eq!(migrated.circuit_version, market.circuit_version);
+    assert_eq!(migrated.encrypted_vote_state, market.encrypted_vote_state);
+    assert_eq!(migrated.metadata_uri, market.metadata_uri);
+
+    mxe.context.get_new_latest_blockhash().await.unwrap();
+    let error = mxe
+        .process(&[migrate_market], &[])
+        .await
+        .err()
+        .expect("migrate twice");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::AccountAlreadyMigrated));
+
+    let migrate_position = migrate_ix(
+        payer,
+        voter.position,
+        prediction_markets::instruction::MigratePosition {},
+    );
+    mxe.process(&[migrate_position], &[])
+        .await
+        .expect("migrate v1 position");
+    assert_eq!(data_len(&mut mxe, voter.position).await, UserPosition::LEN);
+    let migrated: UserPosition = mxe.account(voter.position).await;
+    assert_eq!(migrated.version, UserPosition::VERSION);
+    assert!(!migrated.is_public);
+    assert_eq!(migrated.stake_amount, position.stake_amount);
+    assert_eq!(migrated.encrypted_vote_data, position.encrypted_vote_data);
+    assert_eq!(migrated.vote_root, position.vote_root);
+    assert_eq!(migrated.is_aggregated, position.is_aggregated);
+
+    let migrate_profile = migrate_ix(
+        payer,
+        voter.profile,
+        prediction_markets::instruction::MigrateProfile {},
+    );
+    mxe.process(&[migrate_profile], &[])
+        .await
+        .expect("migrate v1 profile");
+    assert_eq!(data_len(&mut mxe, voter.profile).await, UserProfile::LEN);
+    let migrated: UserProfile = mxe.account(voter.profile).await;
+    assert_eq!(migrated.version, UserProfile::VERSION);
+    assert_eq!(migrated.parlay_count, 0);
+    assert_eq!(migrated.user, voter.keypair.pubkey());
+    assert_eq!(migrated.leaderboard_season, profile.leaderboard_season);
+
+    // A migrated market can be the parent of a conditional market
+    let create_child = create_market_ix(
+        &mut mxe,
+        &oracle,
+        false,
+        0,
+        PrivacyMode::Private,
+        Some((market_address(), true)),
+    )
+    .await;
+    mxe.process(&[create_child], &[])
+        .await
+        .expect("create a market conditioned on a migrated one");
+    let child: PredictionMarket = mxe.account(market_address_for(MARKET_ID + 1)).await;
+    assert_eq!(child.parent_market, Some(market_address()));
+    assert!(child.parent_outcome);
+}
+
+fn public_tally_address() -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"public_tally", market_address().as_ref()],
+        &prediction_markets::ID,
+    )
+    .0
+}
+
+/// Fund a voter, create their profile and vote in the clear
+async fn join_public_market(
+    mxe: &mut MockMxe,
+    index: u8,
+    vote_choice: u8,
+    stake_amount: u64,
+    predicted_probability: u8,
+    conviction_score: u16,
+) -> Voter {
+    let voter = new_voter(
+        mxe,
+        index,
+        vote_choice,
+        stake_amount,
+        predicted_probability,
+        conviction_score,
+    )
+    .await;
+    let submit = program_ix(
+        prediction_markets::accounts::SubmitPublicVote {
+            user: voter.keypair.pubkey(),
+            prediction_market: market_address(),
+            user_position: voter.position,
+            user_profile: voter.profile,
+            public_tally: public_tally_address(),
+            program_config: program_config_address(),
+            system_program: system_program::ID,
+        },
+        prediction_markets::instruction::SubmitPublicVote {
+            vote_choice,
+            stake_amount,
+            predicted_probability,
+            conviction_score,
+        },
+    );
+    let queued = mxe
+        .process(&[submit], &[&voter.keypair])
+        .await
+        .expect("submit public vote");
+    assert!(queued.is_empty(), "public votes never reach the MXE");
+
+    voter
+}
+
+fn claim_public_ix(voter: &Voter) -> Instruction {
+    program_ix(
+        prediction_markets::accounts::ClaimPublicPayout {
+            user: voter.keypair.pubkey(),
+            prediction_market: market_address(),
+            user_position: voter.position,
+            user_profile: voter.profile,
+            public_tally: public_tally_address(),
+            program_config: program_config_address(),
+        },
+        prediction_markets::instruction::ClaimPublicPayout {},
+    )
+}
+
+#[tokio::test]
+async fn public_market_settles_without_the_mxe() {
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    let create = create_market_ix(&mut mxe, &oracle, false, 0, PrivacyMode::Public, None).await;
+    mxe.process(&[create], &[]).await.expect("create public market");
+
+    let voters = vec![
+        join_public_market(&mut mxe, 0, 1, 3_000, 80, 700).await,
+        join_public_market(&mut mxe, 1, 0, 1_500, 35, 250).await,
+    ];
+
+    // Votes are public and tallied as the circuits would
+    let market: PredictionMarket = mxe.account(market_address()).await;
+    let mut expected = MarketVotingState {
+        market_id: MARKET_ID,
+        ..Default::default()
+    };
+    for voter in &voters {
+        let position: UserPosition = mxe.account(voter.position).await;
+        assert!(position.is_revealed);
+        assert_eq!(position.revealed_vote_choice, voter.vote.vote_choice);
+        let vote = VoteData {
+            timestamp: position.timestamp as u64,
+            ..voter.vote.clone()
+        };
+        expected = circuits_reference::aggregate_market_votes(
+            &vote,
+            position.timestamp as u64,
+            market.created_at as u64,
+            market.voting_ends_at as u64,
+            market.time_decay_enabled,
+            &expected,
+        );
+    }
+    assert_eq!(market.yes_stake, 3_000);
+    assert_eq!(market.no_stake, 1_500);
+    assert_eq!(market.participant_count, 2);
+    let odds = circuits_reference::calculate_market_odds(&expected);
+    assert_eq!((market.odds_yes, market.odds_no), (odds.0, odds.1));
+    let tally: PublicTally = mxe.account(public_tally_address()).await;
+    assert_eq!(tally.total_yes_stake, expected.total_yes_stake);
+    assert_eq!(tally.brier_score_sum_yes, expected.brier_score_sum_yes);
+    assert_eq!(tally.conviction_weighted_no, expected.conviction_weighted_no);
+
+    // The encrypted path is closed to public markets
+    let private_voter = new_voter(&mut mxe, 2, 1, 1_000, 60, 400).await;
+    let submit = submit_vote_ix(&mut mxe, &private_voter).await;
+    let error = mxe
+        .process(&[submit], &[&private_voter.keypair])
+        .await
+        .err()
+        .expect("encrypted vote on a public market");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::PrivacyModeMismatch));
+
+    // A stake that would take the market past MAX_STAKE is turned away
+    let whale = new_voter(&mut mxe, 3, 1, MAX_STAKE, 90, 1000).await;
+    let submit = program_ix(
+        prediction_markets::accounts::SubmitPublicVote {
+            user: whale.keypair.pubkey(),
+            prediction_market: market_address(),
+            user_position: whale.position,
+            user_profile: whale.profile,
+            public_tally: public_tally_address(),
+            program_config: program_config_address(),
+            system_program: system_program::ID,
+        },
+        prediction_markets::instruction::SubmitPublicVote {
+            vote_choice: 1,
+            stake_amount: MAX_STAKE,
+            predicted_probability: 90,
+            conviction_score: 1000,
+        },
+    );
+    let error = mxe
+        .process(&[submit], &[&whale.keypair])
+        .await
+        .err()
+        .expect("stake past the market's limit");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::StakeLimitExceeded));
+
+    resolve(&mut mxe, &oracle, true).await;
+    let market: PredictionMarket = mxe.account(market_address()).await;
+
+    for voter in &voters {
+        mxe.process(&[claim_public_ix(voter)], &[&voter.keypair])
+            .await
+            .expect("claim public payout");
+
+        let position: UserPosition = mxe.account(voter.position).await;
+        let payout = circuits_reference::calculate_payout_after_fees(
+            &PayoutData {
+                user: [0; 32],
+                market_id: MARKET_ID,
+                user_stake: voter.vote.stake_amount,
+                user_vote: voter.vote.vote_choice,
+                user_probability: voter.vote.predicted_probability,
+                user_conviction: voter.vote.conviction_score,
+                user_timestamp: position.timestamp as u64,
+                market_outcome: 1,
+                accuracy_bonus: market.total_stake / 100,
+                conviction_bonus: market.total_stake / 200,
+            },
+            market.fee_amount,
+            market.created_at as u64,
+            market.voting_ends_at as u64,
+            market.time_decay_enabled,
+            &expected,
+        );
+        assert!(position.is_claimed);
+        assert_eq!(position.payout_amount, payout);
+    }
+
+    let winner: UserProfile = mxe.account(voters[0].profile).await;
+    let winner_payout = mxe.account::<UserPosition>(voters[0].position).await.payout_amount;
+    assert!(winner_payout > 3_000);
+    assert_eq!(winner.correct_predictions, 1);
+    assert_eq!(winner.streak_current, 1);
+    assert_eq!(winner.total_winnings, winner_payout);
+    let loser: UserProfile = mxe.account(voters[1].profile).await;
+    assert_eq!(loser.correct_predictions, 0);
+    assert_eq!(loser.reputation_score, 980);
+
+    mxe.context.get_new_latest_blockhash().await.unwrap();
+    let error = mxe
+        .process(&[claim_public_ix(&voters[0])], &[&voters[0].keypair])
+        .await
+        .err()
+        .expect("claim twice");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::PayoutAlreadyClaimed));
+}
+
+/// The program's plaintext tally is a copy of the circuits' math; keep it in step
+/// with circuits-reference
+#[test]
+fn program_tally_matches_the_reference() {
+    use prediction_markets::tally;
+
+    let (created_at, voting_ends_at) = (1_000, 1_000 + VOTING_PERIOD as u64);
+    // (choice, stake, probability, conviction, timestamp); the last one is late
+    let votes = [
+        (1, 2_000, 80, 900, 1_000),
+        (0, 500, 30, 200, 1_900),
+        (1, 1_500, 55, 400, 2_500),
+        (2, 700, 50, 100, 3_000),
+        (0, 3_000, 10, 1_000, 4_000),
+        (1, 900, 95, 700, 9_000),
+    ];
+
+    let mut state = tally::MarketVotingState::default();
+    let mut expected = MarketVotingState::default();
+    let mut pairs = Vec::new();
+    for (choice, stake, probability, conviction, timestamp) in votes {
+        let vote = tally::VoteData {
+            vote_choice: choice,
+            stake_amount: stake,
+            predicted_probability: probability,
+            conviction_score: conviction,
+            timestamp,
+            ..Default::default()
+        };
+        let reference = VoteData {
+            vote_choice: choice,
+            stake_amount: stake,
+            predicted_probability: probability,
+            conviction_score: conviction,
+            timestamp,
+            ..Default::default()
+        };
+        state = tally::aggregate_market_votes(
+            &vote, timestamp, created_at, voting_ends_at, true, &state,
+        );
+        expected = circuits_reference::aggregate_market_votes(
+            &reference, timestamp, created_at, voting_ends_at, true, &expected,
+        );
+        assert_eq!(
+            tally::calculate_market_odds(&state),
+            circuits_reference::calculate_market_odds(&expected)
+        );
+        pairs.push((vote, reference));
+    }
+
+    for outcome in [0, 1] {
+        for (vote, reference) in &pairs {
+            let payout = tally::calculate_payout_after_fees(
+                &tally::PayoutData {
+                    user_stake: vote.stake_amount,
+                    user_vote: vote.vote_choice,
+                    user_probability: vote.predicted_probability,
+                    user_conviction: vote.conviction_score,
+                    user_timestamp: vote.timestamp,
+                    market_outcome: outcome,
+                    accuracy_bonus: 86,
+                    conviction_bonus: 43,
+                    ..Default::default()
+                },
+                120,
+                created_at,
+                voting_ends_at,
+                true,
+                &state,
+            );
+            let expected_payout = circuits_reference::calculate_payout_after_fees(
+                &PayoutData {
+                    user_stake: reference.stake_amount,
+                    user_vote: reference.vote_choice,
+                    user_probability: reference.predicted_probability,
+                    user_conviction: reference.conviction_score,
+                    user_timestamp: reference.timestamp,
+                    market_outcome: outcome,
+                    accuracy_bonus: 86,
+                    conviction_bonus: 43,
+                    ..Default::default()
+                },
+                120,
+                created_at,
+                voting_ends_at,
+                true,
+                &expected,
+            );
+            assert_eq!(payout, expected_payout);
+
+            let reputation = tally::update_reputation(vote, payout, outcome, None);
+            let expected_reputation =
+                circuits_reference::update_reputation(reference, payout, outcome, None);
+            assert_eq!(reputation.correct_predictions, expected_reputation.correct_predictions);
+            assert_eq!(reputation.total_winnings, expected_reputation.total_winnings);
+            assert_eq!(reputation.streak_best, expected_reputation.streak_best);
+            assert_eq!(reputation.reputation_score, expected_reputation.reputation_score);
+        }
+    }
+}
+
+async fn calculate_hybrid_payout(mxe: &mut MockMxe, voter: &Voter) {
+    let (computation_offset, arcium) = mxe.queue_accounts(Circuit::CalculatePayoutHybrid).await;
+    let payout = program_ix(
+        prediction_markets::accounts::CalculateUserPayoutHybrid {
+            user: voter.keypair.pubkey(),
+            sign_pda_account: arcium.sign_pda_account,
+            mxe_account: arcium.mxe_account,
+            mempool_account: arcium.mempool_account,
+            executing_pool: arcium.executing_pool,
+            computation_account: arcium.computation_account,
+            comp_def_account: arcium.comp_def_account,
+            cluster_account: arcium.cluster_account,
+            pool_account: arcium.pool_account,
+            clock_account: arcium.clock_account,
+            system_program: system_program::ID,
+            arcium_program: arcium.arcium_program,
+            computation_allocator: arcium.computation_allocator,
+            computation_record: arcium.computation_record,
+            prediction_market: market_address(),
+            user_position: voter.position,
+            public_tally: public_tally_address(),
+            program_config: program_config_address(),
+        },
+        prediction_markets::instruction::CalculateUserPayoutHybrid { computation_offset },
+    );
+    mxe.process_and_compute(&[payout], &[&voter.keypair])
+        .await
+        .expect("calculate hybrid payout");
+}
+
+async fn calculate_public_payout(mxe: &mut MockMxe, voter: &Voter) {
+    let (computation_offset, arcium) = mxe.queue_accounts(Circuit::CalculatePublicPayout).await;
+    let payout = program_ix(
+        prediction_markets::accounts::CalculatePublicPayout {
+            user: voter.keypair.pubkey(),
+            sign_pda_account: arcium.sign_pda_account,
+            mxe_account: arcium.mxe_account,
+            mempool_account: arcium.mempool_account,
+            executing_pool: arcium.executing_pool,
+            computation_account: arcium.computation_account,
+            comp_def_account: arcium.comp_def_account,
+            cluster_account: arcium.cluster_account,
+            pool_account: arcium.pool_account,
+            clock_account: arcium.clock_account,
+            system_program: system_program::ID,
+            arcium_program: arcium.arcium_program,
+            computation_allocator: arcium.computation_allocator,
+            computation_record: arcium.computation_record,
+            prediction_market: market_address(),
+            user_position: voter.position,
+            public_tally: public_tally_address(),
+            program_config: program_config_address(),
+        },
+        prediction_markets::instruction::CalculatePublicPayout { computation_offset },
+    );
+    mxe.process_and_compute(&[payout], &[&voter.keypair])
+        .await
+        .expect("calculate public payout");
+}
+
+#[tokio::test]
+async fn hybrid_market_pays_both_pools_as_one() {
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    let create = create_market_ix(&mut mxe, &oracle, false, 0, PrivacyMode::Hybrid, None).await;
+    mxe.process(&[create], &[]).await.expect("create hybrid market");
+
+    let sealed_voters = vec![
+        join_market(&mut mxe, 0, 1, 3_000, 80, 700).await,
+        join_market(&mut mxe, 2, 0, 2_000, 30, 500).await,
+    ];
+    for voter in &sealed_voters {
+        aggregate(&mut mxe, voter).await;
+    }
+    let public_voters = vec![
+        join_public_market(&mut mxe, 1, 1, 1_000, 60, 300).await,
+        join_public_market(&mut mxe, 3, 0, 1_500, 35, 250).await,
+    ];
+
+    // Each position records how it voted; only public stakes are in the clear
+    let market: PredictionMarket = mxe.account(market_address()).await;
+    assert_eq!(market.total_stake, 7_500);
+    assert_eq!(market.yes_stake, 1_000);
+    assert_eq!(market.no_stake, 1_500);
+    assert!(!mxe.account::<UserPosition>(sealed_voters[0].position).await.is_public);
+    assert!(mxe.account::<UserPosition>(public_voters[0].position).await.is_public);
+
+    // The whole market folded as one pool, in voting order; a public vote is
+    // timestamped by the program
+    let mut votes: Vec<VoteData> = sealed_voters.iter().map(|voter| voter.vote.clone()).collect();
+    for voter in &public_voters {
+        let position: UserPosition = mxe.account(voter.position).await;
+        votes.push(VoteData {
+            timestamp: position.timestamp as u64,
+            ..voter.vote.clone()
+        });
+    }
+    let fold = |votes: &[VoteData]| {
+        votes.iter().fold(
+            MarketVotingState {
+                market_id: MARKET_ID,
+                ..Default::default()
+            },
+            |state, vote| {
+                circuits_reference::aggregate_market_votes(
+                    vote,
+                    vote.timestamp,
+                    market.created_at as u64,
+                    market.voting_ends_at as u64,
+                    market.time_decay_enabled,
+                    &state,
+                )
+            },
+        )
+    };
+    let sealed = fold(&votes[..2]);
+    let pool = PublicPool::from(&fold(&votes[2..]));
+    let combined = fold(&votes);
+
+    let (computation_offset, arcium) = mxe.queue_accounts(Circuit::CalculateMarketOddsHybrid).await;
+    let odds = program_ix(
+        prediction_markets::accounts::CalculateMarketOddsHybrid {
+            payer: mxe.payer().pubkey(),
+            sign_pda_account: arcium.sign_pda_account,
+            mxe_account: arcium.mxe_account,
+            mempool_account: arcium.mempool_account,
+            executing_pool: arcium.executing_pool,
+            computation_account: arcium.computation_account,
+            comp_def_account: arcium.comp_def_account,
+            cluster_account: arcium.cluster_account,
+            pool_account: arcium.pool_account,
+            clock_account: arcium.clock_account,
+            system_program: system_program::ID,
+            arcium_program: arcium.arcium_program,
+            computation_allocator: arcium.computation_allocator,
+            computation_record: arcium.computation_record,
+            prediction_market: market_address(),
+            public_tally: public_tally_address(),
+        },
+        prediction_markets::instruction::CalculateMarketOddsHybrid { computation_offset },
+    );
+    mxe.process_and_compute(&[odds], &[]).await.expect("hybrid odds");
+    let market: PredictionMarket = mxe.account(market_address()).await;
+    let (yes, no, participants, _, _) = circuits_reference::calculate_market_odds(&combined);
+    assert_eq!((market.odds_yes, market.odds_no), (yes, no));
+    assert_eq!(participants, 4);
+    assert_eq!(
+        circuits_reference::calculate_market_odds_hybrid(&sealed, &pool),
+        circuits_reference::calculate_market_odds(&combined)
+    );
+
+    resolve(&mut mxe, &oracle, true).await;
+    let market: PredictionMarket = mxe.account(market_address()).await;
+    let expected_payout = |vote: &VoteData| {
+        circuits_reference::calculate_payout_after_fees(
+            &PayoutData {
+                user: [0; 32],
+                market_id: MARKET_ID,
+                user_stake: vote.stake_amount,
+                user_vote: vote.vote_choice,
+                user_probability: vote.predicted_probability,
+                user_conviction: vote.conviction_score,
+                user_timestamp: vote.timestamp,
+                market_outcome: 1,
+                accuracy_bonus: market.total_stake / 100,
+                conviction_bonus: market.total_stake / 200,
+            },
+            market.fee_amount,
+            market.created_at as u64,
+            market.voting_ends_at as u64,
+            market.time_decay_enabled,
+            &combined,
+        )
+    };
+
+    // Each kind of position settles only through its own path
+    let error = mxe
+        .process(&[claim_ix(&public_voters[0])], &[&public_voters[0].keypair])
+        .await
+        .err()
+        .expect("encrypted claim of a public position");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::PrivacyModeMismatch));
+    let error = mxe
+        .process(&[claim_public_ix(&public_voters[0])], &[&public_voters[0].keypair])
+        .await
+        .err()
+        .expect("claim before the MXE computed the payout");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::PayoutNotCalculated));
+
+    let mut total_paid = 0;
+    for (voter, vote) in sealed_voters.iter().zip(&votes[..2]) {
+        calculate_hybrid_payout(&mut mxe, voter).await;
+        record_reputation(&mut mxe, voter).await;
+        mxe.process(&[claim_ix(voter)], &[&voter.keypair])
+            .await
+            .expect("claim payout");
+
+        let position: UserPosition = mxe.account(voter.position).await;
+        let payout = mxe
+            .shared_cipher(&voter.client_pubkey)
+            .decrypt(&[position.encrypted_payout], position.payout_nonce)[0] as u64;
+        assert_eq!(payout, expected_payout(vote));
+        total_paid += payout;
+    }
+    for (voter, vote) in public_voters.iter().zip(&votes[2..]) {
+        calculate_public_payout(&mut mxe, voter).await;
+        mxe.context.get_new_latest_blockhash().await.unwrap();
+        mxe.process(&[claim_public_ix(voter)], &[&voter.keypair])
+            .await
+            .expect("claim public payout");
+
+        let position: UserPosition = mxe.account(voter.position).await;
+        assert!(position.is_claimed);
+        assert_eq!(position.payout_amount, expected_payout(vote));
+        total_paid += position.payout_amount;
+    }
+    assert!(total_paid <= market.total_stake);
+
+    let public_winner: UserProfile = mxe.account(public_voters[0].profile).await;
+    assert_eq!(public_winner.correct_predictions, 1);
+}
+
+/// Vote in the clear on any public market; returns the position
+async fn vote_public_on(mxe: &mut MockMxe, voter: &Voter, market: Pubkey) -> Pubkey {
+    let user = voter.keypair.pubkey();
+    let position = Pubkey::find_program_address(
+        &[b"position", user.as_ref(), market.as_ref()],
+        &prediction_markets::ID,
+    )
+    .0;
+    let submit = program_ix(
+        prediction_markets::accounts::SubmitPublicVote {
+            user,
+            prediction_market: market,
+            user_position: position,
+            user_profile: voter.profile,
+            public_tally: Pubkey::find_program_address(
+                &[b"public_tally", market.as_ref()],
+                &prediction_markets::ID,
+            )
+            .0,
+            program_config: program_config_address(),
+            system_program: system_program::ID,
+        },
+        prediction_markets::instruction::SubmitPublicVote {
+            vote_choice: voter.vote.vote_choice,
+            stake_amount: voter.vote.stake_amount,
+            predicted_probability: voter.vote.predicted_probability,
+            conviction_score: voter.vote.conviction_score,
+        },
+    );
+    mxe.process(&[submit], &[&voter.keypair])
+        .await
+        .expect("submit public vote");
+
+    position
+}
+
+fn claim_refund_ix(
+    voter: &Voter,
+    market: Pubkey,
+    position: Pubkey,
+    parent_market: Option<Pubkey>,
+) -> Instruction {
+    program_ix(
+        prediction_markets::accounts::ClaimRefund {
+            user: voter.keypair.pubkey(),
+            prediction_market: market,
+            user_position: position,
+            program_config: program_config_address(),
+            parent_market,
+        },
+        prediction_markets::instruction::ClaimRefund {},
+    )
+}
+
+#[tokio::test]
+async fn conditional_markets_follow_their_parent() {
+    const BOND: u64 = 50_000_000;
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    let creator = mxe.payer().pubkey();
+    set_technology_bond(&mut mxe, BOND).await;
+    create_market(&mut mxe, &oracle).await;
+    let parent = market_address();
+
+    // "If the parent resolves Yes, ..." and "If the parent resolves No, ..."
+    let if_yes = market_address_for(2);
+    let if_no = market_address_for(3);
+    for parent_outcome in [true, false] {
+        let condition = Some((parent, parent_outcome));
+        let create =
+            create_market_ix(&mut mxe, &oracle, false, 0, PrivacyMode::Public, condition).await;
+        mxe.process(&[create], &[]).await.expect("create conditional market");
+    }
+    let market: PredictionMarket = mxe.account(if_no).await;
+    assert_eq!(market.parent_market, Some(parent));
+    assert!(!market.parent_outcome);
+
+    let voter = new_voter(&mut mxe, 0, 1, 2_000, 70, 500).await;
+    let if_yes_position = vote_public_on(&mut mxe, &voter, if_yes).await;
+    let if_no_position = vote_public_on(&mut mxe, &voter, if_no).await;
+
+    // Neither child can settle before the parent
+    let error = mxe
+        .process(&[resolve_ix(&oracle, if_yes, Some(parent), true)], &[&oracle])
+        .await
+        .err()
+        .expect("resolve before the parent");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::ParentMarketUnresolved));
+    let cancel = |market: Pubkey| {
+        program_ix(
+            prediction_markets::accounts::CancelConditionalMarket {
+                prediction_market: market,
+                parent_market: parent,
+            },
+            prediction_markets::instruction::CancelConditionalMarket {},
+        )
+    };
+    let error = mxe
+        .process(&[cancel(if_no)], &[])
+        .await
+        .err()
+        .expect("cancel before the parent");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::ParentMarketUnresolved));
+
+    resolve(&mut mxe, &oracle, true).await;
+
+    // The child whose condition held resolves as usual and can't be refunded
+    let error = mxe
+        .process(&[resolve_ix(&oracle, if_yes, None, true)], &[&oracle])
+        .await
+        .err()
+        .expect("resolve without the parent");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::InvalidParentMarket));
+    let error = mxe
+        .process(&[cancel(if_yes)], &[])
+        .await
+        .err()
+        .expect("cancel a market whose condition held");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::ParentConditionMet));
+    mxe.context.get_new_latest_blockhash().await.unwrap();
+    mxe.process(&[resolve_ix(&oracle, if_yes, Some(parent), true)], &[&oracle])
+        .await
+        .expect("resolve conditional market");
+    let refund = claim_refund_ix(&voter, if_yes, if_yes_position, Some(parent));
+    let error = mxe
+        .process(&[refund], &[&voter.keypair])
+        .await
+        .err()
+        .expect("refund from a resolved market");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::MarketNotCancelled));
+
+    // The other can't resolve; the first refund claim cancels it
+    let error = mxe
+        .process(&[resolve_ix(&oracle, if_no, Some(parent), true)], &[&oracle])
+        .await
+        .err()
+        .expect("resolve against the parent");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::ParentConditionFailed));
+    mxe.process(&[claim_refund_ix(&voter, if_no, if_no_position, Some(parent))], &[&voter.keypair])
+        .await
+        .expect("claim refund");
+
+    let market: PredictionMarket = mxe.account(if_no).await;
+    assert!(market.status == MarketStatus::Cancelled);
+    let position: UserPosition = mxe.account(if_no_position).await;
+    assert!(position.is_claimed);
+    assert_eq!(position.payout_amount, 2_000);
+
+    mxe.context.get_new_latest_blockhash().await.unwrap();
+    let error = mxe
+        .process(&[claim_refund_ix(&voter, if_no, if_no_position, None)], &[&voter.keypair])
+        .await
+        .err()
+        .expect("refund twice");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::PayoutAlreadyClaimed));
+
+    // Nothing was wrong with the question, so the bond goes back to the creator
+    let reclaim = program_ix(
+        prediction_markets::accounts::ReclaimCreationBond {
+            creator,
+            prediction_market: if_no,
+            program_config: program_config_address(),
+        },
+        prediction_markets::instruction::ReclaimCreationBond {},
+    );
+    let before = balance(&mut mxe, creator).await;
+    mxe.process(&[reclaim], &[]).await.expect("reclaim bond");
+    assert_eq!(mxe.account::<PredictionMarket>(if_no).await.creation_bond, 0);
+    assert!(balance(&mut mxe, creator).await > before + BOND - 100_000);
+}
+
+fn parlay_address(user: &Pubkey, parlay_id: u32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"parlay", user.as_ref(), parlay_id.to_le_bytes().as_ref()],
+        &prediction_markets::ID,
+    )
+    .0
+}
+
+/// Pass the leg markets as remaining accounts, in the parlay's order
+fn with_legs(mut ix: Instruction, legs: &[Pubkey]) -> Instruction {
+    ix.accounts
+        .extend(legs.iter().map(|market| AccountMeta::new_readonly(*market, false)));
+    ix
+}
+
+async fn create_parlay(
+    mxe: &mut MockMxe,
+    voter: &Voter,
+    legs: &[Pubkey],
+    picks: &ParlayChoices,
+    stake_amount: u64,
+) -> Pubkey {
+    let (parlay, create) = create_parlay_ix(mxe, voter, legs, picks, stake_amount).await;
+    mxe.process(&[create], &[&voter.keypair])
+        .await
+        .expect("create parlay");
+
+    parlay
+}
+
+async fn create_parlay_ix(
+    mxe: &mut MockMxe,
+    voter: &Voter,
+    legs: &[Pubkey],
+    picks: &ParlayChoices,
+    stake_amount: u64,
+) -> (Pubkey, Instruction) {
+    let user = voter.keypair.pubkey();
+    let profile: UserProfile = mxe.account(voter.profile).await;
+    let parlay = parlay_address(&user, profile.parlay_count);
+    let ciphertexts = mxe
+        .shared_cipher(&voter.client_pubkey)
+        .encrypt(&picks.choices.map(u128::from), voter.encryption_nonce);
+    let create = program_ix(
+        prediction_markets::accounts::CreateParlay {
+            user,
+            parlay,
+            parlay_pool: parlay_pool_address(),
+            user_profile: voter.profile,
+            mxe_key_epoch: mxe_key_epoch_address(),
+            program_config: program_config_address(),
+            system_program: system_program::ID,
+        },
+        prediction_markets::instruction::CreateParlay {
+            choices_encrypted: ciphertexts.try_into().unwrap(),
+            choices_encryption_pubkey: voter.client_pubkey,
+            choices_nonce: voter.encryption_nonce,
+            stake_amount,
+        },
+    );
+    (parlay, with_legs(create, legs))
+}
+
+async fn settle_parlay_ix(
+    mxe: &mut MockMxe,
+    voter: &Voter,
+    parlay: Pubkey,
+    legs: &[Pubkey],
+) -> Instruction {
+    let (computation_offset, arcium) = mxe.queue_accounts(Circuit::CalculateParlayPayout).await;
+    let settle = program_ix(
+        prediction_markets::accounts::SettleParlay {
+            user: voter.keypair.pubkey(),
+            sign_pda_account: arcium.sign_pda_account,
+            mxe_account: arcium.mxe_account,
+            mempool_account: arcium.mempool_account,
+            executing_pool: arcium.executing_pool,
+            computation_account: arcium.computation_account,
+            comp_def_account: arcium.comp_def_account,
+            cluster_account: arcium.cluster_account,
+            pool_account: arcium.pool_account,
+            clock_account: arcium.clock_account,
+            system_program: system_program::ID,
+            arcium_program: arcium.arcium_program,
+            parlay,
+            parlay_pool: parlay_pool_address(),
+            mxe_key_epoch: mxe_key_epoch_address(),
+            program_config: program_config_address(),
+            computation_allocator: arcium.computation_allocator,
+            computation_record: arcium.computation_record,
+        },
+        prediction_markets::instruction::SettleParlay { computation_offset },
+    );
+    with_legs(settle, legs)
+}
+
+async fn parlay_payout(mxe: &mut MockMxe, parlay: Pubkey) -> u64 {
+    let parlay: Parlay = mxe.account(parlay).await;
+    assert!(parlay.is_settled);
+    parlay.payout_amount
+}
+
+fn claim_parlay_ix(voter: &Voter, parlay: Pubkey) -> Instruction {
+    program_ix(
+        prediction_markets::accounts::ClaimParlay {
+            user: voter.keypair.pubkey(),
+            parlay,
+            parlay_pool: parlay_pool_address(),
+            program_config: program_config_address(),
+        },
+        prediction_markets::instruction::ClaimParlay {},
+    )
+}
+
+async fn fund_parlay_pool(mxe: &mut MockMxe, amount: u64) {
+    let fund = program_ix(
+        prediction_markets::accounts::FundParlayPool {
+            funder: mxe.payer().pubkey(),
+            parlay_pool: parlay_pool_address(),
+            system_program: system_program::ID,
+        },
+        prediction_markets::instruction::FundParlayPool { amount },
+    );
+    mxe.process(&[fund], &[]).await.expect("fund parlay pool");
+}
+
+async fn calculate_odds_ix(mxe: &mut MockMxe, market: Pubkey) -> Instruction {
+    let (computation_offset, arcium) = mxe.queue_accounts(Circuit::CalculateMarketOdds).await;
+    program_ix(
+        prediction_markets::accounts::CalculateMarketOdds {
+            payer: mxe.payer().pubkey(),
+            sign_pda_account: arcium.sign_pda_account,
+            mxe_account: arcium.mxe_account,
+            mempool_account: arcium.mempool_account,
+            executing_pool: arcium.executing_pool,
+            computation_account: arcium.computation_account,
+            comp_def_account: arcium.comp_def_account,
+            cluster_account: arcium.cluster_account,
+            pool_account: arcium.pool_account,
+            clock_account: arcium.clock_account,
+            system_program: system_program::ID,
+            arcium_program: arcium.arcium_program,
+            computation_allocator: arcium.computation_allocator,
+            computation_record: arcium.computation_record,
+            prediction_market: market,
+        },
+        prediction_markets::instruction::CalculateMarketOdds { computation_offset },
+    )
+}
+
+#[tokio::test]
+async fn final_odds_lock_once_the_market_resolves() {
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    create_market(&mut mxe, &oracle).await;
+    join_market(&mut mxe, 0, 1, 1_000, 70, 400).await;
+
+    // Odds queued while the market still took votes don't count as final
+    let odds = calculate_odds_ix(&mut mxe, market_address()).await;
+    let mut early = mxe.process(&[odds], &[]).await.unwrap();
+    mxe.process(&[resolve_ix(&oracle, market_address(), None, true)], &[&oracle])
+        .await
+        .expect("resolve market");
+    assert!(!mxe.account::<PredictionMarket>(market_address()).await.final_odds_locked);
+
+    let odds = calculate_odds_ix(&mut mxe, market_address()).await;
+    mxe.process_and_compute(&[odds], &[])
+        .await
+        .expect("reveal final odds");
+    let market: PredictionMarket = mxe.account(market_address()).await;
+    assert!(market.final_odds_locked);
+
+    // The earlier computation lands late but can't replace them
+    let offset = early[0].computation_offset;
+    mxe.compute(early.remove(0)).await.expect("late odds callback");
+    let record: ComputationRecord = mxe.account(computation_record_address(offset)).await;
+    assert!(record.status == ComputationStatus::Failed);
+    let locked: PredictionMarket = mxe.account(market_address()).await;
+    assert_eq!(locked.odds_updated_at, market.odds_updated_at);
+
+    let odds = calculate_odds_ix(&mut mxe, market_address()).await;
+    let error = mxe
+        .process(&[odds], &[])
+        .await
+        .err()
+        .expect("recompute locked odds");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::FinalOddsLocked));
+
+    // Every vote moves a public market's odds, so they lock when it resolves
+    let create = create_market_ix(&mut mxe, &oracle, false, 0, PrivacyMode::Public, None).await;
+    mxe.process(&[create], &[]).await.expect("create public market");
+    let public = market_address_for(MARKET_ID + 1);
+    mxe.process(&[resolve_ix(&oracle, public, None, false)], &[&oracle])
+        .await
+        .expect("resolve public market");
+    assert!(mxe.account::<PredictionMarket>(public).await.final_odds_locked);
+}
+
+#[tokio::test]
+async fn parlay_settles_once_every_leg_resolves() {
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    for _ in 0..3 {
+        let create =
+            create_market_ix(&mut mxe, &oracle, false, 0, PrivacyMode::Private, None).await;
+        mxe.process(&[create], &[]).await.expect("create market");
+    }
+    let legs = [1, 2, 3].map(market_address_for);
+    let voter = new_voter(&mut mxe, 0, 1, 0, 0, 0).await;
+    // Enough to cover both parlays at the payout cap
+    fund_parlay_pool(&mut mxe, 200_000).await;
+
+    let winner = ParlayChoices { choices: [1, 0, 1, 0] };
+    let (_, create) = create_parlay_ix(&mut mxe, &voter, &[legs[0], legs[0]], &winner, 1_000).await;
+    let error = mxe
+        .process(&[create], &[&voter.keypair])
+        .await
+        .err()
+        .expect("parlay on the same market twice");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::InvalidParlayLegs));
+
+    // Yes on the first, No on the second, Yes on the third
+    let winning = create_parlay(&mut mxe, &voter, &legs, &winner, 1_000).await;
+    let loser = ParlayChoices { choices: [1, 1, 0, 0] };
+    let losing = create_parlay(&mut mxe, &voter, &legs[..2], &loser, 1_000).await;
+    let parlay: Parlay = mxe.account(winning).await;
+    assert_eq!(parlay.leg_count, 3);
+    assert_eq!(parlay.markets[..3], legs);
+    assert_eq!(mxe.account::<UserProfile>(voter.profile).await.parlay_count, 2);
+
+    let settle = settle_parlay_ix(&mut mxe, &voter, winning, &legs).await;
+    let error = mxe
+        .process(&[settle], &[&voter.keypair])
+        .await
+        .err()
+        .expect("settle before the legs resolve");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::ParlayLegsUnresolved));
+
+    // The third market is cancelled, so its leg is void
+    mxe.process(&[resolve_ix(&oracle, legs[0], None, true)], &[&oracle])
+        .await
+        .expect("resolve first leg");
+    mxe.process(&[resolve_ix(&oracle, legs[1], None, false)], &[&oracle])
+        .await
+        .expect("resolve second leg");
+    let cancel = program_ix(
+        prediction_markets::accounts::CancelMarket {
+            oracle: oracle.pubkey(),
+            prediction_market: legs[2],
+            treasury: treasury_address(),
+            dispute_winner: None,
+        },
+        prediction_markets::instruction::CancelMarket {},
+    );
+    mxe.process(&[cancel], &[&oracle]).await.expect("cancel third leg");
+
+    // The resolved legs' odds haven't been revealed since, so they aren't locked
+    let settle = settle_parlay_ix(&mut mxe, &voter, winning, &legs).await;
+    let error = mxe
+        .process(&[settle], &[&voter.keypair])
+        .await
+        .err()
+        .expect("settle on stale odds");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::ParlayOddsStale));
+    for leg in &legs[..2] {
+        let odds = calculate_odds_ix(&mut mxe, *leg).await;
+        mxe.process_and_compute(&[odds], &[])
+            .await
+            .expect("reveal final odds");
+    }
+
+    let settle = settle_parlay_ix(&mut mxe, &voter, winning, &[legs[1], legs[0], legs[2]]).await;
+    let error = mxe
+        .process(&[settle], &[&voter.keypair])
+        .await
+        .err()
+        .expect("settle with the legs out of order");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::InvalidParlayLegs));
+
+    mxe.context.get_new_latest_blockhash().await.unwrap();
+    let settle = settle_parlay_ix(&mut mxe, &voter, winning, &legs).await;
+    let mut queued = mxe.process(&[settle], &[&voter.keypair]).await.unwrap();
+    let again = settle_parlay_ix(&mut mxe, &voter, winning, &legs).await;
+    let error = mxe
+        .process(&[again], &[&voter.keypair])
+        .await
+        .err()
+        .expect("settle while a settlement is queued");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::ParlaySettlementPending));
+    mxe.compute(queued.remove(0)).await.expect("settle winning parlay");
+    // Neither resolved leg took a vote, so both are priced at 50% and each doubles the stake
+    let expected = circuits_reference::calculate_parlay_payout(
+        &winner,
+        1_000,
+        &[1, 0, PARLAY_LEG_VOID, PARLAY_LEG_VOID],
+        &[50; 4],
+        &[50; 4],
+    );
+    assert_eq!(expected, 4_000);
+    assert_eq!(parlay_payout(&mut mxe, winning).await, expected);
+
+    let settle = settle_parlay_ix(&mut mxe, &voter, winning, &legs).await;
+    let error = mxe
+        .process(&[settle], &[&voter.keypair])
+        .await
+        .err()
+        .expect("settle twice");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::ParlayAlreadySettled));
+
+    let settle = settle_parlay_ix(&mut mxe, &voter, losing, &legs[..2]).await;
+    mxe.process_and_compute(&[settle], &[&voter.keypair])
+        .await
+        .expect("settle losing parlay");
+    assert_eq!(parlay_payout(&mut mxe, losing).await, 0);
+
+    // Settling released everything but the winning payout; claiming pays it out
+    let pool: ParlayPool = mxe.account(parlay_pool_address()).await;
+    assert_eq!(pool.reserved, 4_000);
+    let before = balance(&mut mxe, parlay_pool_address()).await;
+    mxe.process(&[claim_parlay_ix(&voter, winning)], &[&voter.keypair])
+        .await
+        .expect("claim winning parlay");
+    assert_eq!(balance(&mut mxe, parlay_pool_address()).await, before - 4_000);
+    assert!(mxe.account::<Parlay>(winning).await.is_claimed);
+    let pool: ParlayPool = mxe.account(parlay_pool_address()).await;
+    assert_eq!(pool.reserved, 0);
+
+    let error = mxe
+        .process(&[claim_parlay_ix(&voter, winning)], &[&voter.keypair])
+        .await
+        .err()
+        .expect("claim twice");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::PayoutAlreadyClaimed));
+}
+
+#[tokio::test]
+async fn parlays_are_escrowed_in_a_funded_pool() {
+    let mut mxe = MockMxe::start().await;
+    let oracle = Keypair::new();
+    create_market(&mut mxe, &oracle).await;
+    let voter = new_voter(&mut mxe, 0, 1, 0, 0, 0).await;
+    let picks = ParlayChoices { choices: [1, 0, 0, 0] };
+
+    // An empty pool can't cover a 100x payout
+    let (_, create) = create_parlay_ix(&mut mxe, &voter, &[market_address()], &picks, 1_000).await;
+    let error = mxe
+        .process(&[create], &[&voter.keypair])
+        .await
+        .err()
+        .expect("parlay the pool can't cover");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::ParlayPoolUnderfunded));
+
+    fund_parlay_pool(&mut mxe, 99_000).await;
+    let before = balance(&mut mxe, parlay_pool_address()).await;
+    let parlay = create_parlay(&mut mxe, &voter, &[market_address()], &picks, 1_000).await;
+    assert_eq!(balance(&mut mxe, parlay_pool_address()).await, before + 1_000);
+    let pool: ParlayPool = mxe.account(parlay_pool_address()).await;
+    assert_eq!(pool.reserved, 100_000);
+
+    let error = mxe
+        .process(&[claim_parlay_ix(&voter, parlay)], &[&voter.keypair])
+        .await
+        .err()
+        .expect("claim before settling");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::ParlayNotSettled));
+
+    // Reserved lamports can't be withdrawn
+    let recipient = Pubkey::new_unique();
+    let withdraw = program_ix(
+        prediction_markets::accounts::WithdrawParlayPool {
+            admin: mxe.payer().pubkey(),
+            program_config: program_config_address(),
+            parlay_pool: parlay_pool_address(),
+            recipient,
+        },
+        prediction_markets::instruction::WithdrawParlayPool { amount: 1 },
+    );
+    let error = mxe
+        .process(&[withdraw], &[])
+        .await
+        .err()
+        .expect("withdraw a reserved lamport");
+    assert_eq!(custom_error(error), u32::from(ErrorCode::ParlayPoolUnderfunded));
+
+    // A failed settlement can be queued again
+    mxe.process(&[resolve_ix(&oracle, market_address(), None, true)], &[&oracle])
+        .await
+        .expect("resolve market");
+    let odds = calculate_odds_ix(&mut mxe, market_address()).await;
+    mxe.process_and_compute(&[odds], &[])
+        .await
+        .expect("reveal final odds");
+    let settle = settle_parlay_ix(&mut mxe, &voter, parlay, &[market_address()]).await;
+    let mut queued = mxe.process(&[settle], &[&voter.keypair]).await.unwrap();
+    mxe.abort(queued.remove(0)).await.expect("abort settlement");
+    assert!(!mxe.account::<Parlay>(parlay).await.is_settling);
+    let settle = settle_parlay_ix(&mut mxe, &voter, parlay, &[market_address()]).await;
+    mxe.process_and_compute(&[settle], &[&voter.keypair])
+        .await
+        .expect("settle parlay");
+
+    // Priced at 50% with no votes, so the stake doubles
+    assert_eq!(parlay_payout(&mut mxe, parlay).await, 2_000);
+    let pool: ParlayPool = mxe.account(parlay_pool_address()).await;
+    assert_eq!(pool.reserved, 2_000);
+    mxe.process(&[claim_parlay_ix(&voter, parlay)], &[&voter.keypair])
+        .await
+        .expect("claim parlay");
+}
@@ -0,0 +1,1518 @@
+//! In-process mock of the Arcium MXE for program tests.
+//!
+//! The prediction_markets program runs natively under `solana-program-test`, next to a
+//! mock Arcium program registered at the real Arcium program id. The mock accepts
+//! `queue_computation` CPIs and logs the queued request; `MockMxe` picks those up
+//! from the transaction logs, decrypts the arguments with a test key, evaluates the
+//! circuit with `circuits-reference`, encrypts the result and calls the program's
+//! callback through the mock, so the callback sees an Arcium top-level instruction
+//! exactly as it would on a live cluster.
+//!
+//! Encryption is a stand-in: each field is XORed with `sha256(key || nonce)`.
+//! Shared ciphertexts use `sha256(mxe key || client pubkey)` as the key, so tests can
+//! encrypt votes and decrypt payouts with `MockMxe::shared_cipher`. The keystream
+//! doesn't depend on a field's position, so the vote ciphertexts the program forwards
+//! to `calculate_payout_v1` in PayoutData order decrypt as they would field by field.
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use anchor_lang::{
+    AnchorDeserialize, AnchorSerialize, Discriminator, Id, InstructionData, ToAccountMetas,
+};
+use arcium_anchor::prelude::*;
+use arcium_client::idl::arcium::client::args::QueueComputation;
+use circuits_reference::{
+    LeaderboardScores, MarketVotingState, ParlayChoices, PayoutData, PositionVote, PublicPool,
+    ReputationState, VoteData, LEADERBOARD_SIZE, MAX_PARLAY_LEGS, REENCRYPT_BATCH_SIZE,
+    REVEAL_BATCH_SIZE,
+};
+use prediction_markets::{ComputationAllocator, ID};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::{Account as SolanaAccount, AccountSharedData},
+    account_info::AccountInfo,
+    bpf_loader_upgradeable,
+    entrypoint::ProgramResult,
+    hash::hashv,
+    instruction::{AccountMeta, Instruction},
+    msg,
+    program::invoke,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    sysvar,
+    transaction::Transaction,
+};
+
+/// Cluster the mock MXE account points at
+const CLUSTER_OFFSET: u32 = 0;
+
+/// Key the mock MXE "encrypts" sealed state with
+const MXE_TEST_KEY: [u8; 32] = [7; 32];
+
+/// Prefix of the log line the mock Arcium program writes for each queued computation
+const QUEUE_LOG_PREFIX: &str = "mock-arcium queue_computation ";
+
+/// Instruction tag telling the mock Arcium program to forward a callback
+const CALLBACK_TAG: &[u8; 8] = b"mock-cb\0";
+
+// =====================================================================
+// TEST CIPHER
+// =====================================================================
+
+/// Deterministic stand-in for the Rescue cipher used by Arcium
+#[derive(Clone, Copy)]
+pub struct TestCipher {
+    key: [u8; 32],
+}
+
+impl TestCipher {
+    fn keystream(&self, nonce: u128) -> [u8; 32] {
+        hashv(&[&self.key, &nonce.to_le_bytes()]).to_bytes()
+    }
+
+    pub fn encrypt(&self, values: &[u128], nonce: u128) -> Vec<[u8; 32]> {
+        let stream = self.keystream(nonce);
+        values
+            .iter()
+            .map(|value| {
+                let mut block = [0u8; 32];
+                block[..16].copy_from_slice(&value.to_le_bytes());
+                for (byte, key) in block.iter_mut().zip(stream) {
+                    *byte ^= key;
+                }
+                block
+            })
+            .collect()
+    }
+
+    /// Cipher this (MXE) key shares with a client x25519 public key
+    fn shared(&self, client_pubkey: &[u8; 32]) -> TestCipher {
+        TestCipher {
+            key: hashv(&[&self.key, client_pubkey]).to_bytes(),
+        }
+    }
+
+    pub fn decrypt(&self, ciphertexts: &[[u8; 32]], nonce: u128) -> Vec<u128> {
+        let stream = self.keystream(nonce);
+        ciphertexts
+            .iter()
+            .map(|ciphertext| {
+                let mut value = [0u8; 16];
+                for (i, byte) in value.iter_mut().enumerate() {
+                    *byte = ciphertext[i] ^ stream[i];
+                }
+                u128::from_le_bytes(value)
+            })
+            .collect()
+    }
+}
+
+// =====================================================================
+// CIRCUITS
+// =====================================================================
+
+/// Computation definitions the program queues, by circuit name
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Circuit {
+    SubmitPrivateVote,
+    AggregateMarketVotes,
+    AggregateMarketVotesV2,
+    CalculatePayout,
+    CalculatePayoutV2,
+    CalculateMarketOdds,
+    UpdateReputation,
+    InsertLeaderboardEntry,
+    RevealLeaderboard,
+    ReencryptMarketState,
+    ReencryptPositions,
+    DiscloseVote,
+    RevealVotes,
+    CalculatePayoutHybrid,
+    CalculatePublicPayout,
+    CalculateMarketOddsHybrid,
+    CalculateParlayPayout,
+}
+
+impl Circuit {
+    pub const ALL: [Circuit; 17] = [
+        Circuit::SubmitPrivateVote,
+        Circuit::AggregateMarketVotes,
+        Circuit::AggregateMarketVotesV2,
+        Circuit::CalculatePayout,
+        Circuit::CalculatePayoutV2,
+        Circuit::CalculateMarketOdds,
+        Circuit::UpdateReputation,
+        Circuit::InsertLeaderboardEntry,
+        Circuit::RevealLeaderboard,
+        Circuit::ReencryptMarketState,
+        Circuit::ReencryptPositions,
+        Circuit::DiscloseVote,
+        Circuit::RevealVotes,
+        Circuit::CalculatePayoutHybrid,
+        Circuit::CalculatePublicPayout,
+        Circuit::CalculateMarketOddsHybrid,
+        Circuit::CalculateParlayPayout,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Circuit::SubmitPrivateVote => "submit_private_vote_v1",
+            Circuit::AggregateMarketVotes => "aggregate_market_votes_v1",
+            Circuit::AggregateMarketVotesV2 => "aggregate_market_votes_v2",
+            Circuit::CalculatePayout => "calculate_payout_v1",
+            Circuit::CalculatePayoutV2 => "calculate_payout_v2",
+            Circuit::CalculateMarketOdds => "calculate_market_odds_v1",
+            Circuit::UpdateReputation => "update_reputation_v1",
+            Circuit::InsertLeaderboardEntry => "insert_leaderboard_entry",
+            Circuit::RevealLeaderboard => "reveal_leaderboard",
+            Circuit::ReencryptMarketState => "reencrypt_market_state_v1",
+            Circuit::ReencryptPositions => "reencrypt_positions_v1",
+            Circuit::DiscloseVote => "disclose_vote_v1",
+            Circuit::RevealVotes => "reveal_votes_v1",
+            Circuit::CalculatePayoutHybrid => "calculate_payout_hybrid_v1",
+            Circuit::CalculatePublicPayout => "calculate_public_payout_v1",
+            Circuit::CalculateMarketOddsHybrid => "calculate_market_odds_hybrid_v1",
+            Circuit::CalculateParlayPayout => "calculate_parlay_payout_v1",
+        }
+    }
+
+    pub fn offset(self) -> u32 {
+        comp_def_offset(self.name())
+    }
+
+    fn from_offset(offset: u32) -> Option<Circuit> {
+        Circuit::ALL.into_iter().find(|circuit| circuit.offset() == offset)
+    }
+}
+
+/// A `queue_computation` call recorded by the mock Arcium program
+pub struct QueuedComputation {
+    pub circuit: Circuit,
+    pub computation_offset: u64,
+    pub args: Vec<Argument>,
+    pub callback_accounts: Vec<AccountMeta>,
+}
+
+/// Arcium accounts every `queue_computation` instruction takes
+pub struct QueueAccounts {
+    pub sign_pda_account: Pubkey,
+    pub mxe_account: Pubkey,
+    pub mempool_account: Pubkey,
+    pub executing_pool: Pubkey,
+    pub computation_account: Pubkey,
+    pub comp_def_account: Pubkey,
+    pub cluster_account: Pubkey,
+    pub pool_account: Pubkey,
+    pub clock_account: Pubkey,
+    pub arcium_program: Pubkey,
+    pub computation_allocator: Pubkey,
+    pub computation_record: Pubkey,
+}
+
+// =====================================================================
+// PROGRAM ENTRYPOINTS
+// =====================================================================
+
+fn prediction_markets_entry(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    // Anchor's entry wants the account slice to live as long as the accounts themselves
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    prediction_markets::entry(program_id, accounts, data)
+}
+
+fn mock_arcium_entry(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    if let Some(callback_data) = data.strip_prefix(&CALLBACK_TAG[..]) {
+        // accounts[0] is the program to call back, the rest are its instruction accounts
+        let (target, forwarded) = accounts
+            .split_first()
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let callback = Instruction {
+            program_id: *target.key,
+            accounts: forwarded
+                .iter()
+                .map(|account| AccountMeta {
+                    pubkey: *account.key,
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data: callback_data.to_vec(),
+        };
+        return invoke(&callback, accounts);
+    }
+
+    if data.starts_with(QueueComputation::DISCRIMINATOR) {
+        msg!("{}{}", QUEUE_LOG_PREFIX, to_hex(data));
+    }
+
+    // init_computation_definition and fee bookkeeping have nothing to emulate
+    Ok(())
+}
+
+// =====================================================================
+// MOCK MXE
+// =====================================================================
+
+pub struct MockMxe {
+    pub context: ProgramTestContext,
+    cipher: TestCipher,
+    previous_cipher: Option<TestCipher>, // Key before the last rotate_key
+    cluster_account: Pubkey,
+    next_output_nonce: u128,
+}
+
+impl MockMxe {
+    /// Start a bank with the program, the mock Arcium program and initialized
+    /// computation definitions for every circuit
+    pub async fn start() -> Self {
+        let mut program_test = ProgramTest::new(
+            "prediction_markets",
+            prediction_markets::ID,
+            processor!(prediction_markets_entry),
+        );
+        program_test.prefer_bpf(false);
+        program_test.add_program("arcium", Arcium::id(), processor!(mock_arcium_entry));
+
+        let mut mxe_account = zeroed::<MXEAccount>();
+        mxe_account.cluster = Some(CLUSTER_OFFSET);
+        let cluster_account = derive_cluster_pda!(mxe_account);
+
+        add_arcium_account(&mut program_test, derive_mxe_pda!(), &mxe_account);
+        add_arcium_account(&mut program_test, cluster_account, &zeroed::<Cluster>());
+        add_arcium_account(
+            &mut program_test,
+            ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+            &zeroed::<FeePool>(),
+        );
+        add_arcium_account(
+            &mut program_test,
+            ARCIUM_CLOCK_ACCOUNT_ADDRESS,
+            &zeroed::<ClockAccount>(),
+        );
+        for circuit in Circuit::ALL {
+            add_arcium_account(
+                &mut program_test,
+                derive_comp_def_pda!(circuit.offset()),
+                &zeroed::<ComputationDefinitionAccount>(),
+            );
+        }
+
+        let mut mxe = MockMxe {
+            context: program_test.start_with_context().await,
+            cipher: TestCipher { key: MXE_TEST_KEY },
+            previous_cipher: None,
+            cluster_account,
+            next_output_nonce: 1,
+        };
+        mxe.set_upgrade_authority();
+
+        let initialize_allocator = Instruction {
+            program_id: prediction_markets::ID,
+            accounts: prediction_markets::accounts::InitializeComputationAllocator {
+                payer: mxe.context.payer.pubkey(),
+                computation_allocator: computation_allocator_address(),
+                program_config: program_config_address(),
+                system_program: solana_sdk::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: prediction_markets::instruction::InitializeComputationAllocator {
+                first_offset: 1,
+            }
+            .data(),
+        };
+        let initialize_key_epoch = Instruction {
+            program_id: prediction_markets::ID,
+            accounts: prediction_markets::accounts::InitializeMxeKeyEpoch {
+                admin: mxe.context.payer.pubkey(),
+                mxe_key_epoch: mxe_key_epoch_address(),
+                program_config: program_config_address(),
+                system_program: solana_sdk::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: prediction_markets::instruction::InitializeMxeKeyEpoch {}.data(),
+        };
+        // No fees until a test sets them with update_fee_config
+        let initialize_config = Instruction {
+            program_id: prediction_markets::ID,
+            accounts: prediction_markets::accounts::InitializeProgramConfig {
+                admin: mxe.context.payer.pubkey(),
+                program_data: program_data_address(),
+                program_config: program_config_address(),
+                treasury: treasury_address(),
+                system_program: solana_sdk::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: prediction_markets::instruction::InitializeProgramConfig {
+                protocol_fee_bps: 0,
+                max_creator_fee_bps: 0,
+            }
+            .data(),
+        };
+        let initialize_registry = Instruction {
+            program_id: prediction_markets::ID,
+            accounts: prediction_markets::accounts::InitializeMarketRegistry {
+                payer: mxe.context.payer.pubkey(),
+                market_registry: market_registry_address(),
+                program_config: program_config_address(),
+                system_program: solana_sdk::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: prediction_markets::instruction::InitializeMarketRegistry {
+                first_market_id: 1,
+            }
+            .data(),
+        };
+        // Empty until a test funds it
+        let initialize_parlay_pool = Instruction {
+            program_id: prediction_markets::ID,
+            accounts: prediction_markets::accounts::InitializeParlayPool {
+                payer: mxe.context.payer.pubkey(),
+                parlay_pool: parlay_pool_address(),
+                program_config: program_config_address(),
+                system_program: solana_sdk::system_program::ID,
+            }
+            .to_account_metas(None),
+            data: prediction_markets::instruction::InitializeParlayPool {}.data(),
+        };
+        // The config goes first: its admin creates every other singleton
+        mxe.process(
+            &[
+                initialize_config,
+                initialize_allocator,
+                initialize_key_epoch,
+                initialize_registry,
+                initialize_parlay_pool,
+            ],
+            &[],
+        )
+        .await
+        .expect("initialize program config and the other singletons");
+
+        mxe
+    }
+
+    /// Give the program a ProgramData account naming the payer as upgrade authority,
+    /// as `anchor deploy` would. The test program runs natively, so it has none
+    fn set_upgrade_authority(&mut self) {
+        // UpgradeableLoaderState::ProgramData { slot, upgrade_authority_address }
+        let mut data = 3u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(self.context.payer.pubkey().as_ref());
+        let account = SolanaAccount {
+            lamports: 1_000_000_000,
+            data,
+            owner: bpf_loader_upgradeable::id(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.context
+            .set_account(&program_data_address(), &AccountSharedData::from(account));
+    }
+
+    pub fn payer(&self) -> &Keypair {
+        &self.context.payer
+    }
+
+    /// Cipher shared between the MXE and a client x25519 public key
+    pub fn shared_cipher(&self, client_pubkey: &[u8; 32]) -> TestCipher {
+        self.cipher.shared(client_pubkey)
+    }
+
+    /// Cipher for MXE-owned (sealed) state
+    pub fn mxe_cipher(&self) -> TestCipher {
+        self.cipher
+    }
+
+    /// Switch to a new MXE key, as a cluster key rotation does. The previous key
+    /// stays available to the re-encryption circuits only; every other circuit
+    /// fails to read state that hasn't been re-encrypted.
+    pub fn rotate_key(&mut self, key: [u8; 32]) {
+        self.previous_cipher = Some(self.cipher);
+        self.cipher = TestCipher { key };
+    }
+
+    /// Next allocated computation offset plus the accounts to queue it with
+    pub async fn queue_accounts(&mut self, circuit: Circuit) -> (u64, QueueAccounts) {
+        let allocator: ComputationAllocator = self.account(computation_allocator_address()).await;
+        let computation_offset = allocator.next_offset;
+
+        let accounts = QueueAccounts {
+            sign_pda_account: derive_sign_pda!(),
+            mxe_account: derive_mxe_pda!(),
+            mempool_account: derive_mempool_pda!(),
+            executing_pool: derive_execpool_pda!(),
+            computation_account: derive_comp_pda!(computation_offset),
+            comp_def_account: derive_comp_def_pda!(circuit.offset()),
+            cluster_account: self.cluster_account,
+            pool_account: ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+            clock_account: ARCIUM_CLOCK_ACCOUNT_ADDRESS,
+            arcium_program: Arcium::id(),
+            computation_allocator: computation_allocator_address(),
+            computation_record: computation_record_address(computation_offset),
+        };
+
+        (computation_offset, accounts)
+    }
+
+    pub async fn unix_timestamp(&mut self) -> i64 {
+        self.context
+            .banks_client
+            .get_sysvar::<solana_sdk::clock::Clock>()
+            .await
+            .expect("clock sysvar")
+            .unix_timestamp
+    }
+
+    pub async fn fund(&mut self, to: &Pubkey, lamports: u64) {
+        let transfer =
+            solana_sdk::system_instruction::transfer(&self.context.payer.pubkey(), to, lamports);
+        self.process(&[transfer], &[]).await.expect("fund account");
+    }
+
+    pub async fn account<T: anchor_lang::AccountDeserialize>(&mut self, address: Pubkey) -> T {
+        let account = self
+            .context
+            .banks_client
+            .get_account(address)
+            .await
+            .expect("banks client")
+            .unwrap_or_else(|| panic!("account {address} not found"));
+        T::try_deserialize(&mut account.data.as_slice()).expect("deserialize account")
+    }
+
+    /// `account`, or None if nothing is stored at `address` yet
+    pub async fn try_account<T: anchor_lang::AccountDeserialize>(
+        &mut self,
+        address: Pubkey,
+    ) -> Option<T> {
+        let account = self
+            .context
+            .banks_client
+            .get_account(address)
+            .await
+            .expect("banks client")?;
+        Some(T::try_deserialize(&mut account.data.as_slice()).expect("deserialize account"))
+    }
+
+    /// Send a transaction paid by the context payer and return the computations it queued
+    pub async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<Vec<QueuedComputation>, BanksClientError> {
+        let blockhash = self
+            .context
+            .banks_client
+            .get_latest_blockhash()
+            .await
+            .expect("blockhash");
+        let mut all_signers: Vec<&Keypair> = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.context.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+
+        let outcome = self
+            .context
+            .banks_client
+            .process_transaction_with_metadata(transaction)
+            .await?;
+        outcome.result?;
+
+        let logs = outcome
+            .metadata
+            .map(|metadata| metadata.log_messages)
+            .unwrap_or_default();
+        Ok(logs.iter().filter_map(|line| parse_queued(line)).collect())
+    }
+
+    /// Send a transaction and run every computation it queued through its callback
+    pub async fn process_and_compute(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        for computation in self.process(instructions, signers).await? {
+            self.compute(computation).await?;
+        }
+        Ok(())
+    }
+
+    /// Evaluate a queued computation and deliver its output to the callback
+    pub async fn compute(&mut self, computation: QueuedComputation) -> Result<(), BanksClientError> {
+        let mut account_data = HashMap::new();
+        for arg in &computation.args {
+            if let Argument::Account(address, _, _) = arg {
+                let account = self
+                    .context
+                    .banks_client
+                    .get_account(*address)
+                    .await?
+                    .unwrap_or_else(|| panic!("argument account {address} not found"));
+                account_data.insert(*address, account.data);
+            }
+        }
+
+        let mut reader = ArgReader {
+            args: computation.args.iter(),
+            account_data: &account_data,
+        };
+        let callback_data = self.evaluate(computation.circuit, &mut reader);
+        self.deliver(computation, callback_data).await
+    }
+
+    /// Deliver `ComputationOutputs::Failure` to the callback, as when the cluster aborts
+    pub async fn abort(&mut self, computation: QueuedComputation) -> Result<(), BanksClientError> {
+        use prediction_markets::instruction as ix;
+
+        let callback_data = match computation.circuit {
+            Circuit::SubmitPrivateVote => ix::SubmitPrivateVoteV1Callback {
+                output: ComputationOutputs::Failure,
+            }
+            .data(),
+            Circuit::AggregateMarketVotes => ix::AggregateMarketVotesV1Callback {
+                output: ComputationOutputs::Failure,
+            }
+            .data(),
+            Circuit::AggregateMarketVotesV2 => ix::AggregateMarketVotesV2Callback {
+                output: ComputationOutputs::Failure,
+            }
+            .data(),
+            Circuit::CalculatePayout => ix::CalculatePayoutV1Callback {
+                output: ComputationOutputs::Failure,
+            }
+            .data(),
+            Circuit::CalculatePayoutV2 => ix::CalculatePayoutV2Callback {
+                output: ComputationOutputs::Failure,
+            }
+            .data(),
+            Circuit::CalculateMarketOdds => ix::CalculateMarketOddsV1Callback {
+                output: ComputationOutputs::Failure,
+            }
+            .data(),
+            Circuit::UpdateReputation => ix::UpdateReputationV1Callback {
+                output: ComputationOutputs::Failure,
+            }
+            .data(),
+            Circuit::InsertLeaderboardEntry => ix::InsertLeaderboardEntryCallback {
+                output: ComputationOutputs::Failure,
+            }
+            .data(),
+            Circuit::RevealLeaderboard => ix::RevealLeaderboardCallback {
+                output: ComputationOutputs::Failure,
+            }
+            .data(),
+            Circuit::ReencryptMarketState => ix::ReencryptMarketStateV1Callback {
+                output: ComputationOutputs::Failure,
+            }
+            .data(),
+            Circuit::ReencryptPositions => ix::ReencryptPositionsV1Callback {
+                output: ComputationOutputs::Failure,
+            }
+            .data(),
+            Circuit::DiscloseVote => ix::DiscloseVoteV1Callback {
+                output: ComputationOutputs::Failure,
+            }
+            .data(),
+            Circuit::RevealVotes => ix::RevealVotesV1Callback {
+                output: ComputationOutputs::Failure,
+            }
+            .data(),
+            Circuit::CalculatePayoutHybrid => ix::CalculatePayoutHybridV1Callback {
+                output: ComputationOutputs::Failure,
+            }
+            .data(),
+            Circuit::CalculatePublicPayout => ix::CalculatePublicPayoutV1Callback {
+                output: ComputationOutputs::Failure,
+            }
+            .data(),
+            Circuit::CalculateMarketOddsHybrid => ix::CalculateMarketOddsHybridV1Callback {
+                output: ComputationOutputs::Failure,
+            }
+            .data(),
+            Circuit::CalculateParlayPayout => ix::CalculateParlayPayoutV1Callback {
+                output: ComputationOutputs::Failure,
+            }
+            .data(),
+        };
+        self.deliver(computation, callback_data).await
+    }
+
+    /// Call the program's callback through the mock Arcium program
+    async fn deliver(
+        &mut self,
+        computation: QueuedComputation,
+        callback_data: Vec<u8>,
+    ) -> Result<(), BanksClientError> {
+        let mut data = CALLBACK_TAG.to_vec();
+        data.extend_from_slice(&callback_data);
+
+        let mut accounts = vec![
+            AccountMeta::new_readonly(prediction_markets::ID, false),
+            AccountMeta::new_readonly(Arcium::id(), false),
+            AccountMeta::new_readonly(derive_comp_def_pda!(computation.circuit.offset()), false),
+            AccountMeta::new_readonly(sysvar::instructions::ID, false),
+        ];
+        accounts.extend(computation.callback_accounts);
+
+        let callback = Instruction {
+            program_id: Arcium::id(),
+            accounts,
+            data,
+        };
+        self.process(&[callback], &[]).await.map(|_| ())
+    }
+
+    fn output_nonce(&mut self) -> u128 {
+        let nonce = self.next_output_nonce;
+        self.next_output_nonce += 1;
+        nonce
+    }
+
+    fn sealed<const N: usize>(&mut self, fields: &[u128]) -> MXEEncryptedStruct<N> {
+        let nonce = self.output_nonce();
+        MXEEncryptedStruct {
+            nonce,
+            ciphertexts: to_array(self.cipher.encrypt(fields, nonce)),
+        }
+    }
+
+    fn shared<const N: usize>(
+        &self,
+        client_pubkey: [u8; 32],
+        input_nonce: u128,
+        fields: &[u128],
+    ) -> SharedEncryptedStruct<N> {
+        let nonce = input_nonce + 1;
+        SharedEncryptedStruct {
+            encryption_key: client_pubkey,
+            nonce,
+            ciphertexts: to_array(self.shared_cipher(&client_pubkey).encrypt(fields, nonce)),
+        }
+    }
+
+    /// Sealed state read from an account; an account that was never written
+    /// (nonce 0) holds zeroes rather than a ciphertext, so it reads as zero
+    fn read_sealed(&self, reader: &mut ArgReader, fields: usize) -> Vec<u128> {
+        self.read_sealed_with(self.cipher, reader, fields)
+    }
+
+    fn read_sealed_with(&self, cipher: TestCipher, reader: &mut ArgReader, fields: usize) -> Vec<u128> {
+        let nonce = reader.u128();
+        let ciphertexts = reader.account(fields);
+        if nonce == 0 {
+            vec![0; fields]
+        } else {
+            cipher.decrypt(&ciphertexts, nonce)
+        }
+    }
+
+    /// Key the re-encryption circuits decrypt under: the one before the last rotation
+    fn old_cipher(&self) -> TestCipher {
+        self.previous_cipher.unwrap_or(self.cipher)
+    }
+
+    /// Vote fields as the program passes them: pubkey, nonce, then VoteData minus voter
+    fn read_vote(&self, reader: &mut ArgReader) -> (VoteData, [u8; 32], u128) {
+        let client_pubkey = reader.pubkey();
+        let nonce = reader.u128();
+        let fields = self
+            .shared_cipher(&client_pubkey)
+            .decrypt(&reader.encrypted(7), nonce);
+        let vote = VoteData {
+            voter: [0; 32],
+            market_id: fields[0] as u64,
+            vote_choice: fields[1] as u8,
+            stake_amount: fields[2] as u64,
+            predicted_probability: fields[3] as u8,
+            conviction_score: fields[4] as u16,
+            timestamp: fields[5] as u64,
+            nonce: fields[6],
+        };
+        (vote, client_pubkey, nonce)
+    }
+
+    /// Read a shared-encrypted PayoutData: pubkey, nonce, 6 encrypted fields, then
+    /// the plaintext outcome and bonus pools
+    fn read_payout_data(&self, reader: &mut ArgReader) -> (PayoutData, [u8; 32], u128) {
+        let client_pubkey = reader.pubkey();
+        let nonce = reader.u128();
+        let fields = self
+            .shared_cipher(&client_pubkey)
+            .decrypt(&reader.encrypted(6), nonce);
+        let payout_data = PayoutData {
+            user: [0; 32],
+            market_id: fields[0] as u64,
+            user_stake: fields[1] as u64,
+            user_vote: fields[2] as u8,
+            user_probability: fields[3] as u8,
+            user_conviction: fields[4] as u16,
+            user_timestamp: fields[5] as u64,
+            market_outcome: reader.u8(),
+            accuracy_bonus: reader.u64(),
+            conviction_bonus: reader.u64(),
+        };
+        (payout_data, client_pubkey, nonce)
+    }
+
+    /// A position's stored vote, as `vote_arguments` passes it, then the plaintext
+    /// outcome and bonus pools
+    fn read_vote_payout_data(&self, reader: &mut ArgReader) -> (PayoutData, [u8; 32], u128) {
+        let (vote, client_pubkey, nonce) = self.read_vote(reader);
+        let market_outcome = reader.u8();
+        let accuracy_bonus = reader.u64();
+        let conviction_bonus = reader.u64();
+        let payout_data = circuits_reference::payout_data_for(
+            &vote,
+            market_outcome,
+            accuracy_bonus,
+            conviction_bonus,
+        );
+        (payout_data, client_pubkey, nonce)
+    }
+
+    /// A hybrid market's public tally, as `PublicTally::pool_arguments` passes it
+    fn read_public_pool(&self, reader: &mut ArgReader) -> PublicPool {
+        PublicPool {
+            total_yes_stake: reader.u64(),
+            total_no_stake: reader.u64(),
+            total_participants: reader.u32(),
+            conviction_weighted_yes: reader.u64(),
+            conviction_weighted_no: reader.u64(),
+            brier_score_sum_yes: reader.u64(),
+            brier_score_sum_no: reader.u64(),
+            time_weighted_yes_stake: reader.u64(),
+            time_weighted_no_stake: reader.u64(),
+            herding_aligned_votes: reader.u32(),
+            herding_counted_votes: reader.u32(),
+        }
+    }
+
+    fn evaluate(&mut self, circuit: Circuit, reader: &mut ArgReader) -> Vec<u8> {
+        use prediction_markets::instruction as ix;
+
+        match circuit {
+            Circuit::SubmitPrivateVote => {
+                let (vote, _, _) = self.read_vote(reader);
+                let current_time = reader.u64();
+                let stake_amount = reader.u64();
+                let is_valid =
+                    circuits_reference::submit_private_vote(&vote, current_time, stake_amount);
+
+                ix::SubmitPrivateVoteV1Callback {
+                    output: ComputationOutputs::Success(SubmitPrivateVoteV1Output {
+                        field_0: is_valid,
+                    }),
+                }
+                .data()
+            }
+            Circuit::AggregateMarketVotes => {
+                let (vote, _, _) = self.read_vote(reader);
+                let current_time = reader.u64();
+                let created_at = reader.u64();
+                let voting_ends_at = reader.u64();
+                let time_decay = reader.bool();
+                let state = market_state_from(&self.read_sealed(reader, 17));
+
+                let state = circuits_reference::aggregate_market_votes(
+                    &vote,
+                    current_time,
+                    created_at,
+                    voting_ends_at,
+                    time_decay,
+                    &state,
+                );
+
+                ix::AggregateMarketVotesV1Callback {
+                    output: ComputationOutputs::Success(AggregateMarketVotesV1Output {
+                        field_0: self.sealed(&market_state_fields(&state)),
+                    }),
+                }
+                .data()
+            }
+            Circuit::AggregateMarketVotesV2 => {
+                let (vote, _, _) = self.read_vote(reader);
+                let current_time = reader.u64();
+                let created_at = reader.u64();
+                let voting_ends_at = reader.u64();
+                let time_decay = reader.bool();
+                let root_hi = reader.u128();
+                let root_lo = reader.u128();
+                let state = market_state_from(&self.read_sealed(reader, 17));
+
+                let state = circuits_reference::aggregate_market_votes(
+                    &vote,
+                    current_time,
+                    created_at,
+                    voting_ends_at,
+                    time_decay,
+                    &state,
+                );
+
+                ix::AggregateMarketVotesV2Callback {
+                    output: ComputationOutputs::Success(AggregateMarketVotesV2Output {
+                        field_0: AggregateMarketVotesV2OutputStruct0 {
+                            field_0: self.sealed(&market_state_fields(&state)),
+                            field_1: root_hi,
+                            field_2: root_lo,
+                        },
+                    }),
+                }
+                .data()
+            }
+            Circuit::CalculatePayout => {
+                let (payout_data, client_pubkey, nonce) = self.read_payout_data(reader);
+                let created_at = reader.u64();
+                let voting_ends_at = reader.u64();
+                let time_decay = reader.bool();
+                let state = market_state_from(&self.read_sealed(reader, 17));
+
+                let payout = circuits_reference::calculate_payout(
+                    &payout_data,
+                    created_at,
+                    voting_ends_at,
+                    time_decay,
+                    &state,
+                );
+
+                ix::CalculatePayoutV1Callback {
+                    output: ComputationOutputs::Success(CalculatePayoutV1Output {
+                        field_0: self.shared(client_pubkey, nonce, &[payout as u128]),
+                    }),
+                }
+                .data()
+            }
+            Circuit::CalculatePayoutV2 => {
+                let (payout_data, client_pubkey, nonce) = self.read_vote_payout_data(reader);
+                let fee_amount = reader.u64();
+                let created_at = reader.u64();
+                let voting_ends_at = reader.u64();
+                let time_decay = reader.bool();
+                let state = market_state_from(&self.read_sealed(reader, 17));
+
+                let payout = circuits_reference::calculate_payout_after_fees(
+                    &payout_data,
+                    fee_amount,
+                    created_at,
+                    voting_ends_at,
+                    time_decay,
+                    &state,
+                );
+
+                ix::CalculatePayoutV2Callback {
+                    output: ComputationOutputs::Success(CalculatePayoutV2Output {
+                        field_0: self.shared(client_pubkey, nonce, &[payout as u128]),
+                    }),
+                }
+                .data()
+            }
+            Circuit::CalculateMarketOdds => {
+                let state = market_state_from(&self.read_sealed(reader, 17));
+                let (yes, no, participants, high_confidence, herding) =
+                    circuits_reference::calculate_market_odds(&state);
+
+                ix::CalculateMarketOddsV1Callback {
+                    output: ComputationOutputs::Success(CalculateMarketOddsV1Output {
+                        field_0: CalculateMarketOddsV1OutputStruct0 {
+                            field_0: yes,
+                            field_1: no,
+                            field_2: participants,
+                            field_3: high_confidence,
+                            field_4: herding,
+                        },
+                    }),
+                }
+                .data()
+            }
+            Circuit::UpdateReputation => {
+                let (vote, _, _) = self.read_vote(reader);
+                let payout_pubkey = reader.pubkey();
+                let payout_nonce = reader.u128();
+                let payout = self
+                    .shared_cipher(&payout_pubkey)
+                    .decrypt(&reader.encrypted(1), payout_nonce)[0] as u64;
+                let market_outcome = reader.u8();
+                let has_state = reader.bool();
+                let sealed = reputation_from(&self.read_sealed(reader, 5));
+
+                let reputation = circuits_reference::update_reputation(
+                    &vote,
+                    payout,
+                    market_outcome,
+                    has_state.then_some(&sealed),
+                );
+
+                ix::UpdateReputationV1Callback {
+                    output: ComputationOutputs::Success(UpdateReputationV1Output {
+                        field_0: self.sealed(&reputation_fields(&reputation)),
+                    }),
+                }
+                .data()
+            }
+            Circuit::InsertLeaderboardEntry => {
+                let reputation = reputation_from(&self.read_sealed(reader, 5));
+                let has_scores = reader.bool();
+                let board = leaderboard_from(&self.read_sealed(reader, LEADERBOARD_SIZE));
+
+                let (board, slot) = circuits_reference::insert_leaderboard_entry(
+                    &reputation,
+                    has_scores.then_some(&board),
+                );
+
+                ix::InsertLeaderboardEntryCallback {
+                    output: ComputationOutputs::Success(InsertLeaderboardEntryOutput {
+                        field_0: InsertLeaderboardEntryOutputStruct0 {
+                            field_0: self.sealed(&leaderboard_fields(&board)),
+                            field_1: slot,
+                        },
+                    }),
+                }
+                .data()
+            }
+            Circuit::RevealLeaderboard => {
+                let board = leaderboard_from(&self.read_sealed(reader, LEADERBOARD_SIZE));
+
+                ix::RevealLeaderboardCallback {
+                    output: ComputationOutputs::Success(RevealLeaderboardOutput {
+                        field_0: circuits_reference::reveal_leaderboard(&board),
+                    }),
+                }
+                .data()
+            }
+            Circuit::ReencryptMarketState => {
+                let old = self.old_cipher();
+                let state = market_state_from(&self.read_sealed_with(old, reader, 17));
+                let state = circuits_reference::reencrypt_market_state(&state);
+
+                ix::ReencryptMarketStateV1Callback {
+                    output: ComputationOutputs::Success(ReencryptMarketStateV1Output {
+                        field_0: self.sealed(&market_state_fields(&state)),
+                    }),
+                }
+                .data()
+            }
+            Circuit::ReencryptPositions => {
+                let old = self.old_cipher();
+                let mut owners = Vec::with_capacity(REENCRYPT_BATCH_SIZE);
+                let mut votes = Vec::with_capacity(REENCRYPT_BATCH_SIZE);
+                for _ in 0..REENCRYPT_BATCH_SIZE {
+                    let client_pubkey = reader.pubkey();
+                    let nonce = reader.u128();
+                    let fields = old.shared(&client_pubkey).decrypt(&reader.encrypted(7), nonce);
+                    owners.push((client_pubkey, nonce));
+                    votes.push(position_vote_from(&fields));
+                }
+
+                let votes: [PositionVote; REENCRYPT_BATCH_SIZE] =
+                    votes.try_into().expect("batch size");
+                let votes = circuits_reference::reencrypt_positions(&votes);
+                let mut outputs = owners
+                    .into_iter()
+                    .zip(votes.iter())
+                    .map(|((client_pubkey, nonce), vote)| {
+                        self.shared(client_pubkey, nonce, &position_vote_fields(vote))
+                    });
+
+                ix::ReencryptPositionsV1Callback {
+                    output: ComputationOutputs::Success(ReencryptPositionsV1Output {
+                        field_0: ReencryptPositionsV1OutputStruct0 {
+                            field_0: outputs.next().unwrap(),
+                            field_1: outputs.next().unwrap(),
+                            field_2: outputs.next().unwrap(),
+                            field_3: outputs.next().unwrap(),
+                        },
+                    }),
+                }
+                .data()
+            }
+            Circuit::DiscloseVote => {
+                let client_pubkey = reader.pubkey();
+                let nonce = reader.u128();
+                let fields = self
+                    .shared_cipher(&client_pubkey)
+                    .decrypt(&reader.encrypted(7), nonce);
+                let recipient_pubkey = reader.pubkey();
+                let recipient_nonce = reader.u128();
+                let vote = circuits_reference::disclose_vote(&position_vote_from(&fields));
+
+                // A Shared owner passed as an argument encrypts under its own nonce
+                ix::DiscloseVoteV1Callback {
+                    output: ComputationOutputs::Success(DiscloseVoteV1Output {
+                        field_0: self.shared(
+                            recipient_pubkey,
+                            recipient_nonce,
+                            &position_vote_fields(&vote),
+                        ),
+                    }),
+                }
+                .data()
+            }
+            Circuit::RevealVotes => {
+                let mut votes = Vec::with_capacity(REVEAL_BATCH_SIZE);
+                for _ in 0..REVEAL_BATCH_SIZE {
+                    let client_pubkey = reader.pubkey();
+                    let nonce = reader.u128();
+                    let fields = self
+                        .shared_cipher(&client_pubkey)
+                        .decrypt(&reader.encrypted(7), nonce);
+                    votes.push(position_vote_from(&fields));
+                }
+                let votes: [PositionVote; REVEAL_BATCH_SIZE] =
+                    votes.try_into().expect("batch size");
+                let (choices, probabilities, convictions) = circuits_reference::reveal_votes(&votes);
+
+                ix::RevealVotesV1Callback {
+                    output: ComputationOutputs::Success(RevealVotesV1Output {
+                        field_0: RevealVotesV1OutputStruct0 {
+                            field_0: choices,
+                            field_1: probabilities,
+                            field_2: convictions,
+                        },
+                    }),
+                }
+                .data()
+            }
+            Circuit::CalculatePayoutHybrid => {
+                let (payout_data, client_pubkey, nonce) = self.read_vote_payout_data(reader);
+                let fee_amount = reader.u64();
+                let created_at = reader.u64();
+                let voting_ends_at = reader.u64();
+                let time_decay = reader.bool();
+                let state = market_state_from(&self.read_sealed(reader, 17));
+                let pool = self.read_public_pool(reader);
+
+                let payout = circuits_reference::calculate_payout_hybrid(
+                    &payout_data,
+                    fee_amount,
+                    created_at,
+                    voting_ends_at,
+                    time_decay,
+                    &state,
+                    &pool,
+                );
+
+                ix::CalculatePayoutHybridV1Callback {
+                    output: ComputationOutputs::Success(CalculatePayoutHybridV1Output {
+                        field_0: self.shared(client_pubkey, nonce, &[payout as u128]),
+                    }),
+                }
+                .data()
+            }
+            Circuit::CalculatePublicPayout => {
+                let mut payout_data = PayoutData {
+                    user_stake: reader.u64(),
+                    user_vote: reader.u8(),
+                    user_probability: reader.u8(),
+                    user_conviction: reader.u16(),
+                    user_timestamp: reader.u64(),
+                    market_outcome: reader.u8(),
+                    accuracy_bonus: reader.u64(),
+                    conviction_bonus: reader.u64(),
+                    ..Default::default()
+                };
+                let fee_amount = reader.u64();
+                let created_at = reader.u64();
+                let voting_ends_at = reader.u64();
+                let time_decay = reader.bool();
+                let state = market_state_from(&self.read_sealed(reader, 17));
+                let pool = self.read_public_pool(reader);
+                payout_data.market_id = state.market_id;
+
+                let payout = circuits_reference::calculate_payout_hybrid(
+                    &payout_data,
+                    fee_amount,
+                    created_at,
+                    voting_ends_at,
+                    time_decay,
+                    &state,
+                    &pool,
+                );
+
+                ix::CalculatePublicPayoutV1Callback {
+                    output: ComputationOutputs::Success(CalculatePublicPayoutV1Output {
+                        field_0: payout,
+                    }),
+                }
+                .data()
+            }
+            Circuit::CalculateMarketOddsHybrid => {
+                let state = market_state_from(&self.read_sealed(reader, 17));
+                let pool = self.read_public_pool(reader);
+                let (yes, no, participants, high_confidence, herding) =
+                    circuits_reference::calculate_market_odds_hybrid(&state, &pool);
+
+                ix::CalculateMarketOddsHybridV1Callback {
+                    output: ComputationOutputs::Success(CalculateMarketOddsHybridV1Output {
+                        field_0: CalculateMarketOddsHybridV1OutputStruct0 {
+                            field_0: yes,
+                            field_1: no,
+                            field_2: participants,
+                            field_3: high_confidence,
+                            field_4: herding,
+                        },
+                    }),
+                }
+                .data()
+            }
+            Circuit::CalculateParlayPayout => {
+                let client_pubkey = reader.pubkey();
+                let nonce = reader.u128();
+                let fields = self
+                    .shared_cipher(&client_pubkey)
+                    .decrypt(&reader.encrypted(MAX_PARLAY_LEGS), nonce);
+                let picks = ParlayChoices {
+                    choices: std::array::from_fn(|leg| fields[leg] as u8),
+                };
+                let stake_amount = reader.u64();
+                let mut outcomes = [0; MAX_PARLAY_LEGS];
+                let mut odds_yes = [0; MAX_PARLAY_LEGS];
+                let mut odds_no = [0; MAX_PARLAY_LEGS];
+                for leg in 0..MAX_PARLAY_LEGS {
+                    outcomes[leg] = reader.u8();
+                    odds_yes[leg] = reader.u8();
+                    odds_no[leg] = reader.u8();
+                }
+
+                let payout = circuits_reference::calculate_parlay_payout(
+                    &picks,
+                    stake_amount,
+                    &outcomes,
+                    &odds_yes,
+                    &odds_no,
+                );
+
+                ix::CalculateParlayPayoutV1Callback {
+                    output: ComputationOutputs::Success(CalculateParlayPayoutV1Output {
+                        field_0: payout,
+                    }),
+                }
+                .data()
+            }
+        }
+    }
+}
+
+// =====================================================================
+// ARGUMENT DECODING
+// =====================================================================
+
+/// Walks a queued argument list in the order the circuit declares its inputs
+struct ArgReader<'a> {
+    args: std::slice::Iter<'a, Argument>,
+    account_data: &'a HashMap<Pubkey, Vec<u8>>,
+}
+
+impl<'a> ArgReader<'a> {
+    fn next(&mut self) -> &'a Argument {
+        self.args.next().expect("circuit read past the queued arguments")
+    }
+
+    fn pubkey(&mut self) -> [u8; 32] {
+        match self.next() {
+            Argument::ArcisPubkey(key) => *key,
+            _ => panic!("expected ArcisPubkey"),
+        }
+    }
+
+    fn u128(&mut self) -> u128 {
+        match self.next() {
+            Argument::PlaintextU128(value) => *value,
+            _ => panic!("expected PlaintextU128"),
+        }
+    }
+
+    fn u64(&mut self) -> u64 {
+        match self.next() {
+            Argument::PlaintextU64(value) => *value,
+            _ => panic!("expected PlaintextU64"),
+        }
+    }
+
+    fn u32(&mut self) -> u32 {
+        match self.next() {
+            Argument::PlaintextU32(value) => *value,
+            _ => panic!("expected PlaintextU32"),
+        }
+    }
+
+    fn u16(&mut self) -> u16 {
+        match self.next() {
+            Argument::PlaintextU16(value) => *value,
+            _ => panic!("expected PlaintextU16"),
+        }
+    }
+
+    fn u8(&mut self) -> u8 {
+        match self.next() {
+            Argument::PlaintextU8(value) => *value,
+            _ => panic!("expected PlaintextU8"),
+        }
+    }
+
+    fn bool(&mut self) -> bool {
+        match self.next() {
+            Argument::PlaintextBool(value) => *value,
+            _ => panic!("expected PlaintextBool"),
+        }
+    }
+
+    fn encrypted(&mut self, count: usize) -> Vec<[u8; 32]> {
+        (0..count)
+            .map(|_| match self.next() {
+                Argument::EncryptedU8(ciphertext)
+                | Argument::EncryptedU16(ciphertext)
+                | Argument::EncryptedU64(ciphertext)
+                | Argument::EncryptedU128(ciphertext) => *ciphertext,
+                _ => panic!("expected an encrypted field"),
+            })
+            .collect()
+    }
+
+    fn account(&mut self, fields: usize) -> Vec<[u8; 32]> {
+        match self.next() {
+            Argument::Account(address, offset, length) => {
+                assert_eq!(*length as usize, fields * 32, "account argument length");
+                let data = &self.account_data[address];
+                data[*offset as usize..(*offset + *length) as usize]
+                    .chunks(32)
+                    .map(|chunk| chunk.try_into().unwrap())
+                    .collect()
+            }
+            _ => panic!("expected Account"),
+        }
+    }
+}
+
+fn parse_queued(line: &str) -> Option<QueuedComputation> {
+    let hex = line.split(QUEUE_LOG_PREFIX).nth(1)?;
+    let data = from_hex(hex.trim());
+    let request = QueueComputation::deserialize(&mut &data[QueueComputation::DISCRIMINATOR.len()..])
+        .expect("decode queue_computation");
+
+    let circuit = Circuit::from_offset(request.computation_definition_offset)
+        .unwrap_or_else(|| panic!("unknown comp def {}", request.computation_definition_offset));
+    let callback_accounts = request
+        .custom_callback_instructions
+        .iter()
+        .flat_map(|callback| callback.accounts.iter())
+        .map(|account| AccountMeta {
+            pubkey: account.pubkey,
+            is_signer: false,
+            is_writable: account.is_writable,
+        })
+        .collect();
+
+    Some(QueuedComputation {
+        circuit,
+        computation_offset: request.comp_offset,
+        args: request.args,
+        callback_accounts,
+    })
+}
+
+// =====================================================================
+// FIELD LAYOUTS
+// =====================================================================
+
+pub fn market_state_fields(state: &MarketVotingState) -> Vec<u128> {
+    vec![
+        state.market_id as u128,
+        state.total_yes_votes as u128,
+        state.total_no_votes as u128,
+        state.total_skip_votes as u128,
+        state.total_yes_stake as u128,
+        state.total_no_stake as u128,
+        state.total_participants as u128,
+        state.weighted_probability_sum as u128,
+        state.conviction_weighted_yes as u128,
+        state.conviction_weighted_no as u128,
+        state.last_updated as u128,
+        state.brier_score_sum_yes as u128,
+        state.brier_score_sum_no as u128,
+        state.time_weighted_yes_stake as u128,
+        state.time_weighted_no_stake as u128,
+        state.herding_aligned_votes as u128,
+        state.herding_counted_votes as u128,
+    ]
+}
+
+pub fn market_state_from(fields: &[u128]) -> MarketVotingState {
+    MarketVotingState {
+        market_id: fields[0] as u64,
+        total_yes_votes: fields[1] as u32,
+        total_no_votes: fields[2] as u32,
+        total_skip_votes: fields[3] as u32,
+        total_yes_stake: fields[4] as u64,
+        total_no_stake: fields[5] as u64,
+        total_participants: fields[6] as u32,
+        weighted_probability_sum: fields[7] as u64,
+        conviction_weighted_yes: fields[8] as u64,
+        conviction_weighted_no: fields[9] as u64,
+        last_updated: fields[10] as u64,
+        brier_score_sum_yes: fields[11] as u64,
+        brier_score_sum_no: fields[12] as u64,
+        time_weighted_yes_stake: fields[13] as u64,
+        time_weighted_no_stake: fields[14] as u64,
+        herding_aligned_votes: fields[15] as u32,
+        herding_counted_votes: fields[16] as u32,
+    }
+}
+
+pub fn reputation_fields(reputation: &ReputationState) -> Vec<u128> {
+    vec![
+        reputation.correct_predictions as u128,
+        reputation.total_winnings as u128,
+        reputation.streak_current as u128,
+        reputation.streak_best as u128,
+        reputation.reputation_score as u128,
+    ]
+}
+
+pub fn reputation_from(fields: &[u128]) -> ReputationState {
+    ReputationState {
+        correct_predictions: fields[0] as u32,
+        total_winnings: fields[1] as u64,
+        streak_current: fields[2] as u16,
+        streak_best: fields[3] as u16,
+        reputation_score: fields[4] as u16,
+    }
+}
+
+pub fn leaderboard_fields(board: &LeaderboardScores) -> Vec<u128> {
+    board.scores.iter().map(|score| *score as u128).collect()
+}
+
+pub fn leaderboard_from(fields: &[u128]) -> LeaderboardScores {
+    let mut board = LeaderboardScores::default();
+    for (score, field) in board.scores.iter_mut().zip(fields) {
+        *score = *field as u16;
+    }
+    board
+}
+
+pub fn position_vote_fields(vote: &PositionVote) -> Vec<u128> {
+    vec![
+        vote.market_id as u128,
+        vote.vote_choice as u128,
+        vote.stake_amount as u128,
+        vote.predicted_probability as u128,
+        vote.conviction_score as u128,
+        vote.timestamp as u128,
+        vote.nonce,
+    ]
+}
+
+pub fn position_vote_from(fields: &[u128]) -> PositionVote {
+    PositionVote {
+        market_id: fields[0] as u64,
+        vote_choice: fields[1] as u8,
+        stake_amount: fields[2] as u64,
+        predicted_probability: fields[3] as u8,
+        conviction_score: fields[4] as u16,
+        timestamp: fields[5] as u64,
+        nonce: fields[6],
+    }
+}
+
+/// Encrypted vote fields in the order `submit_encrypted_vote` expects (voter omitted)
+pub fn vote_fields(vote: &VoteData) -> Vec<u128> {
+    vec![
+        vote.market_id as u128,
+        vote.vote_choice as u128,
+        vote.stake_amount as u128,
+        vote.predicted_probability as u128,
+        vote.conviction_score as u128,
+        vote.timestamp as u128,
+        vote.nonce,
+    ]
+}
+
+// =====================================================================
+// HELPERS
+// =====================================================================
+
+pub fn mxe_key_epoch_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"mxe_key_epoch"], &prediction_markets::ID).0
+}
+
+pub fn program_data_address() -> Pubkey {
+    Pubkey::find_program_address(&[ID.as_ref()], &bpf_loader_upgradeable::id()).0
+}
+
+pub fn program_config_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"program_config"], &prediction_markets::ID).0
+}
+
+pub fn market_registry_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"market_registry"], &prediction_markets::ID).0
+}
+
+pub fn treasury_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"treasury"], &prediction_markets::ID).0
+}
+
+pub fn parlay_pool_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"parlay_pool"], &prediction_markets::ID).0
+}
+
+pub fn computation_allocator_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"computation_allocator"], &prediction_markets::ID).0
+}
+
+pub fn computation_record_address(computation_offset: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"computation", computation_offset.to_le_bytes().as_ref()],
+        &prediction_markets::ID,
+    )
+    .0
+}
+
+/// Borsh reads an all-zero buffer as zeroes, `None`, empty vectors and first enum
+/// variants, which is all the queue and callback account constraints look at
+fn zeroed<T: AnchorDeserialize>() -> T {
+    T::deserialize(&mut &[0u8; 16 * 1024][..]).expect("zeroed Arcium account")
+}
+
+fn add_arcium_account<T: AnchorSerialize + Discriminator>(
+    program_test: &mut ProgramTest,
+    address: Pubkey,
+    value: &T,
+) {
+    let mut data = T::DISCRIMINATOR.to_vec();
+    value.serialize(&mut data).expect("serialize Arcium account");
+    program_test.add_account(
+        address,
+        SolanaAccount {
+            lamports: 1_000_000_000,
+            data,
+            owner: Arcium::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+}
+
+fn to_array<const N: usize>(ciphertexts: Vec<[u8; 32]>) -> [[u8; 32]; N] {
+    ciphertexts.try_into().expect("ciphertext count")
+}
+
+fn to_hex(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn from_hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).expect("hex log data"))
+        .collect()
+}
@@ -0,0 +1,46 @@
+// Emits the SHA-256 of every compiled circuit in build/ so the admin can register
+// it with `set_circuit_source`. Run after `arcium build`.
+//
+//   npm run circuit-hashes [-- <build dir>]
+//
+// Writes build/circuit-hashes.json: { [circuit]: { compDefOffset, hash, hashHex } }
+
+import { createHash } from "crypto";
+import * as fs from "fs";
+import * as path from "path";
+import { getCompDefAccOffset } from "@arcium-hq/client";
+
+const buildDir = process.argv[2] ?? path.join(__dirname, "..", "build");
+
+const circuits = fs
+  .readdirSync(buildDir)
+  .filter((file) => file.endsWith(".arcis"))
+  .sort();
+
+if (circuits.length === 0) {
+  console.error(`No .arcis files found in ${buildDir}`);
+  process.exit(1);
+}
+
+const hashes: Record<
+  string,
+  { compDefOffset: number; hash: number[]; hashHex: string }
+> = {};
+
+for (const file of circuits) {
+  const name = path.basename(file, ".arcis");
+  const digest = createHash("sha256")
+    .update(fs.readFileSync(path.join(buildDir, file)))
+    .digest();
+
+  hashes[name] = {
+    compDefOffset: Buffer.from(getCompDefAccOffset(name)).readUInt32LE(),
+    hash: Array.from(digest),
+    hashHex: digest.toString("hex"),
+  };
+  console.log(`${name.padEnd(32)} ${digest.toString("hex")}`);
+}
+
+const outFile = path.join(buildDir, "circuit-hashes.json");
+fs.writeFileSync(outFile, JSON.stringify(hashes, null, 2) + "\n");
+console.log(`\nWrote ${circuits.length} circuit hashes to ${outFile}`);